@@ -0,0 +1,535 @@
+use crate::db::{models::*, queries::Queries, DbPool};
+use crate::commands::workflow::sync_project_step_or_log;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tauri::State;
+use uuid::Uuid;
+use chrono::Utc;
+
+// Command Structures
+
+#[derive(Debug, Deserialize)]
+pub struct GenerateSystemDesignRequest {
+    pub project_id: String,
+    /// Restrict generation to these solutions; defaults to every selected solution
+    pub solution_ids: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TableDesign {
+    pub table: DatabaseTable,
+    pub columns: Vec<DatabaseColumn>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DataFlowDesign {
+    pub flow: DataFlow,
+    pub steps: Vec<DataFlowStep>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SystemDesignResponse {
+    pub version: i32,
+    pub architecture: Vec<SystemArchitecture>,
+    pub tables: Vec<TableDesign>,
+    pub relationships: Vec<DatabaseRelationship>,
+    pub data_flows: Vec<DataFlowDesign>,
+}
+
+const STOP_WORDS: [&str; 16] = [
+    "with", "that", "this", "from", "their", "they", "have", "into",
+    "when", "what", "which", "where", "able", "want", "more", "better",
+];
+
+// Commands
+
+/// Generate the tech stack, database schema and per-story data flows for a project
+#[tauri::command]
+pub async fn generate_system_design(
+    request: GenerateSystemDesignRequest,
+    pool: State<'_, DbPool>,
+) -> Result<SystemDesignResponse, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    generate_design(&queries, &request)
+}
+
+fn generate_design(queries: &Queries, request: &GenerateSystemDesignRequest) -> Result<SystemDesignResponse, String> {
+    let solutions: Vec<Solution> = queries
+        .get_solutions_with_mappings(&request.project_id)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(solution, _)| solution)
+        .filter(|solution| match &request.solution_ids {
+            Some(ids) => ids.contains(&solution.id),
+            None => solution.is_selected,
+        })
+        .collect();
+
+    if solutions.is_empty() {
+        return Err("Select at least one solution before generating the system design".to_string());
+    }
+
+    let user_stories = queries
+        .get_user_stories(&request.project_id)
+        .map_err(|e| e.to_string())?;
+
+    let version = queries
+        .get_latest_architecture_version(&request.project_id)
+        .map_err(|e| e.to_string())? + 1;
+
+    let architecture = generate_tech_stack(&request.project_id, version, &solutions, &user_stories);
+    let tables = generate_database_tables(&request.project_id, &solutions);
+    let relationships = generate_relationships(&request.project_id, &tables);
+    let frontend = architecture.iter()
+        .find(|layer| layer.layer == "Frontend")
+        .map(|layer| layer.technology.clone())
+        .unwrap_or_else(|| "Frontend".to_string());
+    let data_flows = generate_data_flows(&user_stories, &solutions, &tables, &frontend);
+
+    queries
+        .save_system_design(&request.project_id, &architecture, &tables, &relationships, &data_flows)
+        .map_err(|e| e.to_string())?;
+
+    sync_project_step_or_log(queries, &request.project_id, &WorkflowStep::Architecture, "system_design_generator");
+
+    queries
+        .append_state_event(&LangGraphStateEvent {
+            id: Uuid::new_v4().to_string(),
+            project_id: request.project_id.clone(),
+            event_type: "architecture_generated".to_string(),
+            event_data: serde_json::json!({
+                "version": version,
+                "techStackCount": architecture.len(),
+                "tableCount": tables.len(),
+                "dataFlowCount": data_flows.len(),
+            }),
+            event_metadata: None,
+            sequence_number: 0,
+            created_at: Utc::now(),
+            created_by: Some("system_design_generator".to_string()),
+        })
+        .map_err(|e| e.to_string())?;
+
+    Ok(SystemDesignResponse {
+        version,
+        architecture,
+        tables: tables.into_iter().map(|(table, columns)| TableDesign { table, columns }).collect(),
+        relationships,
+        data_flows: data_flows.into_iter().map(|(flow, steps)| DataFlowDesign { flow, steps }).collect(),
+    })
+}
+
+/// Load the current system design. Tables, flows and relationships are not versioned, so older
+/// tech stacks are only available through `get_architecture_version`.
+#[tauri::command]
+pub async fn get_system_design(
+    project_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<SystemDesignResponse, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    let version = queries
+        .get_latest_architecture_version(&project_id)
+        .map_err(|e| e.to_string())?;
+    let architecture = queries
+        .get_system_architecture(&project_id, Some(version))
+        .map_err(|e| e.to_string())?;
+
+    let tables = queries
+        .get_database_tables(&project_id)
+        .map_err(|e| e.to_string())?;
+    let relationships = queries
+        .get_database_relationships(&project_id)
+        .map_err(|e| e.to_string())?;
+    let data_flows = queries
+        .get_data_flows(&project_id)
+        .map_err(|e| e.to_string())?;
+
+    Ok(SystemDesignResponse {
+        version,
+        architecture,
+        tables: tables.into_iter().map(|(table, columns)| TableDesign { table, columns }).collect(),
+        relationships,
+        data_flows: data_flows.into_iter().map(|(flow, steps)| DataFlowDesign { flow, steps }).collect(),
+    })
+}
+
+/// Tech stack layers of an earlier system design version
+#[tauri::command]
+pub async fn get_architecture_version(
+    project_id: String,
+    version: i32,
+    pool: State<'_, DbPool>,
+) -> Result<Vec<SystemArchitecture>, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    queries
+        .get_system_architecture(&project_id, Some(version))
+        .map_err(|e| e.to_string())
+}
+
+// Helper functions for rule-based design generation
+
+fn generate_tech_stack(
+    project_id: &str,
+    version: i32,
+    solutions: &[Solution],
+    user_stories: &[UserStory],
+) -> Vec<SystemArchitecture> {
+    let corpus = solutions.iter()
+        .map(|s| format!("{} {}", s.title, s.description))
+        .chain(user_stories.iter().map(|us| format!("{} {} {}", us.title, us.i_want, us.so_that)))
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    let mentions = |words: &[&str]| words.iter().any(|w| corpus.contains(w));
+
+    let solution_titles = solutions.iter()
+        .map(|s| s.title.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut layers = Vec::new();
+
+    if mentions(&["mobile", "phone", "on the go"]) {
+        layers.push(("Frontend", "React Native + TypeScript",
+            format!("Stories describe mobile usage; a shared React Native codebase covers iOS and Android for {}", solution_titles)));
+    } else {
+        layers.push(("Frontend", "React + TypeScript",
+            format!("Component-driven UI with strong typing for the selected features: {}", solution_titles)));
+    }
+
+    layers.push(("Backend", "Node.js + Express",
+        format!("A thin REST API serves {} user stories with a single language across the stack", user_stories.len())));
+
+    layers.push(("Database", "PostgreSQL",
+        "Relational model with foreign keys fits the entities generated from the selected solutions".to_string()));
+
+    if mentions(&["real-time", "realtime", "alert", "notification", "notify", "live", "online"]) {
+        layers.push(("Realtime", "WebSockets + push notifications",
+            "Stories require users to be notified as soon as data changes".to_string()));
+    }
+
+    if mentions(&["analytics", "trend", "report", "insight", "dashboard", "metric"]) {
+        layers.push(("Analytics", "PostgreSQL materialized views + Recharts",
+            "Trend and reporting stories are served from pre-aggregated views and rendered as charts".to_string()));
+    }
+
+    layers.push(("DevOps", "Docker + GitHub Actions",
+        "Reproducible containers and CI on every push keep the generated repository deployable".to_string()));
+
+    layers.push(("Testing", "Cucumber (Gherkin) + Playwright",
+        "Acceptance criteria are exported as feature files and executed end to end".to_string()));
+
+    layers.into_iter()
+        .map(|(layer, technology, justification)| SystemArchitecture {
+            id: Uuid::new_v4().to_string(),
+            project_id: project_id.to_string(),
+            layer: layer.to_string(),
+            technology: technology.to_string(),
+            justification,
+            version,
+            created_at: Utc::now(),
+        })
+        .collect()
+}
+
+fn generate_database_tables(project_id: &str, solutions: &[Solution]) -> Vec<(DatabaseTable, Vec<DatabaseColumn>)> {
+    let mut tables = Vec::new();
+    let mut used_names = HashSet::new();
+
+    let users = new_table(project_id, "users");
+    let users_columns = vec![
+        new_column(&users.id, "id", "UUID", true, None, &["NOT NULL"]),
+        new_column(&users.id, "email", "VARCHAR(255)", false, None, &["NOT NULL", "UNIQUE"]),
+        new_column(&users.id, "name", "VARCHAR(255)", false, None, &[]),
+        new_column(&users.id, "created_at", "TIMESTAMP", false, None, &["NOT NULL", "DEFAULT CURRENT_TIMESTAMP"]),
+    ];
+    used_names.insert(users.table_name.clone());
+    tables.push((users, users_columns));
+
+    for solution in solutions {
+        let base_name = table_name_for(&solution.title);
+        let mut name = base_name.clone();
+        let mut suffix = 2;
+        while used_names.contains(&name) {
+            name = format!("{}_{}", base_name, suffix);
+            suffix += 1;
+        }
+        used_names.insert(name.clone());
+
+        let table = new_table(project_id, &name);
+        let columns = vec![
+            new_column(&table.id, "id", "UUID", true, None, &["NOT NULL"]),
+            new_column(&table.id, "user_id", "UUID", false, Some("users"), &["NOT NULL"]),
+            new_column(&table.id, "title", "VARCHAR(255)", false, None, &["NOT NULL"]),
+            new_column(&table.id, "details", "TEXT", false, None, &[]),
+            new_column(&table.id, "status", "VARCHAR(50)", false, None, &["NOT NULL", "DEFAULT 'active'"]),
+            new_column(&table.id, "created_at", "TIMESTAMP", false, None, &["NOT NULL", "DEFAULT CURRENT_TIMESTAMP"]),
+            new_column(&table.id, "updated_at", "TIMESTAMP", false, None, &["NOT NULL", "DEFAULT CURRENT_TIMESTAMP"]),
+        ];
+        tables.push((table, columns));
+    }
+
+    tables
+}
+
+fn generate_relationships(
+    project_id: &str,
+    tables: &[(DatabaseTable, Vec<DatabaseColumn>)],
+) -> Vec<DatabaseRelationship> {
+    tables.iter()
+        .flat_map(|(table, columns)| {
+            columns.iter()
+                .filter_map(|column| column.references_table.as_ref())
+                .map(move |referenced| DatabaseRelationship {
+                    id: Uuid::new_v4().to_string(),
+                    project_id: project_id.to_string(),
+                    from_table: referenced.clone(),
+                    to_table: table.table_name.clone(),
+                    relationship_type: "one-to-many".to_string(),
+                    created_at: Utc::now(),
+                })
+        })
+        .collect()
+}
+
+fn generate_data_flows(
+    user_stories: &[UserStory],
+    solutions: &[Solution],
+    tables: &[(DatabaseTable, Vec<DatabaseColumn>)],
+    frontend: &str,
+) -> Vec<(DataFlow, Vec<DataFlowStep>)> {
+    user_stories.iter()
+        .map(|story| {
+            let table_name = best_matching_table(story, solutions, tables);
+            let flow = DataFlow {
+                id: Uuid::new_v4().to_string(),
+                user_story_id: story.id.clone(),
+                description: format!("Data flow for '{}'", story.title),
+                created_at: Utc::now(),
+            };
+
+            let step_templates = [
+                (format!("User requests {}", story.i_want.trim_start_matches("to ")), frontend, "API",
+                    Some(serde_json::json!({ "user_id": "uuid", "action": story.title }).to_string())),
+                ("Authenticate user and validate input".to_string(), "API", "API", None),
+                (format!("Read and write {} records", table_name), "API", "Database",
+                    Some(serde_json::json!({ "table": table_name }).to_string())),
+                (format!("Return {} records", table_name), "Database", "API", None),
+                (format!("Render result so that {}", story.so_that), "API", frontend, None),
+            ];
+
+            let steps = step_templates.into_iter()
+                .enumerate()
+                .map(|(i, (action, source, target, payload))| DataFlowStep {
+                    id: Uuid::new_v4().to_string(),
+                    data_flow_id: flow.id.clone(),
+                    step_number: i as i32 + 1,
+                    action,
+                    source: source.to_string(),
+                    target: target.to_string(),
+                    data_payload: payload,
+                    created_at: Utc::now(),
+                })
+                .collect();
+
+            (flow, steps)
+        })
+        .collect()
+}
+
+/// Pick the solution table whose title and description share the most keywords with the story
+fn best_matching_table(
+    story: &UserStory,
+    solutions: &[Solution],
+    tables: &[(DatabaseTable, Vec<DatabaseColumn>)],
+) -> String {
+    let story_words = keywords(&format!("{} {} {}", story.title, story.i_want, story.so_that));
+
+    // tables[0] is `users`; solution tables follow in solution order
+    solutions.iter()
+        .zip(tables.iter().skip(1))
+        .map(|(solution, (table, _))| {
+            let solution_words = keywords(&format!("{} {}", solution.title, solution.description));
+            (story_words.intersection(&solution_words).count(), table.table_name.clone())
+        })
+        .filter(|(score, _)| *score > 0)
+        .max_by_key(|(score, _)| *score)
+        .map(|(_, name)| name)
+        .unwrap_or_else(|| "users".to_string())
+}
+
+fn keywords(text: &str) -> HashSet<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.len() > 3 && !STOP_WORDS.contains(word))
+        .map(|word| word.to_string())
+        .collect()
+}
+
+fn table_name_for(title: &str) -> String {
+    let name = title.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+
+    if name.is_empty() {
+        "records".to_string()
+    } else if name.chars().next().map(|c| c.is_ascii_digit()).unwrap_or(false) {
+        format!("t_{}", name)
+    } else {
+        name
+    }
+}
+
+fn new_table(project_id: &str, table_name: &str) -> DatabaseTable {
+    DatabaseTable {
+        id: Uuid::new_v4().to_string(),
+        project_id: project_id.to_string(),
+        table_name: table_name.to_string(),
+        created_at: Utc::now(),
+    }
+}
+
+fn new_column(
+    table_id: &str,
+    column_name: &str,
+    data_type: &str,
+    is_primary_key: bool,
+    references_table: Option<&str>,
+    constraints: &[&str],
+) -> DatabaseColumn {
+    DatabaseColumn {
+        id: Uuid::new_v4().to_string(),
+        table_id: table_id.to_string(),
+        column_name: column_name.to_string(),
+        data_type: data_type.to_string(),
+        is_primary_key,
+        is_foreign_key: references_table.is_some(),
+        references_table: references_table.map(|t| t.to_string()),
+        constraints: constraints.iter().map(|c| c.to_string()).collect(),
+        created_at: Utc::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_db;
+
+    fn solution(id: &str, title: &str, description: &str) -> Solution {
+        Solution {
+            id: id.to_string(),
+            project_id: "p1".to_string(),
+            persona_id: "pe1".to_string(),
+            title: title.to_string(),
+            description: description.to_string(),
+            solution_type: None,
+            complexity: None,
+            position: 0,
+            is_locked: false,
+            is_selected: true,
+            generation_batch: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn story(id: &str, title: &str, i_want: &str, so_that: &str) -> UserStory {
+        UserStory {
+            id: id.to_string(),
+            project_id: "p1".to_string(),
+            title: title.to_string(),
+            as_a: "clinic manager".to_string(),
+            i_want: i_want.to_string(),
+            so_that: so_that.to_string(),
+            acceptance_criteria: Vec::new(),
+            priority: None,
+            complexity_points: None,
+            position: 0,
+            is_edited: false,
+            original_content: None,
+            edited_content: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_solutions_become_tables_referencing_users() {
+        let solutions = vec![
+            solution("s1", "Appointment Reminders", "Send reminders before each appointment"),
+            solution("s2", "Appointment reminders!", "Duplicate title"),
+            solution("s3", "2FA Login", "Secure sign in"),
+        ];
+        let tables = generate_database_tables("p1", &solutions);
+        let names: Vec<&str> = tables.iter().map(|(t, _)| t.table_name.as_str()).collect();
+        assert_eq!(names, vec!["users", "appointment_reminders", "appointment_reminders_2", "t_2fa_login"]);
+
+        let (_, columns) = &tables[1];
+        let user_id = columns.iter().find(|c| c.column_name == "user_id").unwrap();
+        assert!(user_id.is_foreign_key);
+        assert_eq!(user_id.references_table.as_deref(), Some("users"));
+        assert!(columns.iter().filter(|c| c.is_primary_key).map(|c| c.column_name.as_str()).eq(["id"]));
+
+        let relationships = generate_relationships("p1", &tables);
+        assert_eq!(relationships.len(), 3);
+        assert!(relationships.iter().all(|r| r.from_table == "users" && r.relationship_type == "one-to-many"));
+        assert_eq!(relationships[0].to_table, "appointment_reminders");
+
+        let stories = vec![
+            story("us1", "Get reminded", "to receive reminders before each appointment", "I do not miss visits"),
+            story("us2", "Export", "to download everything", "I keep a copy"),
+        ];
+        let flows = generate_data_flows(&stories, &solutions, &tables, "React + TypeScript");
+        assert_eq!(flows.len(), 2);
+        assert_eq!(flows[0].1[2].action, "Read and write appointment_reminders records");
+        assert_eq!(flows[1].1[2].action, "Read and write users records");
+        assert_eq!(flows[0].1.len(), 5);
+    }
+
+    #[test]
+    fn test_regenerating_bumps_the_architecture_version() {
+        let (_pool, queries) = test_db("p1", None);
+        queries.create_core_problem(&CoreProblem {
+            id: "cp1".to_string(),
+            project_id: "p1".to_string(),
+            original_input: "Patients miss appointments".to_string(),
+            validated_problem: None,
+            is_valid: true,
+            validation_feedback: None,
+            version: 1,
+            created_at: Utc::now(),
+        }).unwrap();
+        queries.create_personas(&[Persona {
+            id: "pe1".to_string(),
+            core_problem_id: "cp1".to_string(),
+            name: "Sarah".to_string(),
+            industry: "Healthcare".to_string(),
+            role: "Clinic manager".to_string(),
+            pain_degree: 4,
+            position: 0,
+            is_locked: false,
+            is_active: true,
+            generation_batch: None,
+            created_at: Utc::now(),
+        }]).unwrap();
+        queries.create_solutions_with_mappings(&[solution("s1", "Appointment Reminders", "Notify patients")], &[]).unwrap();
+        queries.create_user_stories(&[story("us1", "Get reminded", "to receive reminders", "I do not miss visits")]).unwrap();
+        let request = GenerateSystemDesignRequest { project_id: "p1".to_string(), solution_ids: None };
+
+        let first = generate_design(&queries, &request).unwrap();
+        let second = generate_design(&queries, &request).unwrap();
+        assert_eq!((first.version, second.version), (1, 2));
+        assert_eq!(queries.get_latest_architecture_version("p1").unwrap(), 2);
+
+        let old = queries.get_system_architecture("p1", Some(1)).unwrap();
+        assert_eq!(old.len(), first.architecture.len());
+        assert!(old.iter().all(|layer| layer.version == 1));
+        assert!(first.architecture.iter().any(|layer| layer.layer == "Realtime"));
+        assert_eq!(queries.get_system_architecture("p1", Some(2)).unwrap().len(), second.architecture.len());
+        // The schema is replaced rather than versioned
+        assert_eq!(queries.get_database_tables("p1").unwrap().len(), 2);
+    }
+}
@@ -1,16 +1,34 @@
 use crate::db::{models::*, queries::Queries, DbPool};
+use crate::commands::canvas_export::{export_canvas, CanvasImageFormat};
+use crate::commands::export_history::{new_export, ExportKind, Exported};
+use crate::commands::canvas_layout::{layered_layout, LayoutOptions};
+use crate::commands::ui_state::current_ui_state;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use tauri::State;
 use uuid::Uuid;
 use chrono::Utc;
 
+const RETENTION_SETTING: &str = "canvas_autosave_retention_days";
+const DEFAULT_RETENTION_DAYS: u32 = 30;
+
 #[derive(Debug, Deserialize)]
 pub struct SaveCanvasRequest {
     pub project_id: String,
     pub nodes: JsonValue,
     pub edges: JsonValue,
     pub viewport: Option<JsonValue>,
+    pub change_description: Option<String>,
+    pub autosave: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BranchCanvasRequest {
+    pub project_id: String,
+    pub version_id: String,
+    pub branch_name: String,
+    pub change_description: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -25,7 +43,7 @@ pub struct NodePosition {
     pub y: f64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ExportCanvasRequest {
     pub project_id: String,
     pub format: String, // "png" or "svg"
@@ -50,12 +68,12 @@ impl Default for CanvasResponse {
     }
 }
 
-/// Save canvas state with validation
+/// Save canvas state as a new version with validation
 #[tauri::command]
 pub async fn save_canvas_state(
     db: State<'_, DbPool>,
     request: SaveCanvasRequest,
-) -> Result<(), String> {
+) -> Result<CanvasVersion, String> {
     let pool = db.inner();
     let queries = Queries::new(pool.clone());
     
@@ -79,10 +97,120 @@ pub async fn save_canvas_state(
         updated_at: Utc::now(),
     };
     
-    queries.save_canvas_state(&canvas_state)
+    let kind = if request.autosave.unwrap_or(false) {
+        CanvasSaveKind::Autosave
+    } else {
+        CanvasSaveKind::Manual
+    };
+    let version = queries.save_canvas_version(&canvas_state, kind, request.change_description.as_deref())
+        .map_err(|e| e.to_string())?;
+
+    // Squash old autosaves on manual saves only, so autosave stays cheap
+    if kind == CanvasSaveKind::Manual {
+        let days = retention_days(&queries, &version.project_id).map_err(|e| e.to_string())?;
+        queries.squash_canvas_autosaves(&version.project_id, days)
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(version)
+}
+
+/// List saved canvas versions, newest first, optionally for a single branch
+#[tauri::command]
+pub async fn get_canvas_history(
+    db: State<'_, DbPool>,
+    project_id: String,
+    branch_name: Option<String>,
+) -> Result<Vec<CanvasVersion>, String> {
+    let queries = Queries::new(db.inner().clone());
+
+    queries.get_canvas_history(&project_id, branch_name.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Restore an older version by saving a copy of it as the new current version
+#[tauri::command]
+pub async fn restore_canvas_version(
+    db: State<'_, DbPool>,
+    project_id: String,
+    version_id: String,
+) -> Result<CanvasVersion, String> {
+    let queries = Queries::new(db.inner().clone());
+
+    queries.restore_canvas_version(&project_id, &version_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Name the current canvas version
+#[tauri::command]
+pub async fn create_canvas_checkpoint(
+    db: State<'_, DbPool>,
+    project_id: String,
+    name: String,
+    change_description: Option<String>,
+) -> Result<CanvasVersion, String> {
+    let queries = Queries::new(db.inner().clone());
+
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Checkpoint name cannot be empty".to_string());
+    }
+
+    queries.create_canvas_checkpoint(&project_id, name, change_description.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Start a new branch from an older version; the branch becomes current
+#[tauri::command]
+pub async fn branch_canvas(
+    db: State<'_, DbPool>,
+    request: BranchCanvasRequest,
+) -> Result<CanvasVersion, String> {
+    let queries = Queries::new(db.inner().clone());
+
+    let branch_name = request.branch_name.trim();
+    if branch_name.is_empty() {
+        return Err("Branch name cannot be empty".to_string());
+    }
+
+    queries.branch_canvas_version(
+        &request.project_id,
+        &request.version_id,
+        branch_name,
+        request.change_description.as_deref(),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Squash autosaves older than the retention window. Passing `older_than_days`
+/// also stores it as the project's retention policy.
+#[tauri::command]
+pub async fn apply_canvas_retention(
+    db: State<'_, DbPool>,
+    project_id: String,
+    older_than_days: Option<u32>,
+) -> Result<usize, String> {
+    let queries = Queries::new(db.inner().clone());
+
+    let days = match older_than_days {
+        Some(days) => {
+            queries.set_project_setting(&project_id, RETENTION_SETTING, &days.to_string())
+                .map_err(|e| e.to_string())?;
+            days
+        }
+        None => retention_days(&queries, &project_id).map_err(|e| e.to_string())?,
+    };
+
+    queries.squash_canvas_autosaves(&project_id, days)
         .map_err(|e| e.to_string())
 }
 
+fn retention_days(queries: &Queries, project_id: &str) -> anyhow::Result<u32> {
+    Ok(queries.get_project_setting(project_id, RETENTION_SETTING)?
+        .and_then(|days| days.parse().ok())
+        .unwrap_or(DEFAULT_RETENTION_DAYS))
+}
+
 /// Load latest canvas state for a project
 #[tauri::command]
 pub async fn load_canvas_state(
@@ -92,116 +220,149 @@ pub async fn load_canvas_state(
     let pool = db.inner();
     let queries = Queries::new(pool.clone());
     
-    match queries.get_latest_canvas_state(&project_id) {
+    let mut response = match queries.get_latest_canvas_state(&project_id) {
         Ok(Some(canvas_state)) => {
-            Ok(CanvasResponse {
+            CanvasResponse {
                 nodes: canvas_state.nodes,
                 edges: canvas_state.edges,
                 viewport: canvas_state.viewport,
-            })
+            }
         }
         Ok(None) => {
             // Return default empty state if no canvas state exists
-            Ok(CanvasResponse::default())
+            CanvasResponse::default()
         }
-        Err(e) => Err(e.to_string()),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    // The last pan/zoom lives in the UI state, not in the versioned canvas
+    if let Some(ui_state) = current_ui_state(&queries, &project_id).map_err(|e| e.to_string())? {
+        response.viewport = Some(ui_state.viewport);
     }
+
+    Ok(response)
 }
 
-/// Export canvas as image (placeholder implementation)
+/// Export the current canvas as an image. SVG is returned as markup, PNG as base64.
 #[tauri::command]
 pub async fn export_canvas_image(
-    _db: State<'_, DbPool>,
-    _request: ExportCanvasRequest,
+    db: State<'_, DbPool>,
+    request: ExportCanvasRequest,
 ) -> Result<String, String> {
-    // For now, return error "Not implemented"
-    // Will require canvas rendering library later
-    Err("Canvas image export not implemented - requires canvas rendering library".to_string())
+    let queries = Queries::new(db.inner().clone());
+
+    Ok(export_canvas_image_with_record(&queries, request)?.output)
 }
 
-/// Calculate hierarchical layout for canvas nodes
+pub fn export_canvas_image_with_record(queries: &Queries, request: ExportCanvasRequest) -> Result<Exported<String>, String> {
+    let format = CanvasImageFormat::parse(&request.format)
+        .ok_or_else(|| format!("Unsupported image format '{}'; expected svg or png", request.format))?;
+    let image = export_canvas(queries, &request.project_id, format, request.width, request.height)
+        .map_err(|e| e.to_string())?;
+
+    let record = ExportRecord {
+        file_size_bytes: Some(image.len() as i64),
+        included_sections: vec!["canvas".to_string()],
+        ..new_export(&request.project_id, ExportKind::CanvasImage, &request.format.to_lowercase(), &request)
+            .map_err(|e| e.to_string())?
+    };
+    queries.record_export(&record).map_err(|e| e.to_string())?;
+
+    let output = match format {
+        CanvasImageFormat::Svg => String::from_utf8(image).map_err(|e| e.to_string())?,
+        CanvasImageFormat::Png => STANDARD.encode(image),
+    };
+    Ok(Exported { output, record })
+}
+
+/// Calculate a layered layout for canvas nodes from their edges
 #[tauri::command]
 pub async fn calculate_layout(
     _db: State<'_, DbPool>,
     nodes: JsonValue,
+    edges: Option<JsonValue>,
+    options: Option<LayoutOptions>,
 ) -> Result<LayoutCalculation, String> {
     if !nodes.is_array() {
         return Err("Nodes must be a valid JSON array".to_string());
     }
-    
-    let node_array = nodes.as_array().unwrap();
-    let positions = calculate_hierarchical_layout(node_array);
-    
+    let edges = edges.unwrap_or_else(|| serde_json::json!([]));
+    if !edges.is_array() {
+        return Err("Edges must be a valid JSON array".to_string());
+    }
+
+    let positions = layered_layout(
+        nodes.as_array().unwrap(),
+        edges.as_array().unwrap(),
+        &options.unwrap_or_default(),
+    );
+
     Ok(LayoutCalculation { nodes: positions })
 }
 
-/// Calculate positions based on node types in a hierarchical layout
-fn calculate_hierarchical_layout(nodes: &[JsonValue]) -> Vec<NodePosition> {
-    let mut positions = Vec::new();
-    let mut problem_nodes = Vec::new();
-    let mut persona_nodes = Vec::new();
-    let mut pain_point_nodes = Vec::new();
-    let mut solution_nodes = Vec::new();
-    
-    // Group nodes by type
-    for node in nodes {
-        if let Some(node_type) = node.get("type").and_then(|t| t.as_str()) {
-            let node_id = node.get("id").and_then(|id| id.as_str()).unwrap_or("unknown");
-            
-            match node_type {
-                "problem" => problem_nodes.push(node_id),
-                "persona" => persona_nodes.push(node_id),
-                "painPoint" => pain_point_nodes.push(node_id),
-                "solution" => solution_nodes.push(node_id),
-                _ => {} // Handle unknown node types
-            }
-        }
-    }
-    
-    // Calculate positions for each node type
-    
-    // Problem nodes: x=100, y=center
-    let problem_center_y = 300.0;
-    for (i, node_id) in problem_nodes.iter().enumerate() {
-        positions.push(NodePosition {
-            id: node_id.to_string(),
-            x: 100.0,
-            y: problem_center_y + (i as f64 * 120.0),
-        });
-    }
-    
-    // Personas: x=400, y=distributed
-    let persona_start_y = 50.0;
-    let persona_spacing = 150.0;
-    for (i, node_id) in persona_nodes.iter().enumerate() {
-        positions.push(NodePosition {
-            id: node_id.to_string(),
-            x: 400.0,
-            y: persona_start_y + (i as f64 * persona_spacing),
-        });
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_db;
+
+    fn save(queries: &Queries, nodes: JsonValue, kind: CanvasSaveKind) -> CanvasVersion {
+        let state = CanvasState {
+            id: Uuid::new_v4().to_string(),
+            project_id: "p1".to_string(),
+            nodes,
+            edges: serde_json::json!([]),
+            viewport: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        queries.save_canvas_version(&state, kind, None).unwrap()
     }
-    
-    // Pain points: x=700, y=distributed
-    let pain_start_y = 50.0;
-    let pain_spacing = 100.0;
-    for (i, node_id) in pain_point_nodes.iter().enumerate() {
-        positions.push(NodePosition {
-            id: node_id.to_string(),
-            x: 700.0,
-            y: pain_start_y + (i as f64 * pain_spacing),
-        });
+
+    #[test]
+    fn test_versions_chain_restore_and_branch() {
+        let (_pool, queries) = test_db("p1", None);
+
+        let v1 = save(&queries, serde_json::json!([{ "id": "a" }]), CanvasSaveKind::Manual);
+        let v2 = save(&queries, serde_json::json!([{ "id": "a" }, { "id": "b" }]), CanvasSaveKind::Manual);
+        let same = save(&queries, serde_json::json!([{ "id": "a" }, { "id": "b" }]), CanvasSaveKind::Autosave);
+        assert_eq!(same.id, v2.id);
+        assert_eq!(v2.parent_version_id.as_deref(), Some(v1.id.as_str()));
+
+        let restored = queries.restore_canvas_version("p1", &v1.id).unwrap();
+        assert_eq!(restored.version, 3);
+        assert_eq!(restored.node_count, 1);
+        assert_eq!(restored.parent_version_id.as_deref(), Some(v2.id.as_str()));
+
+        let branch = queries.branch_canvas_version("p1", &v2.id, "alt", None).unwrap();
+        assert_eq!(branch.branch_name, "alt");
+        assert!(queries.branch_canvas_version("p1", &v1.id, "alt", None).is_err());
+
+        let history = queries.get_canvas_history("p1", None).unwrap();
+        assert_eq!(history.iter().filter(|v| v.is_current).count(), 1);
+        assert_eq!(history[0].id, branch.id);
+        assert_eq!(queries.get_latest_canvas_state("p1").unwrap().unwrap().nodes.as_array().unwrap().len(), 2);
     }
-    
-    // Solutions: x=1000, y=distributed
-    let solution_start_y = 50.0;
-    let solution_spacing = 120.0;
-    for (i, node_id) in solution_nodes.iter().enumerate() {
-        positions.push(NodePosition {
-            id: node_id.to_string(),
-            x: 1000.0,
-            y: solution_start_y + (i as f64 * solution_spacing),
-        });
+
+    #[test]
+    fn test_squash_keeps_last_autosave_and_checkpoints() {
+        let (pool, queries) = test_db("p1", None);
+
+        let first = save(&queries, serde_json::json!([1]), CanvasSaveKind::Autosave);
+        save(&queries, serde_json::json!([2]), CanvasSaveKind::Autosave);
+        let last = save(&queries, serde_json::json!([3]), CanvasSaveKind::Autosave);
+        save(&queries, serde_json::json!([4]), CanvasSaveKind::Autosave);
+        queries.create_canvas_checkpoint("p1", "draft", None).unwrap();
+        save(&queries, serde_json::json!([5]), CanvasSaveKind::Manual);
+        pool.get().unwrap()
+            .execute("UPDATE canvas_states SET created_at = datetime('now', '-60 days')", [])
+            .unwrap();
+
+        // v1 and v2 collapse into v3; the checkpoint and the head stay
+        assert_eq!(queries.squash_canvas_autosaves("p1", 30).unwrap(), 2);
+        let history = queries.get_canvas_history("p1", None).unwrap();
+        assert_eq!(history.iter().map(|v| v.version).collect::<Vec<_>>(), vec![5, 4, 3]);
+        assert!(history.iter().all(|v| v.id != first.id));
+        assert_eq!(queries.get_canvas_version(&last.id).unwrap().unwrap().parent_version_id, None);
+        assert_eq!(queries.squash_canvas_autosaves("p1", 90).unwrap(), 0);
     }
-    
-    positions
 }
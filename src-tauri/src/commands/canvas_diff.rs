@@ -0,0 +1,448 @@
+use crate::db::{models::*, queries::Queries, DbPool};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+use std::collections::HashMap;
+use tauri::State;
+use uuid::Uuid;
+
+/// Position changes shorter than this (in canvas pixels) are not reported as moves
+const DEFAULT_MOVE_THRESHOLD: f64 = 5.0;
+/// React Flow bookkeeping that changes on every interaction and carries no meaning
+const IGNORED_KEYS: [&str; 4] = ["position", "positionAbsolute", "selected", "dragging"];
+
+#[derive(Debug, Deserialize)]
+pub struct DiffCanvasRequest {
+    pub project_id: String,
+    /// Older side of the diff; takes precedence over `since`
+    pub from_version_id: Option<String>,
+    /// Diff against the version that was current at this time, e.g. "since yesterday"
+    pub since: Option<DateTime<Utc>>,
+    /// Newer side of the diff, defaults to the current version
+    pub to_version_id: Option<String>,
+    pub move_threshold: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApplyCanvasPatchRequest {
+    pub project_id: String,
+    pub patch: CanvasPatch,
+    /// Version the patch is applied to, defaults to the current version
+    pub base_version_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CanvasDiff {
+    pub from_version: i32,
+    pub to_version: i32,
+    pub summary: CanvasDiffSummary,
+    pub patch: CanvasPatch,
+}
+
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct CanvasDiffSummary {
+    pub added_nodes: usize,
+    pub removed_nodes: usize,
+    pub moved_nodes: usize,
+    pub changed_nodes: usize,
+    pub added_edges: usize,
+    pub removed_edges: usize,
+    pub changed_edges: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CanvasPatch {
+    pub from_version_id: Option<String>,
+    pub to_version_id: Option<String>,
+    pub operations: Vec<CanvasPatchOp>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum CanvasPatchOp {
+    AddNode { node: JsonValue },
+    RemoveNode { id: String },
+    MoveNode { id: String, from: Position, to: Position },
+    UpdateNode { id: String, changes: Vec<FieldChange> },
+    AddEdge { edge: JsonValue },
+    RemoveEdge { id: String },
+    UpdateEdge { id: String, changes: Vec<FieldChange> },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// A single leaf value that differs, addressed by a dotted path such as `data.label`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FieldChange {
+    pub path: String,
+    pub old: Option<JsonValue>,
+    pub new: Option<JsonValue>,
+}
+
+/// Diff two canvas versions node by node and edge by edge
+#[tauri::command]
+pub async fn diff_canvas_versions(
+    request: DiffCanvasRequest,
+    pool: State<'_, DbPool>,
+) -> Result<CanvasDiff, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    let from = match (&request.from_version_id, request.since) {
+        (Some(id), _) => version_in_project(&queries, &request.project_id, id),
+        (None, Some(since)) => queries
+            .get_canvas_version_at(&request.project_id, since)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("No canvas version existed at {}", since.to_rfc3339())),
+        (None, None) => Err("Either from_version_id or since is required".to_string()),
+    }?;
+    let to = match &request.to_version_id {
+        Some(id) => version_in_project(&queries, &request.project_id, id)?,
+        None => current_version(&queries, &request.project_id)?,
+    };
+
+    let from_state = load_state(&queries, &from.id)?;
+    let to_state = load_state(&queries, &to.id)?;
+    let threshold = request.move_threshold.unwrap_or(DEFAULT_MOVE_THRESHOLD);
+
+    let operations = diff_canvas(&from_state, &to_state, threshold);
+    Ok(CanvasDiff {
+        from_version: from.version,
+        to_version: to.version,
+        summary: summarize(&operations),
+        patch: CanvasPatch {
+            from_version_id: Some(from.id),
+            to_version_id: Some(to.id),
+            operations,
+        },
+    })
+}
+
+/// Apply a patch on top of a version and save the result as the new current version
+#[tauri::command]
+pub async fn apply_canvas_patch(
+    request: ApplyCanvasPatchRequest,
+    pool: State<'_, DbPool>,
+) -> Result<CanvasVersion, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    let base = match &request.base_version_id {
+        Some(id) => version_in_project(&queries, &request.project_id, id)?,
+        None => current_version(&queries, &request.project_id)?,
+    };
+    let base_state = load_state(&queries, &base.id)?;
+
+    let (nodes, edges) = apply_patch(&base_state.nodes, &base_state.edges, &request.patch)
+        .map_err(|e| e.to_string())?;
+
+    let patched = CanvasState {
+        id: Uuid::new_v4().to_string(),
+        project_id: request.project_id,
+        nodes,
+        edges,
+        viewport: base_state.viewport,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    };
+    let description = format!("Applied {} change(s) on top of version {}", request.patch.operations.len(), base.version);
+
+    queries
+        .save_canvas_version(&patched, CanvasSaveKind::Manual, Some(&description))
+        .map_err(|e| e.to_string())
+}
+
+fn version_in_project(queries: &Queries, project_id: &str, id: &str) -> Result<CanvasVersion, String> {
+    queries
+        .get_canvas_version(id)
+        .map_err(|e| e.to_string())?
+        .filter(|version| version.project_id == project_id)
+        .ok_or_else(|| format!("Canvas version {} not found in project", id))
+}
+
+fn current_version(queries: &Queries, project_id: &str) -> Result<CanvasVersion, String> {
+    queries
+        .get_canvas_history(project_id, None)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|version| version.is_current)
+        .ok_or_else(|| "Project has no saved canvas".to_string())
+}
+
+fn load_state(queries: &Queries, id: &str) -> Result<CanvasState, String> {
+    queries
+        .get_canvas_state(id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Canvas version {} not found", id))
+}
+
+/// Operations that turn `from` into `to`. Moves shorter than `move_threshold` are
+/// dropped, so applying the result reproduces positions only to within that distance.
+pub fn diff_canvas(from: &CanvasState, to: &CanvasState, move_threshold: f64) -> Vec<CanvasPatchOp> {
+    let mut operations = Vec::new();
+
+    let old_nodes = index_by_id(&from.nodes, node_key);
+    let new_nodes = index_by_id(&to.nodes, node_key);
+    for (id, _) in &old_nodes {
+        if !new_nodes.iter().any(|(new_id, _)| new_id == id) {
+            operations.push(CanvasPatchOp::RemoveNode { id: id.clone() });
+        }
+    }
+    for (id, node) in &new_nodes {
+        match old_nodes.iter().find(|(old_id, _)| old_id == id) {
+            None => operations.push(CanvasPatchOp::AddNode { node: (*node).clone() }),
+            Some((_, old)) => {
+                if let (Some(from), Some(to)) = (position(old), position(node)) {
+                    if (to.x - from.x).hypot(to.y - from.y) > move_threshold {
+                        operations.push(CanvasPatchOp::MoveNode { id: id.clone(), from, to });
+                    }
+                }
+                let changes = field_changes(old, node);
+                if !changes.is_empty() {
+                    operations.push(CanvasPatchOp::UpdateNode { id: id.clone(), changes });
+                }
+            }
+        }
+    }
+
+    let old_edges = index_by_id(&from.edges, edge_key);
+    let new_edges = index_by_id(&to.edges, edge_key);
+    for (id, _) in &old_edges {
+        if !new_edges.iter().any(|(new_id, _)| new_id == id) {
+            operations.push(CanvasPatchOp::RemoveEdge { id: id.clone() });
+        }
+    }
+    for (id, edge) in &new_edges {
+        match old_edges.iter().find(|(old_id, _)| old_id == id) {
+            None => operations.push(CanvasPatchOp::AddEdge { edge: (*edge).clone() }),
+            Some((_, old)) => {
+                let changes = field_changes(old, edge);
+                if !changes.is_empty() {
+                    operations.push(CanvasPatchOp::UpdateEdge { id: id.clone(), changes });
+                }
+            }
+        }
+    }
+
+    operations
+}
+
+/// Apply `patch` to a node and edge list, failing on operations that do not fit the base
+pub fn apply_patch(nodes: &JsonValue, edges: &JsonValue, patch: &CanvasPatch) -> Result<(JsonValue, JsonValue)> {
+    let mut nodes = nodes.as_array().cloned().unwrap_or_default();
+    let mut edges = edges.as_array().cloned().unwrap_or_default();
+
+    for operation in &patch.operations {
+        match operation {
+            CanvasPatchOp::AddNode { node } => insert(&mut nodes, node, node_key, "node")?,
+            CanvasPatchOp::RemoveNode { id } => {
+                let index = position_of(&nodes, id, node_key, "node")?;
+                nodes.remove(index);
+            }
+            CanvasPatchOp::MoveNode { id, to, .. } => {
+                let index = position_of(&nodes, id, node_key, "node")?;
+                nodes[index]["position"] = serde_json::json!({ "x": to.x, "y": to.y });
+            }
+            CanvasPatchOp::UpdateNode { id, changes } => {
+                let index = position_of(&nodes, id, node_key, "node")?;
+                apply_changes(&mut nodes[index], changes);
+            }
+            CanvasPatchOp::AddEdge { edge } => insert(&mut edges, edge, edge_key, "edge")?,
+            CanvasPatchOp::RemoveEdge { id } => {
+                let index = position_of(&edges, id, edge_key, "edge")?;
+                edges.remove(index);
+            }
+            CanvasPatchOp::UpdateEdge { id, changes } => {
+                let index = position_of(&edges, id, edge_key, "edge")?;
+                apply_changes(&mut edges[index], changes);
+            }
+        }
+    }
+
+    Ok((JsonValue::Array(nodes), JsonValue::Array(edges)))
+}
+
+pub fn summarize(operations: &[CanvasPatchOp]) -> CanvasDiffSummary {
+    let mut summary = CanvasDiffSummary::default();
+    for operation in operations {
+        match operation {
+            CanvasPatchOp::AddNode { .. } => summary.added_nodes += 1,
+            CanvasPatchOp::RemoveNode { .. } => summary.removed_nodes += 1,
+            CanvasPatchOp::MoveNode { .. } => summary.moved_nodes += 1,
+            CanvasPatchOp::UpdateNode { .. } => summary.changed_nodes += 1,
+            CanvasPatchOp::AddEdge { .. } => summary.added_edges += 1,
+            CanvasPatchOp::RemoveEdge { .. } => summary.removed_edges += 1,
+            CanvasPatchOp::UpdateEdge { .. } => summary.changed_edges += 1,
+        }
+    }
+    summary
+}
+
+fn node_key(node: &JsonValue) -> Option<String> {
+    node["id"].as_str().map(str::to_string)
+}
+
+/// Edges saved without an id are identified by their endpoints
+fn edge_key(edge: &JsonValue) -> Option<String> {
+    node_key(edge).or_else(|| match (edge["source"].as_str(), edge["target"].as_str()) {
+        (Some(source), Some(target)) => Some(format!("{}->{}", source, target)),
+        _ => None,
+    })
+}
+
+/// Items in their original order; items without an identity cannot be tracked and are skipped
+fn index_by_id(items: &JsonValue, key: fn(&JsonValue) -> Option<String>) -> Vec<(String, &JsonValue)> {
+    items
+        .as_array()
+        .map(|items| items.iter().filter_map(|item| key(item).map(|id| (id, item))).collect())
+        .unwrap_or_default()
+}
+
+fn position(node: &JsonValue) -> Option<Position> {
+    Some(Position {
+        x: node["position"]["x"].as_f64()?,
+        y: node["position"]["y"].as_f64()?,
+    })
+}
+
+fn field_changes(old: &JsonValue, new: &JsonValue) -> Vec<FieldChange> {
+    let mut old_fields = HashMap::new();
+    let mut new_fields = HashMap::new();
+    flatten("", old, &mut old_fields);
+    flatten("", new, &mut new_fields);
+
+    let mut paths: Vec<&String> = old_fields.keys().chain(new_fields.keys()).collect();
+    paths.sort();
+    paths.dedup();
+
+    paths
+        .into_iter()
+        .filter(|path| old_fields.get(*path) != new_fields.get(*path))
+        .map(|path| FieldChange {
+            path: path.clone(),
+            old: old_fields.get(path).cloned(),
+            new: new_fields.get(path).cloned(),
+        })
+        .collect()
+}
+
+/// Leaf values keyed by dotted path; arrays are compared as a whole
+fn flatten(prefix: &str, value: &JsonValue, fields: &mut HashMap<String, JsonValue>) {
+    match value {
+        JsonValue::Object(map) => {
+            for (key, value) in map {
+                if prefix.is_empty() && IGNORED_KEYS.contains(&key.as_str()) {
+                    continue;
+                }
+                let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten(&path, value, fields);
+            }
+        }
+        _ => {
+            fields.insert(prefix.to_string(), value.clone());
+        }
+    }
+}
+
+fn apply_changes(item: &mut JsonValue, changes: &[FieldChange]) {
+    for change in changes {
+        let mut segments: Vec<&str> = change.path.split('.').collect();
+        let Some(last) = segments.pop() else { continue };
+
+        let mut target = &mut *item;
+        for segment in segments {
+            if !target[segment].is_object() {
+                target[segment] = JsonValue::Object(Map::new());
+            }
+            target = &mut target[segment];
+        }
+
+        match (&change.new, target.as_object_mut()) {
+            (Some(value), Some(map)) => {
+                map.insert(last.to_string(), value.clone());
+            }
+            (None, Some(map)) => {
+                map.remove(last);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn insert(items: &mut Vec<JsonValue>, item: &JsonValue, key: fn(&JsonValue) -> Option<String>, kind: &str) -> Result<()> {
+    let id = key(item).ok_or_else(|| anyhow!("Cannot add a {} without an id", kind))?;
+    if items.iter().any(|existing| key(existing).as_deref() == Some(id.as_str())) {
+        return Err(anyhow!("Cannot add {} '{}': it already exists", kind, id));
+    }
+    items.push(item.clone());
+    Ok(())
+}
+
+fn position_of(items: &[JsonValue], id: &str, key: fn(&JsonValue) -> Option<String>, kind: &str) -> Result<usize> {
+    items
+        .iter()
+        .position(|item| key(item).as_deref() == Some(id))
+        .ok_or_else(|| anyhow!("Patch refers to {} '{}', which is not on the canvas", kind, id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn state(nodes: JsonValue, edges: JsonValue) -> CanvasState {
+        CanvasState {
+            id: "c".to_string(),
+            project_id: "p".to_string(),
+            nodes,
+            edges,
+            viewport: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_patch_round_trips_between_versions() {
+        let from = state(
+            json!([
+                { "id": "a", "type": "persona", "position": { "x": 0.0, "y": 0.0 }, "data": { "label": "Sarah", "locked": false } },
+                { "id": "b", "type": "problem", "position": { "x": 100.0, "y": 0.0 }, "data": { "label": "Stock" } }
+            ]),
+            json!([{ "id": "e1", "source": "a", "target": "b" }]),
+        );
+        let to = state(
+            json!([
+                { "id": "a", "type": "persona", "position": { "x": 40.0, "y": 30.0 }, "data": { "label": "Sarah K.", "role": "Owner" } },
+                { "id": "c", "type": "solution", "position": { "x": 200.0, "y": 0.0 }, "data": { "label": "Alerts" } }
+            ]),
+            json!([{ "source": "a", "target": "c", "animated": true }]),
+        );
+
+        let operations = diff_canvas(&from, &to, DEFAULT_MOVE_THRESHOLD);
+        let summary = summarize(&operations);
+        assert_eq!(summary.added_nodes, 1);
+        assert_eq!(summary.removed_nodes, 1);
+        assert_eq!(summary.moved_nodes, 1);
+        assert_eq!(summary.changed_nodes, 1);
+        assert_eq!((summary.added_edges, summary.removed_edges), (1, 1));
+
+        let patch = CanvasPatch { from_version_id: None, to_version_id: None, operations };
+        let (nodes, edges) = apply_patch(&from.nodes, &from.edges, &patch).unwrap();
+        assert_eq!(nodes, to.nodes);
+        assert_eq!(edges, to.edges);
+        assert!(apply_patch(&to.nodes, &to.edges, &patch).is_err());
+    }
+
+    #[test]
+    fn test_small_moves_and_selection_are_ignored() {
+        let from = state(json!([{ "id": "a", "position": { "x": 0.0, "y": 0.0 }, "selected": false }]), json!([]));
+        let to = state(json!([{ "id": "a", "position": { "x": 3.0, "y": 2.0 }, "selected": true }]), json!([]));
+
+        assert!(diff_canvas(&from, &to, DEFAULT_MOVE_THRESHOLD).is_empty());
+        assert_eq!(diff_canvas(&from, &to, 1.0).len(), 1);
+    }
+}
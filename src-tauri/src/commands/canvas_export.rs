@@ -0,0 +1,349 @@
+use crate::commands::canvas_layout::{layered_layout, node_size, LayoutOptions};
+use crate::db::queries::Queries;
+use anyhow::{anyhow, Result};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+const MAX_DIMENSION: u32 = 8192;
+const PADDING: f64 = 40.0;
+const FONT_SIZE: f64 = 14.0;
+/// Rough advance of one character at `FONT_SIZE`, used to truncate labels
+const CHAR_WIDTH: f64 = 7.5;
+const SANS_SERIF_FAMILIES: [&str; 6] = ["Inter", "Helvetica", "Arial", "Liberation Sans", "DejaVu Sans", "Noto Sans"];
+
+/// Image format produced by `export_canvas`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CanvasImageFormat {
+    Svg,
+    Png,
+}
+
+impl CanvasImageFormat {
+    pub fn parse(format: &str) -> Option<Self> {
+        match format.to_lowercase().as_str() {
+            "svg" => Some(CanvasImageFormat::Svg),
+            "png" => Some(CanvasImageFormat::Png),
+            _ => None,
+        }
+    }
+}
+
+/// Render the project's current canvas without a webview
+pub fn export_canvas(
+    queries: &Queries,
+    project_id: &str,
+    format: CanvasImageFormat,
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>> {
+    if width == 0 || height == 0 || width > MAX_DIMENSION || height > MAX_DIMENSION {
+        return Err(anyhow!("Image size must be between 1 and {} pixels per side", MAX_DIMENSION));
+    }
+
+    let canvas = queries
+        .get_latest_canvas_state(project_id)?
+        .ok_or_else(|| anyhow!("Project has no saved canvas"))?;
+    let nodes = canvas.nodes.as_array().cloned().unwrap_or_default();
+    let edges = canvas.edges.as_array().cloned().unwrap_or_default();
+
+    let svg = render_canvas_svg(&nodes, &edges, width, height);
+    match format {
+        CanvasImageFormat::Svg => Ok(svg.into_bytes()),
+        CanvasImageFormat::Png => rasterize_svg(&svg, width, height),
+    }
+}
+
+/// Box of a node in canvas coordinates
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+impl Frame {
+    fn center(&self) -> (f64, f64) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+}
+
+/// Draw React Flow nodes and edges as a standalone SVG document scaled to fit `width` x `height`
+pub fn render_canvas_svg(nodes: &[JsonValue], edges: &[JsonValue], width: u32, height: u32) -> String {
+    let frames = node_frames(nodes, edges);
+
+    let (min_x, min_y, max_x, max_y) = frames.values().fold(
+        (f64::MAX, f64::MAX, f64::MIN, f64::MIN),
+        |(min_x, min_y, max_x, max_y), frame| {
+            (min_x.min(frame.x), min_y.min(frame.y), max_x.max(frame.x + frame.width), max_y.max(frame.y + frame.height))
+        },
+    );
+    let (view_x, view_y, view_width, view_height) = if frames.is_empty() {
+        (0.0, 0.0, width as f64, height as f64)
+    } else {
+        (min_x - PADDING, min_y - PADDING, max_x - min_x + PADDING * 2.0, max_y - min_y + PADDING * 2.0)
+    };
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{:.1} {:.1} {:.1} {:.1}" preserveAspectRatio="xMidYMid meet" font-family="Inter, Helvetica, Arial, sans-serif">"#,
+        width, height, view_x, view_y, view_width, view_height
+    );
+    svg.push_str(concat!(
+        "  <defs>\n",
+        r#"    <marker id="arrow" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="8" markerHeight="8" orient="auto-start-reverse">"#,
+        "\n",
+        r##"      <path d="M 0 0 L 10 5 L 0 10 z" fill="#94a3b8"/>"##,
+        "\n    </marker>\n  </defs>\n",
+    ));
+    let _ = writeln!(
+        svg,
+        r##"  <rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="#ffffff"/>"##,
+        view_x, view_y, view_width, view_height
+    );
+
+    for edge in edges {
+        let source = edge["source"].as_str().and_then(|id| frames.get(id));
+        let target = edge["target"].as_str().and_then(|id| frames.get(id));
+        if let (Some(source), Some(target)) = (source, target) {
+            render_edge(&mut svg, edge, source, target);
+        }
+    }
+
+    for node in nodes {
+        if let Some(frame) = node["id"].as_str().and_then(|id| frames.get(id)) {
+            render_node(&mut svg, node, frame);
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Rasterise an SVG document to PNG bytes with resvg
+pub fn rasterize_svg(svg: &str, width: u32, height: u32) -> Result<Vec<u8>> {
+    rasterize_svg_pixmap(svg, width, height)?
+        .encode_png()
+        .map_err(|e| anyhow!("Failed to encode PNG: {}", e))
+}
+
+/// Rasterise an SVG document to premultiplied RGBA pixels
+pub fn rasterize_svg_pixmap(svg: &str, width: u32, height: u32) -> Result<resvg::tiny_skia::Pixmap> {
+    let mut options = resvg::usvg::Options::default();
+    let fonts = options.fontdb_mut();
+    fonts.load_system_fonts();
+
+    // Headless machines rarely have Helvetica or Arial; map `sans-serif` to whatever is installed
+    let installed: Vec<String> = fonts
+        .faces()
+        .filter_map(|face| face.families.first().map(|(family, _)| family.clone()))
+        .collect();
+    let fallback = SANS_SERIF_FAMILIES
+        .iter()
+        .find(|family| installed.iter().any(|name| name == *family))
+        .map(|family| family.to_string())
+        .or_else(|| installed.first().cloned());
+    if let Some(family) = fallback {
+        fonts.set_sans_serif_family(family.clone());
+        options.font_family = family;
+    }
+
+    let tree = resvg::usvg::Tree::from_str(svg, &options)
+        .map_err(|e| anyhow!("Failed to parse canvas SVG: {}", e))?;
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(width, height)
+        .ok_or_else(|| anyhow!("Cannot allocate a {}x{} image", width, height))?;
+
+    let size = tree.size();
+    let transform = resvg::tiny_skia::Transform::from_scale(
+        width as f32 / size.width(),
+        height as f32 / size.height(),
+    );
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+    Ok(pixmap)
+}
+
+/// Absolute frames for every node; nodes without a stored position are placed by the layout engine
+fn node_frames(nodes: &[JsonValue], edges: &[JsonValue]) -> HashMap<String, Frame> {
+    let incremental = LayoutOptions { incremental: true, ..Default::default() };
+    let placed: HashMap<String, (f64, f64)> = layered_layout(nodes, edges, &incremental)
+        .into_iter()
+        .map(|position| (position.id, (position.x, position.y)))
+        .collect();
+    let by_id: HashMap<&str, &JsonValue> = nodes
+        .iter()
+        .filter_map(|node| node["id"].as_str().map(|id| (id, node)))
+        .collect();
+
+    let mut frames = HashMap::new();
+    for (id, node) in &by_id {
+        let Some(&(mut x, mut y)) = placed.get(*id) else { continue };
+
+        // Child nodes are positioned relative to their parent group
+        let mut parent = parent_id(node);
+        let mut depth = 0;
+        while let (Some(parent_key), true) = (parent, depth < 16) {
+            let Some(parent_node) = by_id.get(parent_key) else { break };
+            x += parent_node["position"]["x"].as_f64().unwrap_or(0.0);
+            y += parent_node["position"]["y"].as_f64().unwrap_or(0.0);
+            parent = parent_id(parent_node);
+            depth += 1;
+        }
+
+        let (width, height) = node_size(node);
+        frames.insert(id.to_string(), Frame { x, y, width, height });
+    }
+    frames
+}
+
+fn parent_id(node: &JsonValue) -> Option<&str> {
+    node["parentId"].as_str().or_else(|| node["parentNode"].as_str())
+}
+
+fn render_node(svg: &mut String, node: &JsonValue, frame: &Frame) {
+    let node_type = node["type"].as_str().unwrap_or("default");
+    let (fill, stroke) = match node_type {
+        "problem" => ("#fef2f2", "#ef4444"),
+        "persona" => ("#eff6ff", "#3b82f6"),
+        "painPoint" => ("#fff7ed", "#f97316"),
+        "solution" => ("#f0fdf4", "#22c55e"),
+        "group" => ("#f8fafc", "#cbd5e1"),
+        _ => ("#ffffff", "#64748b"),
+    };
+
+    let _ = writeln!(
+        svg,
+        r#"  <rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" rx="8" fill="{}" stroke="{}" stroke-width="1.5"/>"#,
+        frame.x, frame.y, frame.width, frame.height, fill, stroke
+    );
+
+    let label = ["label", "title", "name"]
+        .iter()
+        .find_map(|key| node["data"][*key].as_str())
+        .or_else(|| node["id"].as_str())
+        .unwrap_or_default();
+    let (center_x, center_y) = frame.center();
+    let _ = writeln!(
+        svg,
+        r##"  <text x="{:.1}" y="{:.1}" font-size="{}" font-weight="600" fill="#0f172a" text-anchor="middle">{}</text>"##,
+        center_x,
+        center_y + FONT_SIZE / 3.0,
+        FONT_SIZE,
+        escape_xml(&truncate(label, frame.width - 16.0))
+    );
+    if node_type != "default" && node_type != "group" {
+        let _ = writeln!(
+            svg,
+            r#"  <text x="{:.1}" y="{:.1}" font-size="11" fill="{}" text-anchor="middle">{}</text>"#,
+            center_x,
+            frame.y + frame.height - 10.0,
+            stroke,
+            escape_xml(node_type)
+        );
+    }
+}
+
+fn render_edge(svg: &mut String, edge: &JsonValue, source: &Frame, target: &Frame) {
+    let (source_x, source_y) = source.center();
+    let (target_x, target_y) = target.center();
+
+    // Leave and enter on the sides facing each other, like React Flow's default handles
+    let horizontal = (target_x - source_x).abs() >= (target_y - source_y).abs();
+    let ((start_x, start_y), (end_x, end_y)) = if horizontal {
+        let direction = (target_x - source_x).signum();
+        (
+            (source_x + direction * source.width / 2.0, source_y),
+            (target_x - direction * target.width / 2.0, target_y),
+        )
+    } else {
+        let direction = (target_y - source_y).signum();
+        (
+            (source_x, source_y + direction * source.height / 2.0),
+            (target_x, target_y - direction * target.height / 2.0),
+        )
+    };
+    let (control_1, control_2) = if horizontal {
+        let middle = (start_x + end_x) / 2.0;
+        ((middle, start_y), (middle, end_y))
+    } else {
+        let middle = (start_y + end_y) / 2.0;
+        ((start_x, middle), (end_x, middle))
+    };
+
+    let dash = if edge["animated"].as_bool().unwrap_or(false) { r#" stroke-dasharray="6 4""# } else { "" };
+    let _ = writeln!(
+        svg,
+        r##"  <path d="M {:.1} {:.1} C {:.1} {:.1}, {:.1} {:.1}, {:.1} {:.1}" fill="none" stroke="#94a3b8" stroke-width="1.5"{} marker-end="url(#arrow)"/>"##,
+        start_x, start_y, control_1.0, control_1.1, control_2.0, control_2.1, end_x, end_y, dash
+    );
+
+    if let Some(label) = edge["label"].as_str().filter(|label| !label.is_empty()) {
+        let _ = writeln!(
+            svg,
+            r##"  <text x="{:.1}" y="{:.1}" font-size="11" fill="#475569" text-anchor="middle">{}</text>"##,
+            (start_x + end_x) / 2.0,
+            (start_y + end_y) / 2.0 - 4.0,
+            escape_xml(label)
+        );
+    }
+}
+
+fn truncate(label: &str, width: f64) -> String {
+    let max_chars = ((width / CHAR_WIDTH).floor() as usize).max(4);
+    if label.chars().count() <= max_chars {
+        label.to_string()
+    } else {
+        let kept: String = label.chars().take(max_chars - 1).collect();
+        format!("{}…", kept.trim_end())
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample() -> (Vec<JsonValue>, Vec<JsonValue>) {
+        (
+            vec![
+                json!({ "id": "p", "type": "problem", "position": { "x": 0.0, "y": 0.0 }, "data": { "label": "Stock <outs> & waste" } }),
+                json!({ "id": "s", "type": "solution", "data": { "title": "Smart Inventory Alerts" } }),
+            ],
+            vec![json!({ "id": "e", "source": "p", "target": "s", "label": "solves", "animated": true })],
+        )
+    }
+
+    #[test]
+    fn test_svg_contains_escaped_labels_and_edges() {
+        let (nodes, edges) = sample();
+        let svg = render_canvas_svg(&nodes, &edges, 800, 600);
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(r#"width="800" height="600""#));
+        assert!(svg.contains("Stock &lt;outs&gt; &amp; waste"));
+        assert!(svg.contains("Smart Inventory Alerts"));
+        assert!(svg.contains("stroke-dasharray"));
+        assert!(svg.contains(">solves</text>"));
+    }
+
+    #[test]
+    fn test_png_is_rasterised_at_requested_size() {
+        let (nodes, edges) = sample();
+        let svg = render_canvas_svg(&nodes, &edges, 320, 200);
+        let png = rasterize_svg(&svg, 320, 200).unwrap();
+
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        // IHDR carries width and height as big-endian u32s
+        assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 320);
+        assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), 200);
+    }
+}
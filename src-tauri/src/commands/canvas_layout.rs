@@ -0,0 +1,524 @@
+use crate::commands::canvas::NodePosition;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+
+const DEFAULT_NODE_WIDTH: f64 = 200.0;
+const DEFAULT_NODE_HEIGHT: f64 = 100.0;
+const MARGIN: f64 = 50.0;
+const ORDERING_SWEEPS: usize = 12;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum LayoutDirection {
+    #[default]
+    #[serde(rename = "LR", alias = "left-right")]
+    LeftRight,
+    #[serde(rename = "TB", alias = "top-down")]
+    TopDown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutOptions {
+    pub direction: LayoutDirection,
+    /// Gap between neighbouring nodes in the same layer
+    pub node_spacing: f64,
+    /// Gap between consecutive layers
+    pub layer_spacing: f64,
+    /// Keep every node that already has a position where it is and only place the rest
+    pub incremental: bool,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        Self {
+            direction: LayoutDirection::LeftRight,
+            node_spacing: 40.0,
+            layer_spacing: 120.0,
+            incremental: false,
+        }
+    }
+}
+
+/// A node as the layout sees it, measured along the layer (`main`) and across it (`cross`)
+struct Vertex {
+    id: Option<String>,
+    main_size: f64,
+    cross_size: f64,
+    /// Top-left corner that must not change, in (main, cross) coordinates
+    fixed: Option<(f64, f64)>,
+    /// Position the node had before layout, used to seed the ordering
+    current_cross: Option<f64>,
+    rank_hint: usize,
+}
+
+/// Layered (Sugiyama-style) layout: break cycles, assign layers by longest path,
+/// order each layer by barycenter sweeps to reduce crossings, then assign coordinates
+/// around locked nodes. Nodes without an id are skipped; every other node gets a position.
+pub fn layered_layout(nodes: &[JsonValue], edges: &[JsonValue], options: &LayoutOptions) -> Vec<NodePosition> {
+    let (mut vertices, index) = build_vertices(nodes, options);
+    let real_count = vertices.len();
+    if real_count == 0 {
+        return Vec::new();
+    }
+
+    let mut links: Vec<(usize, usize)> = edges
+        .iter()
+        .filter_map(|edge| {
+            let source = *index.get(edge["source"].as_str()?)?;
+            let target = *index.get(edge["target"].as_str()?)?;
+            (source != target).then_some((source, target))
+        })
+        .collect();
+    links.sort_unstable();
+    links.dedup();
+
+    let mut links = break_cycles(real_count, &links);
+    links.sort_unstable();
+    links.dedup();
+    let mut layer_of = assign_layers(&vertices, &links);
+    let (layers, links) = split_long_edges(&mut vertices, &mut layer_of, &links);
+    let layers = order_layers(&vertices, layers, &links, &layer_of);
+    let coordinates = assign_coordinates(&vertices, &layers, &links, options);
+
+    let (mut min_main, mut min_cross) = (f64::MAX, f64::MAX);
+    for (main, cross) in coordinates.iter().take(real_count) {
+        min_main = min_main.min(*main);
+        min_cross = min_cross.min(*cross);
+    }
+    // Only recentre a layout that is not anchored to locked or existing nodes
+    let anchored = vertices.iter().any(|vertex| vertex.fixed.is_some());
+    let (shift_main, shift_cross) = if anchored { (0.0, 0.0) } else { (MARGIN - min_main, MARGIN - min_cross) };
+
+    (0..real_count)
+        .filter_map(|vertex| {
+            let id = vertices[vertex].id.clone()?;
+            let (main, cross) = coordinates[vertex];
+            let (main, cross) = (main + shift_main, cross + shift_cross);
+            let (x, y) = match options.direction {
+                LayoutDirection::LeftRight => (main, cross),
+                LayoutDirection::TopDown => (cross, main),
+            };
+            Some(NodePosition { id, x, y })
+        })
+        .collect()
+}
+
+/// Rendered size of a React Flow node, preferring measured dimensions
+pub fn node_size(node: &JsonValue) -> (f64, f64) {
+    let dimension = |key: &str| {
+        node["measured"][key].as_f64()
+            .or_else(|| node[key].as_f64())
+            .or_else(|| node["style"][key].as_f64())
+    };
+    (
+        dimension("width").unwrap_or(DEFAULT_NODE_WIDTH),
+        dimension("height").unwrap_or(DEFAULT_NODE_HEIGHT),
+    )
+}
+
+pub fn is_locked(node: &JsonValue) -> bool {
+    ["isLocked", "locked", "pinned"].iter().any(|key| node["data"][*key].as_bool().unwrap_or(false))
+        || node["pinned"].as_bool().unwrap_or(false)
+}
+
+/// Problem-space node types keep their natural left-to-right order when edges allow it
+fn type_rank(node: &JsonValue) -> usize {
+    match node["type"].as_str() {
+        Some("problem") => 0,
+        Some("persona") => 1,
+        Some("painPoint") => 2,
+        Some("solution") => 3,
+        _ => 0,
+    }
+}
+
+fn build_vertices(nodes: &[JsonValue], options: &LayoutOptions) -> (Vec<Vertex>, HashMap<String, usize>) {
+    let mut vertices = Vec::new();
+    let mut index = HashMap::new();
+
+    for node in nodes {
+        let Some(id) = node["id"].as_str() else { continue };
+        if index.contains_key(id) {
+            continue;
+        }
+
+        let (width, height) = node_size(node);
+        let position = match (node["position"]["x"].as_f64(), node["position"]["y"].as_f64()) {
+            (Some(x), Some(y)) => Some(to_axes(x, y, options.direction)),
+            _ => None,
+        };
+        let keep = is_locked(node) || options.incremental;
+        let (main_size, cross_size) = to_axes(width, height, options.direction);
+
+        index.insert(id.to_string(), vertices.len());
+        vertices.push(Vertex {
+            id: Some(id.to_string()),
+            main_size,
+            cross_size,
+            fixed: if keep { position } else { None },
+            current_cross: position.map(|(_, cross)| cross),
+            rank_hint: type_rank(node),
+        });
+    }
+
+    (vertices, index)
+}
+
+fn to_axes(x: f64, y: f64, direction: LayoutDirection) -> (f64, f64) {
+    match direction {
+        LayoutDirection::LeftRight => (x, y),
+        LayoutDirection::TopDown => (y, x),
+    }
+}
+
+/// Reverse the back edges found by a depth-first search so the graph becomes acyclic
+fn break_cycles(count: usize, links: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let mut outgoing = vec![Vec::new(); count];
+    for &(source, target) in links {
+        outgoing[source].push(target);
+    }
+
+    // 0 = unvisited, 1 = on the stack, 2 = done
+    let mut state = vec![0u8; count];
+    let mut reversed = Vec::new();
+    for root in 0..count {
+        if state[root] != 0 {
+            continue;
+        }
+        let mut stack = vec![(root, 0usize)];
+        state[root] = 1;
+        while let Some((vertex, next)) = stack.pop() {
+            if next < outgoing[vertex].len() {
+                stack.push((vertex, next + 1));
+                let target = outgoing[vertex][next];
+                match state[target] {
+                    0 => {
+                        state[target] = 1;
+                        stack.push((target, 0));
+                    }
+                    1 => reversed.push((vertex, target)),
+                    _ => {}
+                }
+            } else {
+                state[vertex] = 2;
+            }
+        }
+    }
+
+    links
+        .iter()
+        .map(|&link| if reversed.contains(&link) { (link.1, link.0) } else { link })
+        .collect()
+}
+
+/// Longest-path layering on top of the node type hints, with empty layers removed
+fn assign_layers(vertices: &[Vertex], links: &[(usize, usize)]) -> Vec<usize> {
+    let mut layer: Vec<usize> = vertices.iter().map(|vertex| vertex.rank_hint).collect();
+    let mut incoming = vec![0usize; vertices.len()];
+    for &(_, target) in links {
+        incoming[target] += 1;
+    }
+
+    let mut queue: Vec<usize> = (0..vertices.len()).filter(|&vertex| incoming[vertex] == 0).collect();
+    while let Some(vertex) = queue.pop() {
+        for &(source, target) in links.iter().filter(|(source, _)| *source == vertex) {
+            layer[target] = layer[target].max(layer[source] + 1);
+            incoming[target] -= 1;
+            if incoming[target] == 0 {
+                queue.push(target);
+            }
+        }
+    }
+
+    let mut used: Vec<usize> = layer.clone();
+    used.sort_unstable();
+    used.dedup();
+    layer.iter().map(|l| used.binary_search(l).unwrap_or(0)).collect()
+}
+
+/// Replace edges spanning several layers with chains through dummy vertices
+fn split_long_edges(
+    vertices: &mut Vec<Vertex>,
+    layer_of: &mut Vec<usize>,
+    links: &[(usize, usize)],
+) -> (Vec<Vec<usize>>, Vec<(usize, usize)>) {
+    let mut short_links = Vec::new();
+    for &(source, target) in links {
+        let mut previous = source;
+        for layer in layer_of[source] + 1..layer_of[target] {
+            let dummy = vertices.len();
+            vertices.push(Vertex {
+                id: None,
+                main_size: 0.0,
+                cross_size: 0.0,
+                fixed: None,
+                current_cross: None,
+                rank_hint: layer,
+            });
+            layer_of.push(layer);
+            short_links.push((previous, dummy));
+            previous = dummy;
+        }
+        short_links.push((previous, target));
+    }
+
+    let layer_count = layer_of.iter().max().map(|max| max + 1).unwrap_or(0);
+    let mut layers = vec![Vec::new(); layer_count];
+    for (vertex, &layer) in layer_of.iter().enumerate() {
+        layers[layer].push(vertex);
+    }
+
+    (layers, short_links)
+}
+
+/// Barycenter sweeps, keeping the ordering with the fewest crossings
+fn order_layers(
+    vertices: &[Vertex],
+    mut layers: Vec<Vec<usize>>,
+    links: &[(usize, usize)],
+    layer_of: &[usize],
+) -> Vec<Vec<usize>> {
+    // Seed with the existing arrangement so re-running the layout is stable
+    for layer in &mut layers {
+        layer.sort_by(|a, b| {
+            let key = |v: usize| vertices[v].fixed.map(|(_, cross)| cross).or(vertices[v].current_cross);
+            match (key(*a), key(*b)) {
+                (Some(a_key), Some(b_key)) => a_key.total_cmp(&b_key),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => a.cmp(b),
+            }
+        });
+    }
+
+    let mut best = layers.clone();
+    let mut best_crossings = count_crossings(&layers, links, layer_of);
+    for sweep in 0..ORDERING_SWEEPS {
+        let downward = sweep % 2 == 0;
+        let order: Vec<usize> = if downward {
+            (1..layers.len()).collect()
+        } else {
+            (0..layers.len().saturating_sub(1)).rev().collect()
+        };
+
+        for layer in order {
+            let reference = if downward { layer - 1 } else { layer + 1 };
+            let slot: HashMap<usize, usize> = layers[reference].iter().enumerate().map(|(i, &v)| (v, i)).collect();
+
+            let mut keyed: Vec<(f64, usize)> = layers[layer]
+                .iter()
+                .enumerate()
+                .map(|(current, &vertex)| {
+                    let neighbours: Vec<f64> = links
+                        .iter()
+                        .filter_map(|&(source, target)| match downward {
+                            true if target == vertex => slot.get(&source),
+                            false if source == vertex => slot.get(&target),
+                            _ => None,
+                        })
+                        .map(|&i| i as f64)
+                        .collect();
+                    let barycenter = if neighbours.is_empty() {
+                        current as f64
+                    } else {
+                        neighbours.iter().sum::<f64>() / neighbours.len() as f64
+                    };
+                    (barycenter, vertex)
+                })
+                .collect();
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            layers[layer] = keyed.into_iter().map(|(_, vertex)| vertex).collect();
+        }
+
+        let crossings = count_crossings(&layers, links, layer_of);
+        if crossings < best_crossings {
+            best_crossings = crossings;
+            best = layers.clone();
+        }
+    }
+
+    best
+}
+
+fn count_crossings(layers: &[Vec<usize>], links: &[(usize, usize)], layer_of: &[usize]) -> usize {
+    let mut slot = vec![0usize; layer_of.len()];
+    for layer in layers {
+        for (i, &vertex) in layer.iter().enumerate() {
+            slot[vertex] = i;
+        }
+    }
+
+    let mut crossings = 0;
+    for (i, &(a_source, a_target)) in links.iter().enumerate() {
+        for &(b_source, b_target) in &links[i + 1..] {
+            if layer_of[a_source] != layer_of[b_source] {
+                continue;
+            }
+            let sources = slot[a_source] as i64 - slot[b_source] as i64;
+            let targets = slot[a_target] as i64 - slot[b_target] as i64;
+            if sources * targets < 0 {
+                crossings += 1;
+            }
+        }
+    }
+    crossings
+}
+
+/// Top-left (main, cross) coordinates for every vertex
+fn assign_coordinates(
+    vertices: &[Vertex],
+    layers: &[Vec<usize>],
+    links: &[(usize, usize)],
+    options: &LayoutOptions,
+) -> Vec<(f64, f64)> {
+    let mut coordinates = vec![(0.0, 0.0); vertices.len()];
+
+    // Layers follow each other along the main axis; a layer holding locked nodes starts at them
+    let mut next_start = 0.0f64;
+    for layer in layers {
+        let anchored = layer.iter().filter_map(|&v| vertices[v].fixed.map(|(main, _)| main)).reduce(f64::min);
+        let start = anchored.unwrap_or(next_start);
+        let thickness = layer.iter().map(|&v| vertices[v].main_size).fold(0.0, f64::max);
+        for &vertex in layer {
+            coordinates[vertex] = vertices[vertex].fixed.unwrap_or((start, 0.0));
+        }
+        let end = layer.iter().map(|&v| coordinates[v].0 + vertices[v].main_size).fold(start + thickness, f64::max);
+        next_start = end + options.layer_spacing;
+    }
+
+    // Pull nodes towards their parents, then re-centre parents over their children
+    for layer in layers {
+        pack_layer(vertices, layer, &mut coordinates, options.node_spacing, |vertex| {
+            links.iter().filter(|(_, target)| *target == vertex).map(|(source, _)| *source).collect()
+        });
+    }
+    for layer in layers.iter().rev() {
+        pack_layer(vertices, layer, &mut coordinates, options.node_spacing, |vertex| {
+            links.iter().filter(|(source, _)| *source == vertex).map(|(_, target)| *target).collect()
+        });
+    }
+
+    coordinates
+}
+
+/// Place the movable nodes of a layer in order along the cross axis, as close to the
+/// centre of their neighbours as overlaps allow, stepping around locked nodes
+fn pack_layer(
+    vertices: &[Vertex],
+    layer: &[usize],
+    coordinates: &mut [(f64, f64)],
+    spacing: f64,
+    neighbours: impl Fn(usize) -> Vec<usize>,
+) {
+    let obstacles: Vec<(f64, f64)> = layer
+        .iter()
+        .filter_map(|&v| vertices[v].fixed.map(|(_, cross)| (cross, cross + vertices[v].cross_size)))
+        .collect();
+
+    let mut cursor = f64::MIN;
+    for &vertex in layer {
+        if vertices[vertex].fixed.is_some() {
+            continue;
+        }
+        let size = vertices[vertex].cross_size;
+        let centres: Vec<f64> = neighbours(vertex)
+            .into_iter()
+            .map(|n| coordinates[n].1 + vertices[n].cross_size / 2.0)
+            .collect();
+        let desired = if centres.is_empty() {
+            if cursor == f64::MIN { 0.0 } else { cursor }
+        } else {
+            centres.iter().sum::<f64>() / centres.len() as f64 - size / 2.0
+        };
+
+        let mut position = desired.max(cursor);
+        while let Some(&(_, end)) = obstacles
+            .iter()
+            .find(|(start, end)| position < end + spacing && position + size + spacing > *start)
+        {
+            position = end + spacing;
+        }
+
+        coordinates[vertex].1 = position;
+        // Dummy vertices only steer edges and do not take up room
+        if vertices[vertex].id.is_some() {
+            cursor = position + size + spacing;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn find(positions: &[NodePosition], id: &str) -> (f64, f64) {
+        let position = positions.iter().find(|p| p.id == id).unwrap();
+        (position.x, position.y)
+    }
+
+    #[test]
+    fn test_layers_follow_edges_and_keep_unknown_types() {
+        let nodes = vec![
+            json!({ "id": "problem", "type": "problem" }),
+            json!({ "id": "sarah", "type": "persona" }),
+            json!({ "id": "note", "type": "sticky" }),
+            json!({ "id": "alerts", "type": "solution", "measured": { "width": 320.0, "height": 90.0 } }),
+        ];
+        let edges = vec![
+            json!({ "source": "problem", "target": "sarah" }),
+            json!({ "source": "sarah", "target": "note" }),
+            json!({ "source": "alerts", "target": "problem" }),
+        ];
+
+        let positions = layered_layout(&nodes, &edges, &LayoutOptions::default());
+        assert_eq!(positions.len(), 4);
+        assert!(find(&positions, "problem").0 < find(&positions, "sarah").0);
+        assert!(find(&positions, "sarah").0 < find(&positions, "note").0);
+
+        let top_down = LayoutOptions { direction: LayoutDirection::TopDown, ..Default::default() };
+        let positions = layered_layout(&nodes, &edges, &top_down);
+        assert!(find(&positions, "problem").1 < find(&positions, "sarah").1);
+    }
+
+    #[test]
+    fn test_barycenter_ordering_removes_crossing() {
+        // a -> d and b -> c drawn in input order would cross
+        let nodes = vec![
+            json!({ "id": "a" }), json!({ "id": "b" }),
+            json!({ "id": "c" }), json!({ "id": "d" }),
+        ];
+        let edges = vec![
+            json!({ "source": "a", "target": "d" }),
+            json!({ "source": "b", "target": "c" }),
+        ];
+        let positions = layered_layout(&nodes, &edges, &LayoutOptions::default());
+        let above = |first: &str, second: &str| find(&positions, first).1 < find(&positions, second).1;
+        assert_eq!(above("a", "b"), above("d", "c"));
+    }
+
+    #[test]
+    fn test_incremental_mode_only_places_new_nodes() {
+        let nodes = vec![
+            json!({ "id": "problem", "type": "problem", "position": { "x": 100.0, "y": 300.0 } }),
+            json!({ "id": "sarah", "type": "persona", "position": { "x": 420.0, "y": 300.0 } }),
+            json!({ "id": "tom", "type": "persona" }),
+            json!({ "id": "pinned", "type": "solution", "position": { "x": 900.0, "y": 10.0 }, "data": { "isLocked": true } }),
+        ];
+        let edges = vec![
+            json!({ "source": "problem", "target": "sarah" }),
+            json!({ "source": "problem", "target": "tom" }),
+        ];
+
+        let options = LayoutOptions { incremental: true, ..Default::default() };
+        let positions = layered_layout(&nodes, &edges, &options);
+        assert_eq!(find(&positions, "problem"), (100.0, 300.0));
+        assert_eq!(find(&positions, "sarah"), (420.0, 300.0));
+        assert_eq!(find(&positions, "pinned"), (900.0, 10.0));
+
+        let (x, y) = find(&positions, "tom");
+        assert_eq!(x, 420.0);
+        assert!(y >= 300.0 + DEFAULT_NODE_HEIGHT || y + DEFAULT_NODE_HEIGHT <= 300.0);
+    }
+}
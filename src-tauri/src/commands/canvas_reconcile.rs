@@ -0,0 +1,571 @@
+use crate::commands::canvas_layout::{layered_layout, LayoutOptions};
+use crate::db::{models::*, queries::Queries, DbPool};
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+use std::collections::{HashMap, HashSet};
+use tauri::State;
+use uuid::Uuid;
+
+/// Domain tables that canvas nodes can stand for
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum EntityKind {
+    Problem,
+    Persona,
+    PainPoint,
+    Solution,
+}
+
+impl EntityKind {
+    const ALL: [EntityKind; 4] = [EntityKind::Problem, EntityKind::Persona, EntityKind::PainPoint, EntityKind::Solution];
+
+    fn from_node_type(node_type: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.node_type() == node_type)
+    }
+
+    /// React Flow node type used on the canvas
+    fn node_type(&self) -> &'static str {
+        match self {
+            EntityKind::Problem => "problem",
+            EntityKind::Persona => "persona",
+            EntityKind::PainPoint => "painPoint",
+            EntityKind::Solution => "solution",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct EntityRef {
+    pub entity_type: EntityKind,
+    pub entity_id: String,
+    pub label: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrphanNode {
+    pub node_id: String,
+    pub entity_type: EntityKind,
+    pub reference: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CanvasReconciliation {
+    pub canvas_version_id: Option<String>,
+    pub linked_nodes: usize,
+    /// Skeleton placeholders and decoration such as labels or groups
+    pub skipped_nodes: usize,
+    /// Domain-typed nodes whose row does not exist
+    pub orphan_nodes: Vec<OrphanNode>,
+    /// Domain rows that no node on the canvas stands for
+    pub missing_nodes: Vec<EntityRef>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SkippedEntity {
+    pub entity: EntityRef,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CanvasDeletionResult {
+    pub dry_run: bool,
+    pub compared_version_id: String,
+    pub deleted: Vec<EntityRef>,
+    pub skipped: Vec<SkippedEntity>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApplyCanvasDeletionsRequest {
+    pub project_id: String,
+    /// Version to compare against, defaults to the parent of the current version
+    pub since_version_id: Option<String>,
+    /// Report what would be deleted without touching the database (default true)
+    pub dry_run: Option<bool>,
+}
+
+/// Check every domain-typed node on the current canvas against the domain tables
+#[tauri::command]
+pub async fn reconcile_canvas(
+    project_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<CanvasReconciliation, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    let domain = DomainSnapshot::load(&queries, &project_id).map_err(|e| e.to_string())?;
+    let canvas = queries.get_latest_canvas_state(&project_id).map_err(|e| e.to_string())?;
+
+    let nodes = canvas.as_ref().and_then(|c| c.nodes.as_array().cloned()).unwrap_or_default();
+    Ok(reconcile(&domain, canvas.map(|c| c.id), &nodes))
+}
+
+/// Rebuild the canvas from personas, pain points and solutions, keeping the position of
+/// nodes that already stood for a row, and save it as a new version
+#[tauri::command]
+pub async fn regenerate_canvas_from_domain(
+    project_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<CanvasVersion, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    let domain = DomainSnapshot::load(&queries, &project_id).map_err(|e| e.to_string())?;
+    let current = queries.get_latest_canvas_state(&project_id).map_err(|e| e.to_string())?;
+
+    let (nodes, edges) = match &current {
+        Some(canvas) => regenerate(
+            &domain,
+            canvas.nodes.as_array().map(Vec::as_slice).unwrap_or_default(),
+            canvas.edges.as_array().map(Vec::as_slice).unwrap_or_default(),
+        ),
+        None => regenerate(&domain, &[], &[]),
+    };
+
+    let canvas_state = CanvasState {
+        id: Uuid::new_v4().to_string(),
+        project_id,
+        nodes: JsonValue::Array(nodes),
+        edges: JsonValue::Array(edges),
+        viewport: current.and_then(|canvas| canvas.viewport),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    };
+
+    queries
+        .save_canvas_version(&canvas_state, CanvasSaveKind::Manual, Some("Regenerated from domain data"))
+        .map_err(|e| e.to_string())
+}
+
+/// Delete the rows whose nodes were removed from the canvas since an earlier version.
+/// Locked rows and the core problem are never deleted, nor are personas that would take
+/// locked or still-placed pain points and solutions with them.
+#[tauri::command]
+pub async fn apply_canvas_deletions(
+    request: ApplyCanvasDeletionsRequest,
+    pool: State<'_, DbPool>,
+) -> Result<CanvasDeletionResult, String> {
+    let queries = Queries::new(pool.inner().clone());
+    let dry_run = request.dry_run.unwrap_or(true);
+
+    let current = queries
+        .get_canvas_history(&request.project_id, None)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|version| version.is_current)
+        .ok_or_else(|| "Project has no saved canvas".to_string())?;
+    let since_id = request
+        .since_version_id
+        .or(current.parent_version_id)
+        .ok_or_else(|| "The current canvas version has no earlier version to compare with".to_string())?;
+
+    let nodes_of = |id: &str| -> Result<Vec<JsonValue>, String> {
+        let state = queries
+            .get_canvas_state(id)
+            .map_err(|e| e.to_string())?
+            .filter(|state| state.project_id == request.project_id)
+            .ok_or_else(|| format!("Canvas version {} not found in project", id))?;
+        Ok(state.nodes.as_array().cloned().unwrap_or_default())
+    };
+    let before = nodes_of(&since_id)?;
+    let after = nodes_of(&current.id)?;
+
+    let domain = DomainSnapshot::load(&queries, &request.project_id).map_err(|e| e.to_string())?;
+    let still_linked: HashSet<(EntityKind, String)> = linked_entities(&domain, &after).into_iter().collect();
+    let removed: Vec<(EntityKind, String)> = linked_entities(&domain, &before)
+        .into_iter()
+        .filter(|entity| !still_linked.contains(entity))
+        .collect();
+
+    let mut result = CanvasDeletionResult {
+        dry_run,
+        compared_version_id: since_id,
+        deleted: Vec::new(),
+        skipped: Vec::new(),
+    };
+    let (mut personas, mut pain_points, mut solutions) = (Vec::new(), Vec::new(), Vec::new());
+    let mut cascaded = Vec::new();
+    for (kind, id) in removed {
+        let Some((label, locked)) = domain.describe(kind, &id) else { continue };
+        let entity = EntityRef { entity_type: kind, entity_id: id.clone(), label };
+
+        // Deleting a persona deletes its pain points and solutions with it
+        let children = match kind {
+            EntityKind::Persona => domain.persona_children(&id),
+            _ => Vec::new(),
+        };
+        let reason = match kind {
+            EntityKind::Problem => Some("The core problem cannot be deleted from the canvas"),
+            _ if locked => Some("Locked items are kept"),
+            _ if children.iter().any(|(_, locked)| *locked) => Some("It has locked pain points or solutions, which are kept"),
+            _ if children.iter().any(|(child, _)| still_linked.contains(&(child.entity_type, child.entity_id.clone()))) => {
+                Some("Its pain points or solutions are still on the canvas")
+            }
+            _ => None,
+        };
+        if let Some(reason) = reason {
+            result.skipped.push(SkippedEntity { entity, reason: reason.to_string() });
+            continue;
+        }
+
+        match kind {
+            EntityKind::Persona => personas.push(id),
+            EntityKind::PainPoint => pain_points.push(id),
+            EntityKind::Solution => solutions.push(id),
+            EntityKind::Problem => {}
+        }
+        result.deleted.push(entity);
+        cascaded.extend(children.into_iter().map(|(child, _)| child));
+    }
+    for child in cascaded {
+        let listed = result.deleted.iter()
+            .any(|entity| entity.entity_type == child.entity_type && entity.entity_id == child.entity_id);
+        if !listed {
+            result.deleted.push(child);
+        }
+    }
+
+    if !dry_run && !result.deleted.is_empty() {
+        queries
+            .delete_domain_entities(&request.project_id, &personas, &pain_points, &solutions)
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(result)
+}
+
+/// Everything on the domain side of a project, indexed by kind and id
+pub struct DomainSnapshot {
+    problem: Option<CoreProblem>,
+    personas: Vec<Persona>,
+    pain_points: Vec<PainPoint>,
+    solutions: Vec<(Solution, Vec<SolutionPainPointMapping>)>,
+}
+
+impl DomainSnapshot {
+    pub fn load(queries: &Queries, project_id: &str) -> Result<Self> {
+        queries
+            .get_project(project_id)?
+            .ok_or_else(|| anyhow!("Project not found"))?;
+
+        let problem = queries.get_latest_core_problem(project_id)?;
+        let personas = match &problem {
+            Some(problem) => queries.get_personas(&problem.id)?,
+            None => Vec::new(),
+        };
+        let mut pain_points = Vec::new();
+        for persona in &personas {
+            pain_points.extend(queries.get_pain_points(&persona.id)?);
+        }
+        let solutions = queries.get_solutions_with_mappings(project_id)?;
+
+        Ok(Self { problem, personas, pain_points, solutions })
+    }
+
+    fn entities(&self) -> Vec<EntityRef> {
+        let entity = |entity_type, id: &str, label: &str| EntityRef {
+            entity_type,
+            entity_id: id.to_string(),
+            label: label.to_string(),
+        };
+
+        let mut entities = Vec::new();
+        if let Some(problem) = &self.problem {
+            entities.push(entity(EntityKind::Problem, &problem.id, problem_label(problem)));
+        }
+        entities.extend(self.personas.iter().map(|p| entity(EntityKind::Persona, &p.id, &p.name)));
+        entities.extend(self.pain_points.iter().map(|p| entity(EntityKind::PainPoint, &p.id, &p.description)));
+        entities.extend(self.solutions.iter().map(|(s, _)| entity(EntityKind::Solution, &s.id, &s.title)));
+        entities
+    }
+
+    /// Label and lock state of a row, if it exists
+    fn describe(&self, kind: EntityKind, id: &str) -> Option<(String, bool)> {
+        match kind {
+            EntityKind::Problem => self.problem.as_ref().filter(|p| p.id == id).map(|p| (problem_label(p).to_string(), true)),
+            EntityKind::Persona => self.personas.iter().find(|p| p.id == id).map(|p| (p.name.clone(), p.is_locked)),
+            EntityKind::PainPoint => self.pain_points.iter().find(|p| p.id == id).map(|p| (p.description.clone(), p.is_locked)),
+            EntityKind::Solution => self.solutions.iter().find(|(s, _)| s.id == id).map(|(s, _)| (s.title.clone(), s.is_locked)),
+        }
+    }
+
+    /// Pain points and solutions of a persona, with whether each is locked
+    fn persona_children(&self, persona_id: &str) -> Vec<(EntityRef, bool)> {
+        let pain_points = self.pain_points.iter()
+            .filter(|p| p.persona_id == persona_id)
+            .map(|p| (EntityKind::PainPoint, &p.id, &p.description, p.is_locked));
+        let solutions = self.solutions.iter()
+            .filter(|(s, _)| s.persona_id == persona_id)
+            .map(|(s, _)| (EntityKind::Solution, &s.id, &s.title, s.is_locked));
+
+        pain_points.chain(solutions)
+            .map(|(entity_type, id, label, locked)| {
+                (EntityRef { entity_type, entity_id: id.clone(), label: label.clone() }, locked)
+            })
+            .collect()
+    }
+}
+
+fn problem_label(problem: &CoreProblem) -> &str {
+    problem.validated_problem.as_deref().unwrap_or(&problem.original_input)
+}
+
+/// Ids a node may use to point at its row, most explicit first
+fn candidate_references(node: &JsonValue, kind: EntityKind) -> Vec<String> {
+    let data = &node["data"];
+    let typed_key = format!("{}Id", kind.node_type());
+    [&data["entityId"], &data[typed_key.as_str()], &data["id"], &node["id"]]
+        .into_iter()
+        .filter_map(|value| value.as_str().map(str::to_string))
+        .collect()
+}
+
+/// Domain kind of a node, or `None` for placeholders and decoration
+fn node_kind(node: &JsonValue) -> Option<EntityKind> {
+    if node["data"]["isSkeleton"].as_bool().unwrap_or(false) {
+        return None;
+    }
+    node["type"].as_str().and_then(EntityKind::from_node_type)
+}
+
+fn resolve(domain: &DomainSnapshot, node: &JsonValue, kind: EntityKind) -> Option<String> {
+    candidate_references(node, kind)
+        .into_iter()
+        .find(|reference| domain.describe(kind, reference).is_some())
+}
+
+fn linked_entities(domain: &DomainSnapshot, nodes: &[JsonValue]) -> Vec<(EntityKind, String)> {
+    nodes
+        .iter()
+        .filter_map(|node| {
+            let kind = node_kind(node)?;
+            resolve(domain, node, kind).map(|id| (kind, id))
+        })
+        .collect()
+}
+
+pub fn reconcile(domain: &DomainSnapshot, canvas_version_id: Option<String>, nodes: &[JsonValue]) -> CanvasReconciliation {
+    let mut report = CanvasReconciliation {
+        canvas_version_id,
+        linked_nodes: 0,
+        skipped_nodes: 0,
+        orphan_nodes: Vec::new(),
+        missing_nodes: Vec::new(),
+    };
+
+    let mut linked = HashSet::new();
+    for node in nodes {
+        let Some(kind) = node_kind(node) else {
+            report.skipped_nodes += 1;
+            continue;
+        };
+        match resolve(domain, node, kind) {
+            Some(id) => {
+                report.linked_nodes += 1;
+                linked.insert((kind, id));
+            }
+            None => report.orphan_nodes.push(OrphanNode {
+                node_id: node["id"].as_str().unwrap_or_default().to_string(),
+                entity_type: kind,
+                reference: candidate_references(node, kind).into_iter().next(),
+            }),
+        }
+    }
+
+    report.missing_nodes = domain
+        .entities()
+        .into_iter()
+        .filter(|entity| !linked.contains(&(entity.entity_type, entity.entity_id.clone())))
+        .collect();
+    report
+}
+
+/// Nodes and edges for every domain row, reusing positions of nodes already linked to a row
+/// and keeping decoration; orphaned domain-typed nodes are dropped
+pub fn regenerate(domain: &DomainSnapshot, nodes: &[JsonValue], edges: &[JsonValue]) -> (Vec<JsonValue>, Vec<JsonValue>) {
+    let mut positions: HashMap<(EntityKind, String), JsonValue> = HashMap::new();
+    let mut kept_nodes = Vec::new();
+    for node in nodes {
+        match node["type"].as_str().and_then(EntityKind::from_node_type) {
+            Some(kind) => {
+                if let Some(id) = resolve(domain, node, kind) {
+                    positions.insert((kind, id), node["position"].clone());
+                }
+            }
+            None => kept_nodes.push(node.clone()),
+        }
+    }
+    let kept_ids: HashSet<&str> = kept_nodes.iter().filter_map(|node| node["id"].as_str()).collect();
+    let mut new_edges: Vec<JsonValue> = edges
+        .iter()
+        .filter(|edge| {
+            kept_ids.contains(edge["source"].as_str().unwrap_or_default())
+                && kept_ids.contains(edge["target"].as_str().unwrap_or_default())
+        })
+        .cloned()
+        .collect();
+
+    let mut new_nodes = Vec::new();
+    let mut push_node = |kind: EntityKind, id: &str, data: JsonValue| {
+        let mut node = json!({
+            "id": node_id(kind, id),
+            "type": kind.node_type(),
+            "data": data,
+        });
+        node["data"]["entityId"] = json!(id);
+        if let Some(position) = positions.get(&(kind, id.to_string())).filter(|p| p.is_object()) {
+            node["position"] = position.clone();
+        }
+        new_nodes.push(node);
+    };
+
+    if let Some(problem) = &domain.problem {
+        push_node(EntityKind::Problem, &problem.id, json!({
+            "title": "Core Problem",
+            "description": problem_label(problem),
+            "isLocked": true,
+        }));
+    }
+    for persona in &domain.personas {
+        push_node(EntityKind::Persona, &persona.id, json!({
+            "name": persona.name,
+            "industry": persona.industry,
+            "role": persona.role,
+            "painDegree": persona.pain_degree,
+            "isLocked": persona.is_locked,
+        }));
+    }
+    for pain_point in &domain.pain_points {
+        push_node(EntityKind::PainPoint, &pain_point.id, json!({
+            "description": pain_point.description,
+            "severity": pain_point.severity,
+            "impactArea": pain_point.impact_area,
+            "isLocked": pain_point.is_locked,
+        }));
+    }
+    for (solution, _) in &domain.solutions {
+        push_node(EntityKind::Solution, &solution.id, json!({
+            "title": solution.title,
+            "description": solution.description,
+            "solutionType": solution.solution_type,
+            "complexity": solution.complexity,
+            "isLocked": solution.is_locked,
+            "isSelected": solution.is_selected,
+        }));
+    }
+
+    let mut link = |source: String, target: String| {
+        new_edges.push(json!({ "id": format!("{}->{}", source, target), "source": source, "target": target }));
+    };
+    if let Some(problem) = &domain.problem {
+        for persona in &domain.personas {
+            link(node_id(EntityKind::Problem, &problem.id), node_id(EntityKind::Persona, &persona.id));
+        }
+    }
+    for pain_point in &domain.pain_points {
+        link(node_id(EntityKind::Persona, &pain_point.persona_id), node_id(EntityKind::PainPoint, &pain_point.id));
+    }
+    let pain_point_ids: HashSet<&str> = domain.pain_points.iter().map(|p| p.id.as_str()).collect();
+    for (solution, mappings) in &domain.solutions {
+        let mapped: Vec<&str> = mappings
+            .iter()
+            .map(|mapping| mapping.pain_point_id.as_str())
+            .filter(|id| pain_point_ids.contains(id))
+            .collect();
+        if mapped.is_empty() {
+            link(node_id(EntityKind::Persona, &solution.persona_id), node_id(EntityKind::Solution, &solution.id));
+        }
+        for pain_point_id in mapped {
+            link(node_id(EntityKind::PainPoint, pain_point_id), node_id(EntityKind::Solution, &solution.id));
+        }
+    }
+
+    new_nodes.extend(kept_nodes);
+
+    // Place nodes that had no position before
+    let incremental = LayoutOptions { incremental: true, ..Default::default() };
+    let layout: HashMap<String, (f64, f64)> = layered_layout(&new_nodes, &new_edges, &incremental)
+        .into_iter()
+        .map(|position| (position.id, (position.x, position.y)))
+        .collect();
+    for node in &mut new_nodes {
+        if !node["position"].is_object() {
+            let (x, y) = node["id"].as_str().and_then(|id| layout.get(id)).copied().unwrap_or((0.0, 0.0));
+            node["position"] = json!({ "x": x, "y": y });
+        }
+    }
+
+    (new_nodes, new_edges)
+}
+
+fn node_id(kind: EntityKind, id: &str) -> String {
+    format!("{}-{}", kind.node_type(), id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> DomainSnapshot {
+        let persona = PersonaBuilder::new("cp1".to_string(), "Sarah".to_string()).build();
+        let pain_point = PainPoint {
+            id: "pp1".to_string(),
+            persona_id: persona.id.clone(),
+            description: "Stock runs out".to_string(),
+            severity: Some("high".to_string()),
+            impact_area: None,
+            position: 0,
+            is_locked: false,
+            generation_batch: None,
+            created_at: Utc::now(),
+        };
+        let solution = SolutionBuilder::new("p1".to_string(), persona.id.clone(), "Alerts".to_string()).build();
+
+        DomainSnapshot {
+            problem: Some(CoreProblem { id: "cp1".to_string(), project_id: "p1".to_string(), original_input: "Inventory".to_string(), ..Default::default() }),
+            personas: vec![persona],
+            pain_points: vec![pain_point],
+            solutions: vec![(solution, Vec::new())],
+        }
+    }
+
+    #[test]
+    fn test_reports_orphans_in_both_directions() {
+        let domain = snapshot();
+        let persona_id = domain.personas[0].id.clone();
+        let nodes = vec![
+            json!({ "id": "problem", "type": "problem", "data": { "entityId": "cp1" } }),
+            json!({ "id": "persona-1", "type": "persona", "data": { "id": persona_id } }),
+            json!({ "id": "painPoint-gone", "type": "painPoint", "data": {} }),
+            json!({ "id": "persona-2", "type": "persona", "data": { "isSkeleton": true } }),
+            json!({ "id": "label", "type": "label", "data": { "text": "Personas" } }),
+        ];
+
+        let report = reconcile(&domain, None, &nodes);
+        assert_eq!(report.linked_nodes, 2);
+        assert_eq!(report.skipped_nodes, 2);
+        assert_eq!(report.orphan_nodes.len(), 1);
+        assert_eq!(report.orphan_nodes[0].reference.as_deref(), Some("painPoint-gone"));
+        let missing: Vec<EntityKind> = report.missing_nodes.iter().map(|e| e.entity_type).collect();
+        assert_eq!(missing, vec![EntityKind::PainPoint, EntityKind::Solution]);
+    }
+
+    #[test]
+    fn test_regenerate_links_every_row_and_keeps_positions() {
+        let domain = snapshot();
+        let persona_id = domain.personas[0].id.clone();
+        let nodes = vec![
+            json!({ "id": "old", "type": "persona", "position": { "x": 7.0, "y": 9.0 }, "data": { "entityId": persona_id } }),
+            json!({ "id": "stale", "type": "solution", "position": { "x": 0.0, "y": 0.0 }, "data": {} }),
+        ];
+
+        let (nodes, edges) = regenerate(&domain, &nodes, &[]);
+        assert_eq!(nodes.len(), 4);
+        assert_eq!(edges.len(), 3);
+        let persona = nodes.iter().find(|n| n["type"] == "persona").unwrap();
+        assert_eq!(persona["position"], json!({ "x": 7.0, "y": 9.0 }));
+        assert!(nodes.iter().all(|n| n["position"].is_object()));
+
+        let report = reconcile(&domain, None, &nodes);
+        assert!(report.orphan_nodes.is_empty() && report.missing_nodes.is_empty());
+    }
+}
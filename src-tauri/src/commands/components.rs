@@ -0,0 +1,591 @@
+use crate::db::{models::*, queries::Queries, DbPool};
+use crate::commands::workspace::{resolve_project_folder, slugify};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use tauri::State;
+
+const COMPONENTS_DIR: &str = "src/components";
+
+/// Atomic design levels from smallest to largest
+const LEVELS: [&str; 5] = ["atom", "molecule", "organism", "template", "page"];
+
+#[derive(Debug, Serialize)]
+pub struct ComponentTreeReport {
+    pub components: Vec<ComponentNode>,
+    pub issues: Vec<ComponentIssue>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ComponentNode {
+    pub id: String,
+    pub name: String,
+    pub level: String,
+    pub children: Vec<String>, // ids of resolved components
+    pub used_by: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ComponentIssue {
+    pub severity: String, // "error" or "warning"
+    pub component: String,
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScaffoldComponentsRequest {
+    pub project_id: String,
+    pub overwrite: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScaffoldComponentsResponse {
+    pub components_dir: String,
+    pub files_written: Vec<String>,
+    pub skipped_existing: Vec<String>,
+    pub issues: Vec<ComponentIssue>,
+}
+
+/// Composition graph built from `composed_of`, which may hold component names or ids
+struct ComponentGraph<'a> {
+    components: &'a [AtomicComponent],
+    levels: Vec<Option<usize>>,
+    children: Vec<Vec<usize>>,
+}
+
+impl<'a> ComponentGraph<'a> {
+    fn build(components: &'a [AtomicComponent], issues: &mut Vec<ComponentIssue>) -> Self {
+        let mut by_key: HashMap<String, usize> = HashMap::new();
+        for (index, component) in components.iter().enumerate() {
+            let name_key = component.component_name.trim().to_lowercase();
+            match by_key.entry(name_key) {
+                Entry::Occupied(_) => issues.push(issue("error", component, format!(
+                    "Component name '{}' is used more than once", component.component_name
+                ))),
+                Entry::Vacant(entry) => {
+                    entry.insert(index);
+                }
+            }
+            by_key.entry(component.id.clone()).or_insert(index);
+        }
+
+        let levels = components.iter()
+            .map(|component| {
+                let level = level_rank(&component.component_level);
+                if level.is_none() {
+                    issues.push(issue("error", component, format!(
+                        "Unknown component level '{}'; expected one of {}",
+                        component.component_level, LEVELS.join(", ")
+                    )));
+                }
+                level
+            })
+            .collect();
+
+        let children = components.iter()
+            .map(|component| {
+                let mut resolved = Vec::new();
+                for reference in &component.composed_of {
+                    match by_key.get(&reference.trim().to_lowercase()).or_else(|| by_key.get(reference)) {
+                        Some(&child) if !resolved.contains(&child) => resolved.push(child),
+                        Some(_) => {}
+                        None => issues.push(issue("error", component, format!(
+                            "{} references missing component '{}'", component.component_name, reference
+                        ))),
+                    }
+                }
+                resolved
+            })
+            .collect();
+
+        ComponentGraph { components, levels, children }
+    }
+
+    fn check_levels(&self, issues: &mut Vec<ComponentIssue>) {
+        for (parent, children) in self.children.iter().enumerate() {
+            let Some(parent_level) = self.levels[parent] else { continue };
+            for &child in children {
+                let Some(child_level) = self.levels[child] else { continue };
+                let (parent_component, child_component) = (&self.components[parent], &self.components[child]);
+
+                if child_level > parent_level {
+                    issues.push(issue("error", parent_component, format!(
+                        "{} ({}) cannot be composed of {} ({})",
+                        parent_component.component_name, LEVELS[parent_level],
+                        child_component.component_name, LEVELS[child_level]
+                    )));
+                } else if child_level == parent_level && parent != child {
+                    issues.push(issue("warning", parent_component, format!(
+                        "{} is composed of {}, another {}",
+                        parent_component.component_name, child_component.component_name, LEVELS[parent_level]
+                    )));
+                }
+            }
+        }
+    }
+
+    /// Depth-first search reporting each cycle once as "A -> B -> A"
+    fn check_cycles(&self, issues: &mut Vec<ComponentIssue>) {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark { New, Active, Done }
+
+        fn visit(graph: &ComponentGraph, node: usize, marks: &mut [Mark], path: &mut Vec<usize>, issues: &mut Vec<ComponentIssue>) {
+            marks[node] = Mark::Active;
+            path.push(node);
+
+            for &child in &graph.children[node] {
+                match marks[child] {
+                    Mark::New => visit(graph, child, marks, path, issues),
+                    Mark::Active => {
+                        let start = path.iter().position(|&n| n == child).unwrap_or(0);
+                        let cycle: Vec<&str> = path[start..].iter()
+                            .chain(std::iter::once(&child))
+                            .map(|&n| graph.components[n].component_name.as_str())
+                            .collect();
+                        issues.push(issue("error", &graph.components[child], format!(
+                            "Composition cycle: {}", cycle.join(" -> ")
+                        )));
+                    }
+                    Mark::Done => {}
+                }
+            }
+
+            path.pop();
+            marks[node] = Mark::Done;
+        }
+
+        let mut marks = vec![Mark::New; self.components.len()];
+        for node in 0..self.components.len() {
+            if marks[node] == Mark::New {
+                visit(self, node, &mut marks, &mut Vec::new(), issues);
+            }
+        }
+    }
+}
+
+/// Build the composition graph and report cycles, level inversions and missing references
+pub fn validate_components(components: &[AtomicComponent]) -> ComponentTreeReport {
+    let mut issues = Vec::new();
+    let graph = ComponentGraph::build(components, &mut issues);
+    graph.check_levels(&mut issues);
+    graph.check_cycles(&mut issues);
+
+    let nodes = components.iter().enumerate()
+        .map(|(index, component)| ComponentNode {
+            id: component.id.clone(),
+            name: component.component_name.clone(),
+            level: graph.levels[index]
+                .map(|level| LEVELS[level].to_string())
+                .unwrap_or_else(|| component.component_level.clone()),
+            children: graph.children[index].iter().map(|&c| components[c].id.clone()).collect(),
+            used_by: graph.children.iter().enumerate()
+                .filter(|(_, children)| children.contains(&index))
+                .map(|(parent, _)| components[parent].id.clone())
+                .collect(),
+        })
+        .collect();
+
+    ComponentTreeReport { components: nodes, issues }
+}
+
+#[tauri::command]
+pub async fn validate_component_tree(
+    project_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<ComponentTreeReport, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    let components = queries
+        .get_atomic_components(&project_id)
+        .map_err(|e| e.to_string())?;
+
+    Ok(validate_components(&components))
+}
+
+/// Write a React/TSX stub per component into the project folder, grouped by level
+#[tauri::command]
+pub async fn scaffold_components(
+    request: ScaffoldComponentsRequest,
+    pool: State<'_, DbPool>,
+) -> Result<ScaffoldComponentsResponse, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    let components = queries
+        .get_atomic_components(&request.project_id)
+        .map_err(|e| e.to_string())?;
+    if components.is_empty() {
+        return Err("No atomic components designed for this project yet".to_string());
+    }
+
+    let report = validate_components(&components);
+    let errors: Vec<&str> = report.issues.iter()
+        .filter(|i| i.severity == "error")
+        .map(|i| i.message.as_str())
+        .collect();
+    if !errors.is_empty() {
+        return Err(format!("Component tree has {} error(s): {}", errors.len(), errors.join("; ")));
+    }
+
+    let components_dir = resolve_project_folder(&queries, &request.project_id)
+        .map_err(|e| e.to_string())?
+        .join(COMPONENTS_DIR);
+    let overwrite = request.overwrite.unwrap_or(false);
+
+    let names = export_names(&components);
+    let mut issues = report.issues;
+    for component in &components {
+        let name = &names[&component.id];
+        if *name != pascal_case(&component.component_name) {
+            issues.push(issue("warning", component, format!(
+                "{} is exported as {} because another component has the same name in PascalCase",
+                component.component_name, name
+            )));
+        }
+    }
+
+    let mut files_written = Vec::new();
+    let mut skipped_existing = Vec::new();
+    let mut exports_by_level: Vec<Vec<String>> = vec![Vec::new(); LEVELS.len()];
+
+    for (index, component) in components.iter().enumerate() {
+        let Some(level) = level_rank(&component.component_level) else { continue };
+        let children: Vec<&AtomicComponent> = report.components[index].children.iter()
+            .filter_map(|id| components.iter().find(|c| &c.id == id))
+            .collect();
+
+        let level_dir = components_dir.join(level_dir(level));
+        fs::create_dir_all(&level_dir)
+            .map_err(|e| format!("Failed to create component directory: {}", e))?;
+
+        let name = names[&component.id].clone();
+        let path = level_dir.join(format!("{}.tsx", name));
+        exports_by_level[level].push(name);
+        if path.exists() && !overwrite {
+            skipped_existing.push(path.to_string_lossy().to_string());
+            continue;
+        }
+
+        fs::write(&path, render_component(component, level, &children, &names))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        files_written.push(path.to_string_lossy().to_string());
+    }
+
+    // Barrel files are always regenerated so new components are exported
+    for (level, names) in exports_by_level.iter().enumerate() {
+        if names.is_empty() {
+            continue;
+        }
+        let index: String = names.iter()
+            .map(|name| format!("export {{ {} }} from './{}';\nexport type {{ {}Props }} from './{}';\n", name, name, name, name))
+            .collect();
+        let path = components_dir.join(level_dir(level)).join("index.ts");
+        fs::write(&path, index)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        files_written.push(path.to_string_lossy().to_string());
+    }
+
+    Ok(ScaffoldComponentsResponse {
+        components_dir: components_dir.to_string_lossy().to_string(),
+        files_written,
+        skipped_existing,
+        issues,
+    })
+}
+
+/// Export (and file) name per component id. Names that are equal in PascalCase get a number
+/// suffix; the comparison ignores case for case-insensitive file systems.
+fn export_names(components: &[AtomicComponent]) -> HashMap<String, String> {
+    let mut taken = HashSet::new();
+    let mut names = HashMap::new();
+
+    for component in components {
+        let base = pascal_case(&component.component_name);
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while !taken.insert(name.to_lowercase()) {
+            name = format!("{}{}", base, suffix);
+            suffix += 1;
+        }
+        names.insert(component.id.clone(), name);
+    }
+
+    names
+}
+
+// Rendering
+
+fn render_component(
+    component: &AtomicComponent,
+    level: usize,
+    children: &[&AtomicComponent],
+    names: &HashMap<String, String>,
+) -> String {
+    let export_name = |c: &AtomicComponent| names.get(&c.id).cloned().unwrap_or_else(|| pascal_case(&c.component_name));
+    let name = export_name(component);
+    let mut out = String::from("import React from 'react';\n");
+
+    for child in children {
+        let child_name = export_name(child);
+        let child_level = level_rank(&child.component_level).unwrap_or(level);
+        let import_path = if child_level == level {
+            format!("./{}", child_name)
+        } else {
+            format!("../{}/{}", level_dir(child_level), child_name)
+        };
+        out.push_str(&format!("import {{ {} }} from '{}';\n", child_name, import_path));
+    }
+
+    let props = prop_definitions(component.props.as_ref());
+    out.push_str(&format!("\nexport interface {}Props {{\n", name));
+    for (prop, ts_type, required) in &props {
+        out.push_str(&format!("  {}{}: {};\n", prop_key(prop), if *required { "" } else { "?" }, ts_type));
+    }
+    if !props.iter().any(|(prop, _, _)| prop == "children") {
+        out.push_str("  children?: React.ReactNode;\n");
+    }
+    out.push_str("}\n\n");
+
+    if let Some(description) = component.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        out.push_str(&format!("/**\n * {}\n */\n", description.trim().replace("*/", "* /")));
+    }
+
+    let mut destructured: Vec<String> = props.iter()
+        .filter(|(prop, _, _)| is_identifier(prop) && prop != "children")
+        .map(|(prop, _, _)| prop.clone())
+        .collect();
+    destructured.push("children".to_string());
+
+    out.push_str(&format!(
+        "export function {}({{ {} }}: {}Props) {{\n  return (\n    <div className=\"{}\" data-level=\"{}\">\n",
+        name,
+        destructured.join(", "),
+        name,
+        kebab_case(&component.component_name),
+        LEVELS[level]
+    ));
+    for child in children {
+        out.push_str(&format!("      <{} />\n", export_name(child)));
+    }
+    out.push_str("      {children}\n    </div>\n  );\n}\n\n");
+    out.push_str(&format!("export default {};\n", name));
+
+    out
+}
+
+/// Turn `AtomicComponent.props` into (name, TypeScript type, required) triples.
+/// Accepts `{"size": "sm|md|lg"}`, `{"label": "string"}` and `{"onClick": {"type": "function", "required": true}}`.
+fn prop_definitions(props: Option<&Value>) -> Vec<(String, String, bool)> {
+    let Some(Value::Object(props)) = props else { return Vec::new() };
+
+    props.iter()
+        .map(|(raw_name, spec)| {
+            let (name, optional_marker) = match raw_name.strip_suffix('?') {
+                Some(name) => (name.to_string(), true),
+                None => (raw_name.clone(), false),
+            };
+
+            let (ts_type, required) = match spec {
+                Value::Object(detail) if detail.contains_key("type") => (
+                    detail.get("type").map(ts_type).unwrap_or_else(|| "unknown".to_string()),
+                    detail.get("required").and_then(Value::as_bool).unwrap_or(false),
+                ),
+                other => (ts_type(other), false),
+            };
+
+            (name, ts_type, required && !optional_marker)
+        })
+        .collect()
+}
+
+fn ts_type(spec: &Value) -> String {
+    match spec {
+        Value::String(s) if s.contains('|') => s.split('|')
+            .map(|option| option.trim())
+            .filter(|option| !option.is_empty())
+            .map(|option| match option {
+                "string" | "number" | "boolean" | "null" | "undefined" => option.to_string(),
+                _ => format!("'{}'", option.trim_matches(|c| c == '\'' || c == '"').replace('\'', "\\'")),
+            })
+            .collect::<Vec<_>>()
+            .join(" | "),
+        Value::String(s) => match s.trim().to_lowercase().as_str() {
+            "string" | "text" => "string".to_string(),
+            "number" | "integer" | "int" | "float" => "number".to_string(),
+            "boolean" | "bool" => "boolean".to_string(),
+            "function" | "callback" | "handler" => "() => void".to_string(),
+            "node" | "reactnode" | "element" => "React.ReactNode".to_string(),
+            "array" => "unknown[]".to_string(),
+            "object" => "Record<string, unknown>".to_string(),
+            _ if s.contains("=>") || s.ends_with("[]") => s.trim().to_string(),
+            _ => "string".to_string(),
+        },
+        Value::Number(_) => "number".to_string(),
+        Value::Bool(_) => "boolean".to_string(),
+        Value::Array(items) => match items.first() {
+            Some(first) if items.iter().all(|item| item.is_string()) && !first.as_str().unwrap_or("").is_empty() => items.iter()
+                .filter_map(Value::as_str)
+                .map(|option| format!("'{}'", option.replace('\'', "\\'")))
+                .collect::<Vec<_>>()
+                .join(" | "),
+            Some(first) => format!("Array<{}>", ts_type(first)),
+            None => "unknown[]".to_string(),
+        },
+        Value::Object(_) => "Record<string, unknown>".to_string(),
+        Value::Null => "unknown".to_string(),
+    }
+}
+
+// Helpers
+
+fn level_rank(level: &str) -> Option<usize> {
+    let level = level.trim().to_lowercase();
+    let level = level.strip_suffix('s').unwrap_or(&level);
+    LEVELS.iter().position(|l| *l == level)
+}
+
+fn level_dir(level: usize) -> String {
+    format!("{}s", LEVELS[level])
+}
+
+fn issue(severity: &str, component: &AtomicComponent, message: String) -> ComponentIssue {
+    ComponentIssue {
+        severity: severity.to_string(),
+        component: component.component_name.clone(),
+        message,
+    }
+}
+
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+
+    for c in name.chars() {
+        if !c.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            previous_lower = false;
+            continue;
+        }
+        if c.is_ascii_uppercase() && previous_lower && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        current.push(c);
+        previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
+fn pascal_case(name: &str) -> String {
+    let pascal: String = words(name).iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect();
+
+    match pascal.chars().next() {
+        Some(first) if first.is_ascii_alphabetic() => pascal,
+        Some(_) => format!("Component{}", pascal),
+        None => "Component".to_string(),
+    }
+}
+
+fn kebab_case(name: &str) -> String {
+    slugify(&words(name).join(" "))
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.chars().next().map(|c| c.is_ascii_alphabetic() || c == '_' || c == '$').unwrap_or(false)
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+fn prop_key(name: &str) -> String {
+    if is_identifier(name) {
+        name.to_string()
+    } else {
+        format!("'{}'", name.replace('\'', "\\'"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json::json;
+
+    fn component(name: &str, level: &str, composed_of: &[&str], props: Option<Value>) -> AtomicComponent {
+        AtomicComponent {
+            id: format!("id-{}", name.to_lowercase()),
+            project_id: "project-1".to_string(),
+            component_level: level.to_string(),
+            component_name: name.to_string(),
+            description: None,
+            props,
+            composed_of: composed_of.iter().map(|c| c.to_string()).collect(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_reports_cycles_inversions_and_missing_references() {
+        let components = vec![
+            component("Button", "atom", &["Header"], None),
+            component("SearchBar", "molecule", &["Button", "Input"], None),
+            component("Header", "organism", &["SearchBar", "Header"], None),
+        ];
+
+        let report = validate_components(&components);
+        let messages: Vec<&str> = report.issues.iter().map(|i| i.message.as_str()).collect();
+
+        assert!(messages.contains(&"SearchBar references missing component 'Input'"));
+        assert!(messages.contains(&"Button (atom) cannot be composed of Header (organism)"));
+        assert!(messages.contains(&"Composition cycle: Button -> Header -> SearchBar -> Button"));
+        assert!(messages.contains(&"Composition cycle: Header -> Header"));
+        assert_eq!(report.components[0].used_by, vec!["id-searchbar".to_string()]);
+    }
+
+    #[test]
+    fn test_renders_typed_props_and_child_imports() {
+        let button = component("Button", "atom", &[], None);
+        let card = component(
+            "Product Card",
+            "molecule",
+            &["Button"],
+            Some(json!({ "variant": "primary|secondary", "onSelect": { "type": "function", "required": true } })),
+        );
+
+        let tsx = render_component(&card, 1, &[&button], &export_names(&[button.clone(), card.clone()]));
+
+        assert!(tsx.contains("import { Button } from '../atoms/Button';"));
+        assert!(tsx.contains("export interface ProductCardProps {"));
+        assert!(tsx.contains("  onSelect: () => void;"));
+        assert!(tsx.contains("  variant?: 'primary' | 'secondary';"));
+        assert!(tsx.contains("export function ProductCard({ onSelect, variant, children }: ProductCardProps)"));
+        assert!(tsx.contains("<div className=\"product-card\" data-level=\"molecule\">"));
+    }
+
+    #[test]
+    fn test_names_equal_in_pascal_case_get_a_suffix() {
+        let components = vec![
+            component("Product Card", "molecule", &[], None),
+            component("product-card", "molecule", &[], None),
+            component("ProductCard2", "organism", &[], None),
+        ];
+
+        let names = export_names(&components);
+        assert_eq!(names["id-product card"], "ProductCard");
+        assert_eq!(names["id-product-card"], "ProductCard2");
+        assert_eq!(names["id-productcard2"], "ProductCard22");
+    }
+}
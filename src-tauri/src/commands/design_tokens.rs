@@ -0,0 +1,610 @@
+use crate::db::{models::*, queries::Queries, DbPool};
+use crate::commands::export_history::{new_export, ExportKind, Exported};
+use crate::commands::workspace::resolve_project_folder;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use tauri::State;
+
+const TOKENS_DIR: &str = "design-tokens";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportDesignTokensRequest {
+    pub project_id: String,
+    pub formats: Option<Vec<String>>, // "css", "tailwind", "style-dictionary"; all when omitted
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportDesignTokensResponse {
+    pub output_dir: String,
+    pub files_written: Vec<String>,
+    pub exported_tokens: usize,
+    pub issues: Vec<TokenIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TokenIssue {
+    pub token_category: String,
+    pub token_name: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TokenFormat {
+    Css,
+    Tailwind,
+    StyleDictionary,
+}
+
+impl TokenFormat {
+    fn parse(format: &str) -> Result<Self, String> {
+        match format.to_lowercase().as_str() {
+            "css" => Ok(TokenFormat::Css),
+            "tailwind" => Ok(TokenFormat::Tailwind),
+            "style-dictionary" | "style_dictionary" | "json" => Ok(TokenFormat::StyleDictionary),
+            other => Err(format!("Unsupported design token format: {}", other)),
+        }
+    }
+
+    fn filename(&self) -> &'static str {
+        match self {
+            TokenFormat::Css => "tokens.css",
+            TokenFormat::Tailwind => "tailwind.config.js",
+            TokenFormat::StyleDictionary => "tokens.json",
+        }
+    }
+}
+
+/// How a token is treated, derived from its category and name
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TokenKind {
+    Color,
+    Spacing,
+    Radius,
+    Shadow,
+    FontFamily,
+    FontSize,
+    FontWeight,
+    LineHeight,
+    LetterSpacing,
+    Other,
+}
+
+/// A token that passed validation, with its name normalised to kebab-case
+#[derive(Debug, Clone)]
+struct ValidToken {
+    category: String,
+    name: String,
+    value: String,
+    kind: TokenKind,
+}
+
+const NAMED_COLORS: [&str; 16] = [
+    "transparent", "currentcolor", "black", "white", "red", "green", "blue", "yellow",
+    "orange", "purple", "pink", "gray", "grey", "silver", "navy", "teal",
+];
+
+const DIMENSION_UNITS: [&str; 9] = ["px", "rem", "em", "%", "vh", "vw", "pt", "ch", "ex"];
+
+const GENERIC_FONT_FAMILIES: [&str; 13] = [
+    "serif", "sans-serif", "monospace", "cursive", "fantasy", "system-ui", "ui-serif",
+    "ui-sans-serif", "ui-monospace", "ui-rounded", "math", "emoji", "fangsong",
+];
+
+/// Render a project's design tokens as CSS variables, a Tailwind theme
+/// extension and Style Dictionary JSON inside the workspace folder
+#[tauri::command]
+pub async fn export_design_tokens(
+    request: ExportDesignTokensRequest,
+    pool: State<'_, DbPool>,
+) -> Result<ExportDesignTokensResponse, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    Ok(export_design_tokens_with_record(&queries, request)?.output)
+}
+
+pub fn export_design_tokens_with_record(queries: &Queries, request: ExportDesignTokensRequest) -> Result<Exported<ExportDesignTokensResponse>, String> {
+    let formats = match &request.formats {
+        Some(formats) if !formats.is_empty() => formats.iter()
+            .map(|f| TokenFormat::parse(f))
+            .collect::<Result<Vec<_>, _>>()?,
+        _ => vec![TokenFormat::Css, TokenFormat::Tailwind, TokenFormat::StyleDictionary],
+    };
+
+    let tokens = queries
+        .get_design_tokens(&request.project_id)
+        .map_err(|e| e.to_string())?;
+    if tokens.is_empty() {
+        return Err("No design tokens defined for this project yet".to_string());
+    }
+
+    let (valid, issues) = validate_tokens(&tokens);
+    if valid.is_empty() {
+        return Err(format!("None of the {} design tokens are valid", tokens.len()));
+    }
+
+    let output_dir = resolve_project_folder(queries, &request.project_id)
+        .map_err(|e| e.to_string())?
+        .join(TOKENS_DIR);
+    fs::create_dir_all(&output_dir)
+        .map_err(|e| format!("Failed to create design token directory: {}", e))?;
+
+    let mut files_written = Vec::new();
+    let mut bytes_written = 0;
+    for format in formats {
+        let content = match format {
+            TokenFormat::Css => render_css(&valid),
+            TokenFormat::Tailwind => render_tailwind(&valid),
+            TokenFormat::StyleDictionary => render_style_dictionary(&valid),
+        };
+
+        let path = output_dir.join(format.filename());
+        fs::write(&path, &content)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        files_written.push(path.to_string_lossy().to_string());
+        bytes_written += content.len();
+    }
+
+    let record = ExportRecord {
+        file_path: Some(output_dir.to_string_lossy().to_string()),
+        file_size_bytes: Some(bytes_written as i64),
+        included_sections: vec!["designSystem".to_string()],
+        ..new_export(&request.project_id, ExportKind::DesignTokens, "design_tokens", &request)
+            .map_err(|e| e.to_string())?
+    };
+    queries.record_export(&record).map_err(|e| e.to_string())?;
+
+    let output = ExportDesignTokensResponse {
+        output_dir: output_dir.to_string_lossy().to_string(),
+        files_written,
+        exported_tokens: valid.len(),
+        issues,
+    };
+    Ok(Exported { output, record })
+}
+
+/// Render every format at once as (file name, content) pairs, for callers
+/// that lay the files out themselves
+pub fn render_token_files(tokens: &[DesignToken]) -> (Vec<(&'static str, String)>, Vec<TokenIssue>) {
+    let (valid, issues) = validate_tokens(tokens);
+    if valid.is_empty() {
+        return (Vec::new(), issues);
+    }
+
+    let files = vec![
+        (TokenFormat::Css.filename(), render_css(&valid)),
+        (TokenFormat::Tailwind.filename(), render_tailwind(&valid)),
+        (TokenFormat::StyleDictionary.filename(), render_style_dictionary(&valid)),
+    ];
+    (files, issues)
+}
+
+// Validation
+
+/// Split tokens into exportable ones and issues; invalid and duplicate tokens are left out, as
+/// are tokens that would take a CSS variable or Tailwind key another token already has
+fn validate_tokens(tokens: &[DesignToken]) -> (Vec<ValidToken>, Vec<TokenIssue>) {
+    let mut valid = Vec::new();
+    let mut issues = Vec::new();
+    let mut seen = HashSet::new();
+    let mut variables: HashMap<String, String> = HashMap::new();
+    let mut tailwind_keys: HashMap<(&'static str, String), String> = HashMap::new();
+
+    for token in tokens {
+        let category = kebab_case(&token.token_category);
+        let name = kebab_case(&token.token_name);
+        let value = token.token_value.trim().to_string();
+        let mut report = |message: String| issues.push(TokenIssue {
+            token_category: token.token_category.clone(),
+            token_name: token.token_name.clone(),
+            message,
+        });
+
+        if name.is_empty() {
+            report("Token has no usable name".to_string());
+            continue;
+        }
+        if value.is_empty() {
+            report(format!("Token '{}' has an empty value", name));
+            continue;
+        }
+        if !seen.insert((category.clone(), name.clone())) {
+            report(format!("Token '{}' is defined more than once in '{}'; keeping the first", name, category));
+            continue;
+        }
+
+        let kind = token_kind(&category, &name);
+        let problem = match kind {
+            TokenKind::Color if !is_valid_color(&value) => Some("is not a valid colour"),
+            TokenKind::Spacing | TokenKind::Radius | TokenKind::FontSize | TokenKind::LetterSpacing
+                if !is_valid_dimension(&value) => Some("is not a valid dimension"),
+            TokenKind::LineHeight if !is_valid_dimension(&value) && value.parse::<f64>().is_err() =>
+                Some("is not a valid line height"),
+            TokenKind::FontWeight if !is_valid_font_weight(&value) => Some("is not a valid font weight"),
+            _ => None,
+        };
+        if let Some(problem) = problem {
+            report(format!("Value '{}' of token '{}' {}", value, name, problem));
+            continue;
+        }
+
+        let token = ValidToken { category, name, value, kind };
+        let owner = format!("{}/{}", token.category, token.name);
+        let variable = css_variable(&token);
+        if let Some(other) = variables.get(&variable) {
+            report(format!("Token '{}' would be exported as --{}, which '{}' already uses; keeping the first", owner, variable, other));
+            continue;
+        }
+        if let Some(key) = tailwind_key(&token) {
+            if let Some(other) = tailwind_keys.get(&key) {
+                report(format!("Token '{}' would be exported as Tailwind {}.{}, which '{}' already uses; keeping the first", owner, key.0, key.1, other));
+                continue;
+            }
+            tailwind_keys.insert(key, owner.clone());
+        }
+        variables.insert(variable, owner);
+
+        valid.push(token);
+    }
+
+    (valid, issues)
+}
+
+fn token_kind(category: &str, name: &str) -> TokenKind {
+    match category {
+        "color" | "colors" | "colour" | "colours" => TokenKind::Color,
+        "spacing" | "space" | "size" | "sizing" => TokenKind::Spacing,
+        "radius" | "radii" | "border-radius" => TokenKind::Radius,
+        "shadow" | "shadows" | "box-shadow" | "elevation" => TokenKind::Shadow,
+        "typography" | "font" | "fonts" => {
+            if name.contains("weight") {
+                TokenKind::FontWeight
+            } else if name.contains("line-height") || name.contains("leading") {
+                TokenKind::LineHeight
+            } else if name.contains("letter-spacing") || name.contains("tracking") {
+                TokenKind::LetterSpacing
+            } else if name.contains("size") {
+                TokenKind::FontSize
+            } else {
+                TokenKind::FontFamily
+            }
+        }
+        _ => TokenKind::Other,
+    }
+}
+
+fn is_valid_color(value: &str) -> bool {
+    let lower = value.to_lowercase();
+
+    if let Some(hex) = lower.strip_prefix('#') {
+        return matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit());
+    }
+
+    for function in ["rgba", "rgb", "hsla", "hsl"] {
+        if let Some(args) = lower.strip_prefix(function).and_then(|rest| rest.strip_prefix('(')) {
+            let Some(args) = args.strip_suffix(')') else { return false };
+            let parts: Vec<&str> = args
+                .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+                .filter(|part| !part.is_empty())
+                .collect();
+            return matches!(parts.len(), 3 | 4)
+                && parts.iter().enumerate().all(|(i, part)| {
+                    let number = if i == 0 && function.starts_with("hsl") {
+                        part.trim_end_matches("deg")
+                    } else {
+                        part.trim_end_matches('%')
+                    };
+                    number.parse::<f64>().is_ok()
+                });
+        }
+    }
+
+    NAMED_COLORS.contains(&lower.as_str())
+}
+
+fn is_valid_dimension(value: &str) -> bool {
+    if value == "0" {
+        return true;
+    }
+    DIMENSION_UNITS.iter().any(|unit| {
+        value.strip_suffix(unit)
+            .map(|number| !number.is_empty() && number.parse::<f64>().is_ok())
+            .unwrap_or(false)
+    })
+}
+
+fn is_valid_font_weight(value: &str) -> bool {
+    matches!(value, "normal" | "bold" | "lighter" | "bolder")
+        || value.parse::<u32>().map(|w| (1..=1000).contains(&w)).unwrap_or(false)
+}
+
+// Rendering
+
+fn render_css(tokens: &[ValidToken]) -> String {
+    let mut out = String::from("/* Design tokens generated by prob */\n:root {\n");
+    for token in tokens {
+        out.push_str(&format!("  --{}: {};\n", css_variable(token), css_value(token)));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn css_variable(token: &ValidToken) -> String {
+    let prefix = match token.kind {
+        TokenKind::Color => "color",
+        TokenKind::Spacing => "spacing",
+        TokenKind::Radius => "radius",
+        TokenKind::Shadow => "shadow",
+        TokenKind::FontFamily | TokenKind::FontSize | TokenKind::FontWeight => "font",
+        TokenKind::LineHeight | TokenKind::LetterSpacing => "text",
+        TokenKind::Other => token.category.as_str(),
+    };
+    if prefix.is_empty() || token.name.starts_with(&format!("{}-", prefix)) {
+        token.name.clone()
+    } else {
+        format!("{}-{}", prefix, token.name)
+    }
+}
+
+/// Value as written to CSS and Tailwind. Colours and dimensions are validated already; font
+/// families are quoted per family and anything else that could end the declaration early
+/// becomes a CSS string.
+fn css_value(token: &ValidToken) -> String {
+    match token.kind {
+        TokenKind::FontFamily => font_family_list(&token.value),
+        TokenKind::Shadow | TokenKind::Other if !is_safe_css_value(&token.value) => css_string(&token.value),
+        _ => token.value.clone(),
+    }
+}
+
+/// No `;` or braces outside strings, no escapes or comments, balanced quotes and brackets
+fn is_safe_css_value(value: &str) -> bool {
+    if value.contains("/*") {
+        return false;
+    }
+    let mut quote = None;
+    let mut depth = 0i32;
+    for c in value.chars() {
+        if c == '\\' || c.is_control() {
+            return false;
+        }
+        match quote {
+            Some(open) if c == open => quote = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '(' | '[' => depth += 1,
+                ')' | ']' => {
+                    depth -= 1;
+                    if depth < 0 {
+                        return false;
+                    }
+                }
+                ';' | '{' | '}' => return false,
+                _ => {}
+            },
+        }
+    }
+    quote.is_none() && depth == 0
+}
+
+fn css_string(value: &str) -> String {
+    let mut out = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            c if c.is_control() => out.push_str(&format!("\\{:x} ", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Re-quote each family of a font stack, leaving generic families bare
+fn font_family_list(value: &str) -> String {
+    let mut families = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    for c in value.chars() {
+        match quote {
+            Some(open) if c == open => quote = None,
+            Some(_) => current.push(c),
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                ',' => families.push(std::mem::take(&mut current)),
+                c => current.push(c),
+            },
+        }
+    }
+    families.push(current);
+
+    families.iter()
+        .map(|family| single_spaced(family))
+        .filter(|family| !family.is_empty())
+        .map(|family| {
+            if GENERIC_FONT_FAMILIES.contains(&family.to_lowercase().as_str()) {
+                family.to_lowercase()
+            } else {
+                css_string(&family)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn single_spaced(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Theme section and key of a token in the Tailwind config; `Other` tokens have no section
+fn tailwind_key(token: &ValidToken) -> Option<(&'static str, String)> {
+    let (section, strip) = match token.kind {
+        TokenKind::Color => ("colors", &["color-"][..]),
+        TokenKind::Spacing => ("spacing", &["spacing-", "space-"][..]),
+        TokenKind::Radius => ("borderRadius", &["radius-", "rounded-"][..]),
+        TokenKind::Shadow => ("boxShadow", &["shadow-"][..]),
+        TokenKind::FontFamily => ("fontFamily", &["font-family-", "font-"][..]),
+        TokenKind::FontSize => ("fontSize", &["font-size-", "text-size-", "size-"][..]),
+        TokenKind::FontWeight => ("fontWeight", &["font-weight-", "weight-"][..]),
+        TokenKind::LineHeight => ("lineHeight", &["line-height-", "leading-"][..]),
+        TokenKind::LetterSpacing => ("letterSpacing", &["letter-spacing-", "tracking-"][..]),
+        TokenKind::Other => return None,
+    };
+
+    let key = strip.iter()
+        .find_map(|prefix| token.name.strip_prefix(prefix))
+        .unwrap_or(&token.name);
+    let key = if key.is_empty() || token.name == section { "DEFAULT" } else { key };
+    Some((section, key.to_string()))
+}
+
+fn render_tailwind(tokens: &[ValidToken]) -> String {
+    let mut extend = Map::new();
+
+    for token in tokens {
+        let Some((section, key)) = tailwind_key(token) else { continue };
+        // serde_json escapes the value, so it stays one JS string
+        if let Value::Object(entries) = extend.entry(section).or_insert_with(|| json!({})) {
+            entries.insert(key, Value::String(css_value(token)));
+        }
+    }
+
+    let config = json!({ "theme": { "extend": extend } });
+    format!(
+        "/** Tailwind theme generated by prob from the project's design tokens */\n\
+         /** @type {{import('tailwindcss').Config}} */\n\
+         module.exports = {};\n",
+        serde_json::to_string_pretty(&config).unwrap_or_default()
+    )
+}
+
+fn render_style_dictionary(tokens: &[ValidToken]) -> String {
+    let mut root = Map::new();
+
+    for token in tokens {
+        let token_type = match token.kind {
+            TokenKind::Color => "color",
+            TokenKind::Spacing | TokenKind::Radius | TokenKind::FontSize | TokenKind::LetterSpacing => "dimension",
+            TokenKind::Shadow => "shadow",
+            TokenKind::FontFamily => "fontFamily",
+            TokenKind::FontWeight => "fontWeight",
+            TokenKind::LineHeight => "lineHeight",
+            TokenKind::Other => "other",
+        };
+
+        if let Value::Object(group) = root.entry(token.category.clone()).or_insert_with(|| json!({})) {
+            group.insert(token.name.clone(), json!({ "value": token.value, "type": token_type }));
+        }
+    }
+
+    let mut out = serde_json::to_string_pretty(&Value::Object(root)).unwrap_or_default();
+    out.push('\n');
+    out
+}
+
+fn kebab_case(name: &str) -> String {
+    let mut out = String::new();
+    let mut previous_lower = false;
+
+    for c in name.trim().chars() {
+        if c.is_ascii_uppercase() && previous_lower {
+            out.push('-');
+        }
+        if c.is_ascii_alphanumeric() {
+            out.push(c.to_ascii_lowercase());
+            previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        } else if !out.ends_with('-') && !out.is_empty() {
+            out.push('-');
+            previous_lower = false;
+        }
+    }
+
+    out.trim_end_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn token(category: &str, name: &str, value: &str) -> DesignToken {
+        DesignToken {
+            id: name.to_string(),
+            project_id: "project-1".to_string(),
+            token_category: category.to_string(),
+            token_name: name.to_string(),
+            token_value: value.to_string(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_validation_rejects_bad_colours_and_dimensions() {
+        let tokens = vec![
+            token("color", "primary-500", "#3B82F6"),
+            token("color", "overlay", "rgba(0, 0, 0, 0.5)"),
+            token("color", "accent", "#12345"),
+            token("spacing", "md", "1rem"),
+            token("spacing", "lg", "large"),
+            token("typography", "fontWeightBold", "700"),
+            token("color", "primary-500", "#000000"),
+        ];
+
+        let (valid, issues) = validate_tokens(&tokens);
+        let names: Vec<&str> = valid.iter().map(|t| t.name.as_str()).collect();
+
+        assert_eq!(names, vec!["primary-500", "overlay", "md", "font-weight-bold"]);
+        assert_eq!(issues.len(), 3);
+        assert!(issues[0].message.contains("not a valid colour"));
+        assert!(issues[1].message.contains("not a valid dimension"));
+        assert!(issues[2].message.contains("more than once"));
+    }
+
+    #[test]
+    fn test_renders_each_format() {
+        let (valid, _) = validate_tokens(&[
+            token("color", "primary-500", "#3B82F6"),
+            token("typography", "font-size-lg", "1.125rem"),
+            token("shadow", "shadow-md", "0 4px 6px rgba(0,0,0,0.1)"),
+        ]);
+
+        let css = render_css(&valid);
+        assert!(css.contains("--color-primary-500: #3B82F6;"));
+        assert!(css.contains("--font-size-lg: 1.125rem;"));
+        assert!(css.contains("--shadow-md: 0 4px 6px rgba(0,0,0,0.1);"));
+
+        let tailwind = render_tailwind(&valid);
+        assert!(tailwind.contains("\"primary-500\": \"#3B82F6\""));
+        assert!(tailwind.contains("\"fontSize\""));
+        assert!(tailwind.contains("\"lg\": \"1.125rem\""));
+
+        let dictionary: Value = serde_json::from_str(&render_style_dictionary(&valid)).unwrap();
+        assert_eq!(dictionary["color"]["primary-500"]["value"], "#3B82F6");
+        assert_eq!(dictionary["typography"]["font-size-lg"]["type"], "dimension");
+    }
+
+    #[test]
+    fn test_unsafe_values_are_quoted_and_collisions_rejected() {
+        let (valid, issues) = validate_tokens(&[
+            token("typography", "font-body", "Inter Var, 'Helvetica Neue', sans-serif"),
+            token("shadow", "shadow-md", "0 1px red; } body { display: none"),
+            token("motion", "ease", "cubic-bezier(0.4, 0, 0.2, 1)"),
+            token("colors", "primary", "#000000"),
+            token("color", "color-primary", "#ffffff"),
+        ]);
+
+        let css = render_css(&valid);
+        assert!(css.contains(r#"--font-body: "Inter Var", "Helvetica Neue", sans-serif;"#));
+        assert!(css.contains(r#"--shadow-md: "0 1px red; } body { display: none";"#));
+        assert!(css.contains("--motion-ease: cubic-bezier(0.4, 0, 0.2, 1);"));
+        assert_eq!(css.matches("--color-primary").count(), 1);
+
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].message.contains("--color-primary"));
+    }
+}
@@ -0,0 +1,501 @@
+use crate::commands::document_render::{gather_document_data, render_document};
+use crate::commands::documents::{adopt_disk_content, hash_content, overwrite_document, DocumentType};
+use crate::db::{models::*, queries::Queries, DbPool};
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use tauri::State;
+use uuid::Uuid;
+
+/// Sections whose wording is the project's foundation; any edit there is critical
+const CRITICAL_SECTIONS: [&str; 1] = ["Problem Statement"];
+/// Longest excerpt of expected/actual text kept in a discrepancy
+const EXCERPT_CHARS: usize = 400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DriftSeverity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl DriftSeverity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DriftSeverity::Low => "low",
+            DriftSeverity::Medium => "medium",
+            DriftSeverity::High => "high",
+            DriftSeverity::Critical => "critical",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftKind {
+    FileMissing,
+    SectionAdded,
+    SectionRemoved,
+    SectionRenamed,
+    ContentChanged,
+    Formatting,
+}
+
+/// One difference between the file on disk (`actual`) and what the database would generate (`expected`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftDiscrepancy {
+    pub document_type: String,
+    pub kind: DriftKind,
+    pub section: String,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+    pub severity: DriftSeverity,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftResolution {
+    /// Regenerate every affected document from the database
+    UpdateAll,
+    /// Regenerate only the documents listed in the request
+    UpdateSelected,
+    /// Accept the edited files as the new baseline
+    KeepChanges,
+    /// Put back the content that was last written
+    Revert,
+}
+
+impl DriftResolution {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DriftResolution::UpdateAll => "update_all",
+            DriftResolution::UpdateSelected => "update_selected",
+            DriftResolution::KeepChanges => "keep_changes",
+            DriftResolution::Revert => "revert",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveDriftRequest {
+    pub report_id: String,
+    pub method: DriftResolution,
+    pub documents: Option<Vec<String>>,
+    pub resolved_by: Option<String>,
+}
+
+/// Compare every tracked document with the current database state. Returns the pending report,
+/// which is reused while the drift is unchanged, or `None` when files and data agree.
+#[tauri::command]
+pub async fn detect_document_drift(
+    project_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<Option<DocumentDriftReport>, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    detect_drift(&queries, &project_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_document_drift_reports(
+    project_id: String,
+    status: Option<String>,
+    pool: State<'_, DbPool>,
+) -> Result<Vec<DocumentDriftReport>, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    queries
+        .get_drift_reports(&project_id, status.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn resolve_document_drift(
+    request: ResolveDriftRequest,
+    pool: State<'_, DbPool>,
+) -> Result<DocumentDriftReport, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    resolve_drift(&queries, request).await.map_err(|e| e.to_string())
+}
+
+pub async fn detect_drift(queries: &Queries, project_id: &str) -> Result<Option<DocumentDriftReport>> {
+    let data = gather_document_data(project_id, queries).await?;
+    let accepted = accepted_renders(queries, project_id)?;
+
+    let mut discrepancies = Vec::new();
+    for document in queries.get_project_documents(project_id)? {
+        let Some(document_type) = DocumentType::parse(&document.document_type) else { continue };
+        let expected = render_document(&data, document_type)?;
+
+        match fs::read_to_string(&document.file_path) {
+            Ok(actual) => {
+                let still_accepted = document.file_hash.as_deref() == Some(hash_content(actual.as_bytes()).as_str())
+                    && accepted.get(&document.document_type) == Some(&hash_content(expected.as_bytes()));
+                if !still_accepted {
+                    discrepancies.extend(compare_documents(&document.document_type, &expected, &actual));
+                }
+            }
+            Err(_) => discrepancies.push(DriftDiscrepancy {
+                document_type: document.document_type.clone(),
+                kind: DriftKind::FileMissing,
+                section: String::new(),
+                expected: None,
+                actual: None,
+                severity: DriftSeverity::Critical,
+                message: format!("{} is missing from the workspace folder", document.file_path),
+            }),
+        }
+    }
+
+    let Some(severity) = discrepancies.iter().map(|d| d.severity).max() else {
+        return Ok(None);
+    };
+    let mut affected_documents: Vec<String> = discrepancies.iter().map(|d| d.document_type.clone()).collect();
+    affected_documents.dedup();
+    let drift_details = serde_json::to_value(&discrepancies)?;
+
+    // The same drift found again keeps its pending report
+    let pending = queries.get_drift_reports(project_id, Some("pending"))?.into_iter().next();
+    if let Some(pending) = pending.filter(|r| r.affected_documents == affected_documents && r.drift_details == drift_details) {
+        return Ok(Some(pending));
+    }
+
+    let report = DocumentDriftReport {
+        id: Uuid::new_v4().to_string(),
+        project_id: project_id.to_string(),
+        detected_at: Utc::now(),
+        affected_documents,
+        drift_details,
+        drift_severity: severity.as_str().to_string(),
+        resolution_status: "pending".to_string(),
+        resolution_method: None,
+        resolution_metadata: None,
+        resolved_at: None,
+        resolved_by: None,
+    };
+    queries.create_drift_report(&report)?;
+    Ok(Some(report))
+}
+
+async fn resolve_drift(queries: &Queries, request: ResolveDriftRequest) -> Result<DocumentDriftReport> {
+    let report = queries
+        .get_drift_report(&request.report_id)?
+        .ok_or_else(|| anyhow!("Drift report not found"))?;
+    if report.resolution_status != "pending" {
+        return Err(anyhow!("Drift report is already {}", report.resolution_status));
+    }
+
+    let targets: Vec<String> = match request.method {
+        DriftResolution::UpdateSelected => {
+            let selected = request.documents.unwrap_or_default();
+            if selected.is_empty() {
+                return Err(anyhow!("Select at least one document to update"));
+            }
+            if let Some(unknown) = selected.iter().find(|d| !report.affected_documents.contains(d)) {
+                return Err(anyhow!("'{}' is not part of this drift report", unknown));
+            }
+            selected
+        }
+        _ => report.affected_documents.clone(),
+    };
+
+    let data = match request.method {
+        DriftResolution::Revert => None,
+        _ => Some(gather_document_data(&report.project_id, queries).await?),
+    };
+
+    let mut applied = Vec::new();
+    let mut skipped = Vec::new();
+    let mut accepted = HashMap::new();
+    let mut kept = HashMap::new();
+    for document_type in &targets {
+        let Some(document) = queries.get_project_document(&report.project_id, document_type)? else {
+            skipped.push(document_type.clone());
+            continue;
+        };
+        let parsed = DocumentType::parse(document_type)
+            .ok_or_else(|| anyhow!("Unknown document type: {}", document_type))?;
+
+        let content = match (request.method, &data) {
+            (DriftResolution::KeepChanges, _) => {
+                let document = adopt_disk_content(queries, document)?;
+                if let Some(hash) = document.file_hash {
+                    kept.insert(document_type.clone(), hash);
+                }
+                if let Some(data) = &data {
+                    let expected = render_document(data, parsed)?;
+                    accepted.insert(document_type.clone(), hash_content(expected.as_bytes()));
+                }
+                applied.push(document_type.clone());
+                continue;
+            }
+            (DriftResolution::Revert, _) => document.content.clone(),
+            (_, Some(data)) => Some(render_document(data, parsed)?),
+            (_, None) => None,
+        };
+        match content {
+            Some(content) => {
+                overwrite_document(queries, &report.project_id, parsed, &content)?;
+                applied.push(document_type.clone());
+            }
+            None => skipped.push(document_type.clone()),
+        }
+    }
+
+    let mut metadata = serde_json::json!({ "documents": applied, "skipped": skipped });
+    if request.method == DriftResolution::KeepChanges {
+        metadata["accepted"] = serde_json::to_value(&accepted)?;
+        metadata["kept"] = serde_json::to_value(&kept)?;
+    }
+    queries.resolve_drift_report(&report.id, request.method.as_str(), &metadata, request.resolved_by.as_deref())?;
+    queries
+        .get_drift_report(&report.id)?
+        .ok_or_else(|| anyhow!("Drift report not found"))
+}
+
+/// Hash of the generated content each kept file was accepted against, newest resolution first
+fn accepted_renders(queries: &Queries, project_id: &str) -> Result<HashMap<String, String>> {
+    keep_changes_hashes(queries, project_id, "accepted")
+}
+
+/// Hash of each file whose edits were kept, newest resolution first
+pub fn kept_edits(queries: &Queries, project_id: &str) -> Result<HashMap<String, String>> {
+    keep_changes_hashes(queries, project_id, "kept")
+}
+
+fn keep_changes_hashes(queries: &Queries, project_id: &str, key: &str) -> Result<HashMap<String, String>> {
+    let mut hashes = HashMap::new();
+    for report in queries.get_drift_reports(project_id, Some("resolved"))? {
+        if report.resolution_method.as_deref() != Some(DriftResolution::KeepChanges.as_str()) {
+            continue;
+        }
+        let resolved = report.resolution_metadata
+            .and_then(|m| serde_json::from_value::<HashMap<String, String>>(m[key].clone()).ok())
+            .unwrap_or_default();
+        for (document_type, hash) in resolved {
+            hashes.entry(document_type).or_insert(hash);
+        }
+    }
+    Ok(hashes)
+}
+
+/// A heading and everything under it, up to the next heading of the same or higher level
+#[derive(Debug, Default)]
+struct Section {
+    level: usize,
+    heading: String,
+    body: Vec<String>,
+    children: Vec<Section>,
+}
+
+impl Section {
+    fn path(&self, parent: &str) -> String {
+        match (parent.is_empty(), self.heading.is_empty()) {
+            (_, true) => parent.to_string(),
+            (true, false) => self.heading.clone(),
+            (false, false) => format!("{} > {}", parent, self.heading),
+        }
+    }
+}
+
+/// Parse Markdown into a heading tree. Lines inside fenced code blocks are body text.
+fn parse_sections(markdown: &str) -> Section {
+    let mut root = Section::default();
+    // Indices into `children` from the root down to the section being filled
+    let mut stack: Vec<usize> = Vec::new();
+    let mut in_fence = false;
+
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        let level = line.chars().take_while(|c| *c == '#').count();
+        let is_heading = !in_fence && (1..=6).contains(&level) && line[level..].starts_with(' ');
+
+        if is_heading {
+            while let Some(depth) = stack.len().checked_sub(1) {
+                if section_at(&mut root, &stack[..=depth]).level < level {
+                    break;
+                }
+                stack.pop();
+            }
+            let parent = section_at(&mut root, &stack);
+            parent.children.push(Section { level, heading: line[level..].trim().to_string(), ..Default::default() });
+            stack.push(parent.children.len() - 1);
+        } else {
+            section_at(&mut root, &stack).body.push(line.to_string());
+        }
+    }
+    root
+}
+
+fn section_at<'a>(root: &'a mut Section, path: &[usize]) -> &'a mut Section {
+    path.iter().fold(root, |section, index| &mut section.children[*index])
+}
+
+/// Drop numbering such as "Story 3: " so removing one item does not rename every later one
+fn heading_key(heading: &str) -> &str {
+    if let Some((prefix, rest)) = heading.split_once(": ") {
+        if let Some((_, number)) = prefix.rsplit_once(' ') {
+            if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) {
+                return rest;
+            }
+        }
+    }
+    heading
+}
+
+fn normalized(lines: &[String]) -> Vec<String> {
+    lines.iter()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+fn excerpt(lines: &[String]) -> String {
+    let text = lines.join("\n").trim().to_string();
+    match text.char_indices().nth(EXCERPT_CHARS) {
+        Some((cut, _)) => format!("{}…", &text[..cut]),
+        None => text,
+    }
+}
+
+/// Differences between a generated document and the version on disk
+pub fn compare_documents(document_type: &str, expected: &str, actual: &str) -> Vec<DriftDiscrepancy> {
+    let mut out = Vec::new();
+    compare_sections(document_type, "", &parse_sections(expected), &parse_sections(actual), &mut out);
+    out
+}
+
+fn compare_sections(document_type: &str, parent: &str, expected: &Section, actual: &Section, out: &mut Vec<DriftDiscrepancy>) {
+    let path = expected.path(parent);
+    let discrepancy = |kind, severity, expected: Option<String>, actual: Option<String>, message: String| DriftDiscrepancy {
+        document_type: document_type.to_string(),
+        kind,
+        section: path.clone(),
+        expected,
+        actual,
+        severity,
+        message,
+    };
+
+    if expected.body != actual.body {
+        let (kind, severity) = if normalized(&expected.body) == normalized(&actual.body) {
+            (DriftKind::Formatting, DriftSeverity::Low)
+        } else if CRITICAL_SECTIONS.contains(&expected.heading.as_str()) {
+            (DriftKind::ContentChanged, DriftSeverity::Critical)
+        } else {
+            (DriftKind::ContentChanged, DriftSeverity::Medium)
+        };
+        let label = if path.is_empty() { "the introduction".to_string() } else { format!("'{}'", path) };
+        out.push(discrepancy(
+            kind,
+            severity,
+            Some(excerpt(&expected.body)),
+            Some(excerpt(&actual.body)),
+            format!("Text changed in {}", label),
+        ));
+    }
+
+    // Match children by heading, then pair what is left over in order as renames
+    let mut unmatched_actual: Vec<&Section> = actual.children.iter().collect();
+    let mut unmatched_expected = Vec::new();
+    for child in &expected.children {
+        match unmatched_actual.iter().position(|a| heading_key(&a.heading) == heading_key(&child.heading)) {
+            Some(index) => {
+                let matched = unmatched_actual.remove(index);
+                compare_sections(document_type, &path, child, matched, out);
+            }
+            None => unmatched_expected.push(child),
+        }
+    }
+
+    let severity_for = |section: &Section| if section.level <= 1 { DriftSeverity::Medium } else { DriftSeverity::High };
+    let renamed = unmatched_expected.len().min(unmatched_actual.len());
+    for (expected_child, actual_child) in unmatched_expected.iter().zip(unmatched_actual.iter()).take(renamed) {
+        out.push(discrepancy(
+            DriftKind::SectionRenamed,
+            severity_for(expected_child),
+            Some(expected_child.heading.clone()),
+            Some(actual_child.heading.clone()),
+            format!("'{}' is called '{}' in the file", expected_child.heading, actual_child.heading),
+        ));
+        // Compare what is inside the renamed section under its expected name
+        compare_sections(document_type, &path, expected_child, &Section { heading: expected_child.heading.clone(), ..clone_section(actual_child) }, out);
+    }
+    for removed in unmatched_expected.iter().skip(renamed) {
+        out.push(discrepancy(
+            DriftKind::SectionRemoved,
+            severity_for(removed),
+            Some(removed.heading.clone()),
+            None,
+            format!("'{}' was removed from the file", removed.heading),
+        ));
+    }
+    for added in unmatched_actual.iter().skip(renamed) {
+        out.push(discrepancy(
+            DriftKind::SectionAdded,
+            severity_for(added),
+            None,
+            Some(added.heading.clone()),
+            format!("'{}' was added to the file but is not in the project data", added.heading),
+        ));
+    }
+}
+
+fn clone_section(section: &Section) -> Section {
+    Section {
+        level: section.level,
+        heading: section.heading.clone(),
+        body: section.body.clone(),
+        children: section.children.iter().map(clone_section).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GENERATED: &str = "# Product Vision - Demo\n\n## Problem Statement\n\nTeams lose track of decisions.\n\n## Target Personas\n\n### Alice\n\n- **Role:** PM\n\n### Bob\n\n- **Role:** Dev\n\n```mermaid\n# not a heading\n```\n";
+
+    #[test]
+    fn test_identical_documents_have_no_drift() {
+        assert!(compare_documents("product_vision", GENERATED, GENERATED).is_empty());
+    }
+
+    #[test]
+    fn test_edits_are_classified_by_severity() {
+        let edited = GENERATED
+            .replace("### Alice", "### Alicia")
+            .replace("Teams lose track of decisions.", "Teams forget things.")
+            .replace("- **Role:** Dev", "-   **Role:**   Dev");
+
+        let drift = compare_documents("product_vision", GENERATED, &edited);
+        let found = |kind| drift.iter().find(|d| d.kind == kind).unwrap();
+
+        let renamed = found(DriftKind::SectionRenamed);
+        assert_eq!(renamed.severity, DriftSeverity::High);
+        assert_eq!(renamed.expected.as_deref(), Some("Alice"));
+        assert_eq!(renamed.actual.as_deref(), Some("Alicia"));
+        assert_eq!(found(DriftKind::ContentChanged).severity, DriftSeverity::Critical);
+        assert_eq!(found(DriftKind::Formatting).section, "Product Vision - Demo > Target Personas > Bob");
+        assert_eq!(drift.len(), 3);
+    }
+
+    #[test]
+    fn test_removing_a_numbered_story_does_not_rename_the_rest() {
+        let expected = "# User Epics\n\n## Story 1: Sign up\n\n## Story 2: Log in\n\n## Story 3: Reset password\n";
+        let actual = "# User Epics\n\n## Story 1: Sign up\n\n## Story 2: Reset password\n";
+
+        let drift = compare_documents("user_epics", expected, actual);
+        assert_eq!(drift.len(), 1);
+        assert_eq!(drift[0].kind, DriftKind::SectionRemoved);
+        assert_eq!(drift[0].expected.as_deref(), Some("Story 2: Log in"));
+    }
+}
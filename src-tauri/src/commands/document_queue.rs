@@ -0,0 +1,303 @@
+use crate::commands::document_render::{gather_document_data, render_document};
+use crate::commands::documents::{write_document, DocumentEdited, DocumentType};
+use crate::db::{models::*, queries::Queries, DbPool};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+/// Emitted with a `GenerationProgress` whenever a job changes state
+pub const GENERATION_PROGRESS_EVENT: &str = "document-generation-progress";
+
+const DEFAULT_PRIORITY: i32 = 5;
+/// Failed attempts are retried this many times before the job is marked failed
+const MAX_RETRIES: i32 = 3;
+const BASE_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+/// How often the worker looks again when nothing is ready, e.g. to pick up retries after backoff
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+// Wakes the worker as soon as jobs are queued
+lazy_static::lazy_static! {
+    static ref QUEUE_WAKE: Arc<Notify> = Arc::new(Notify::new());
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EnqueueDocumentsRequest {
+    pub project_id: String,
+    /// Defaults to every document type
+    pub document_types: Option<Vec<String>>,
+    /// Lower runs first; defaults to 5
+    pub priority: Option<i32>,
+}
+
+/// Payload of `document-generation-progress`
+#[derive(Debug, Clone, Serialize)]
+pub struct GenerationProgress {
+    pub project_id: String,
+    pub job: DocumentGenerationJob,
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// What the worker should do next for a project's queue
+#[derive(Debug)]
+enum NextJob<'a> {
+    Run(&'a DocumentGenerationJob),
+    /// A dependency failed or was cancelled, so the job can never run
+    Blocked(&'a DocumentGenerationJob, String),
+}
+
+/// Queue every document (or the given ones) for background generation. Types that are already
+/// queued or running are left alone.
+#[tauri::command]
+pub async fn enqueue_project_documents(
+    request: EnqueueDocumentsRequest,
+    pool: State<'_, DbPool>,
+) -> Result<Vec<DocumentGenerationJob>, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    enqueue_documents(&queries, request).map_err(|e| e.to_string())
+}
+
+/// Cancel one job, or all unfinished jobs of the project. Returns how many were cancelled.
+#[tauri::command]
+pub async fn cancel_document_generation(
+    project_id: String,
+    job_id: Option<String>,
+    pool: State<'_, DbPool>,
+) -> Result<usize, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    queries
+        .cancel_generation_jobs(&project_id, job_id.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_document_generation_queue(
+    project_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<Vec<DocumentGenerationJob>, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    queries.get_generation_jobs(&project_id).map_err(|e| e.to_string())
+}
+
+/// Run the queue worker for the lifetime of the app
+pub fn start_generation_worker(app: AppHandle, pool: DbPool) {
+    tauri::async_runtime::spawn(async move {
+        let queries = Queries::new(pool);
+        if let Err(e) = queries.requeue_interrupted_generation_jobs() {
+            eprintln!("Failed to requeue interrupted document jobs: {}", e);
+        }
+
+        loop {
+            match run_next_job(&app, &queries).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    tokio::select! {
+                        _ = QUEUE_WAKE.notified() => {}
+                        _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Document generation worker error: {}", e);
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+            }
+        }
+    });
+}
+
+fn enqueue_documents(queries: &Queries, request: EnqueueDocumentsRequest) -> Result<Vec<DocumentGenerationJob>> {
+    let document_types = match request.document_types {
+        Some(types) => types.iter()
+            .map(|t| DocumentType::parse(t).ok_or_else(|| anyhow!("Unknown document type: {}", t)))
+            .collect::<Result<Vec<_>>>()?,
+        None => DocumentType::ALL.to_vec(),
+    };
+
+    queries.clear_finished_generation_jobs(&request.project_id)?;
+    let active = queries.get_generation_jobs(&request.project_id)?;
+
+    let now = Utc::now();
+    let jobs: Vec<DocumentGenerationJob> = document_types.into_iter()
+        .filter(|t| !active.iter().any(|job| job.document_type == t.as_str()))
+        .map(|t| DocumentGenerationJob {
+            id: Uuid::new_v4().to_string(),
+            project_id: request.project_id.clone(),
+            document_type: t.as_str().to_string(),
+            dependencies: t.dependencies().iter().map(|d| d.as_str().to_string()).collect(),
+            status: "pending".to_string(),
+            priority: request.priority.unwrap_or(DEFAULT_PRIORITY),
+            started_at: None,
+            completed_at: None,
+            error_message: None,
+            retry_count: 0,
+            created_at: now,
+        })
+        .collect();
+    queries.enqueue_generation_jobs(&jobs)?;
+
+    QUEUE_WAKE.notify_one();
+    queries.get_generation_jobs(&request.project_id)
+}
+
+/// Process at most one job. Returns false when nothing was ready.
+async fn run_next_job(app: &AppHandle, queries: &Queries) -> Result<bool> {
+    let now = Utc::now();
+    for project_id in queries.get_generation_queue_projects()? {
+        let jobs = queries.get_generation_jobs(&project_id)?;
+        match next_job(&jobs, now) {
+            Some(NextJob::Run(job)) => {
+                if queries.claim_generation_job(&job.id)? {
+                    emit_progress(app, queries, &project_id, &job.id);
+                    let result = generate(queries, job).await;
+                    finish_job(queries, job, result)?;
+                    emit_progress(app, queries, &project_id, &job.id);
+                }
+                return Ok(true);
+            }
+            Some(NextJob::Blocked(job, reason)) => {
+                queries.finish_generation_job(&job.id, "failed", Some(&reason), job.retry_count)?;
+                emit_progress(app, queries, &project_id, &job.id);
+                return Ok(true);
+            }
+            None => {}
+        }
+    }
+    Ok(false)
+}
+
+/// The first pending job (by priority) whose dependencies are done and whose backoff has elapsed
+fn next_job(jobs: &[DocumentGenerationJob], now: DateTime<Utc>) -> Option<NextJob<'_>> {
+    'jobs: for job in jobs.iter().filter(|j| j.status == "pending") {
+        if let Some(ready_at) = retry_at(job) {
+            if ready_at > now {
+                continue;
+            }
+        }
+
+        for dependency in &job.dependencies {
+            // A type that is not queued is taken as already generated
+            match jobs.iter().find(|j| &j.document_type == dependency).map(|j| j.status.as_str()) {
+                None | Some("completed") => {}
+                Some(status @ ("failed" | "cancelled")) => {
+                    return Some(NextJob::Blocked(job, format!("Dependency {} {}", dependency, status)));
+                }
+                Some(_) => continue 'jobs,
+            }
+        }
+        return Some(NextJob::Run(job));
+    }
+    None
+}
+
+/// When a job that failed before may be attempted again: the backoff doubles with every retry
+fn retry_at(job: &DocumentGenerationJob) -> Option<DateTime<Utc>> {
+    if job.retry_count == 0 {
+        return None;
+    }
+    let backoff = BASE_BACKOFF
+        .saturating_mul(1 << (job.retry_count - 1).min(16))
+        .min(MAX_BACKOFF);
+    job.started_at.map(|started| started + chrono::Duration::from_std(backoff).unwrap_or_default())
+}
+
+async fn generate(queries: &Queries, job: &DocumentGenerationJob) -> Result<()> {
+    let document_type = DocumentType::parse(&job.document_type)
+        .ok_or_else(|| anyhow!("Unknown document type: {}", job.document_type))?;
+    let data = gather_document_data(&job.project_id, queries).await?;
+    let content = render_document(&data, document_type)?;
+
+    write_document(queries, &job.project_id, document_type, &content)?;
+    Ok(())
+}
+
+fn finish_job(queries: &Queries, job: &DocumentGenerationJob, result: Result<()>) -> Result<()> {
+    match result {
+        Ok(()) => queries.finish_generation_job(&job.id, "completed", None, job.retry_count),
+        // Retrying cannot help until the drift is resolved
+        Err(e) if e.is::<DocumentEdited>() => {
+            queries.finish_generation_job(&job.id, "failed", Some(&e.to_string()), job.retry_count)
+        }
+        Err(e) if job.retry_count < MAX_RETRIES => {
+            queries.finish_generation_job(&job.id, "pending", Some(&e.to_string()), job.retry_count + 1)
+        }
+        Err(e) => queries.finish_generation_job(&job.id, "failed", Some(&e.to_string()), job.retry_count),
+    }
+}
+
+fn emit_progress(app: &AppHandle, queries: &Queries, project_id: &str, job_id: &str) {
+    let jobs = match queries.get_generation_jobs(project_id) {
+        Ok(jobs) => jobs,
+        Err(e) => {
+            eprintln!("Failed to read document queue for {}: {}", project_id, e);
+            return;
+        }
+    };
+    let Some(job) = jobs.iter().find(|j| j.id == job_id) else { return };
+
+    let progress = GenerationProgress {
+        project_id: project_id.to_string(),
+        job: job.clone(),
+        completed: jobs.iter().filter(|j| j.status == "completed").count(),
+        total: jobs.iter().filter(|j| j.status != "cancelled").count(),
+    };
+    let _ = app.emit(GENERATION_PROGRESS_EVENT, progress);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(document_type: DocumentType, status: &str) -> DocumentGenerationJob {
+        DocumentGenerationJob {
+            id: document_type.as_str().to_string(),
+            project_id: "p1".to_string(),
+            document_type: document_type.as_str().to_string(),
+            dependencies: document_type.dependencies().iter().map(|d| d.as_str().to_string()).collect(),
+            status: status.to_string(),
+            priority: DEFAULT_PRIORITY,
+            started_at: None,
+            completed_at: None,
+            error_message: None,
+            retry_count: 0,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_jobs_wait_for_their_dependencies() {
+        let now = Utc::now();
+        let mut jobs = vec![
+            job(DocumentType::DataFlow, "pending"),
+            job(DocumentType::SystemArchitecture, "pending"),
+            job(DocumentType::UserEpics, "processing"),
+        ];
+        // Architecture waits on user epics, data flow on architecture
+        assert!(next_job(&jobs, now).is_none());
+
+        jobs[2].status = "completed".to_string();
+        assert!(matches!(next_job(&jobs, now), Some(NextJob::Run(j)) if j.id == "system_architecture"));
+
+        jobs[1].status = "failed".to_string();
+        assert!(matches!(next_job(&jobs, now), Some(NextJob::Blocked(j, _)) if j.id == "data_flow"));
+    }
+
+    #[test]
+    fn test_retries_back_off() {
+        let mut retried = job(DocumentType::ProductVision, "pending");
+        retried.retry_count = 2;
+        retried.started_at = Some(Utc::now());
+
+        assert_eq!(retry_at(&retried), Some(retried.started_at.unwrap() + chrono::Duration::seconds(10)));
+        assert!(next_job(std::slice::from_ref(&retried), Utc::now()).is_none());
+        assert!(next_job(std::slice::from_ref(&retried), Utc::now() + chrono::Duration::seconds(11)).is_some());
+    }
+}
@@ -0,0 +1,467 @@
+use crate::commands::documents::DocumentType;
+use crate::commands::schema_export::{render_schema, SchemaFormat};
+use crate::commands::workspace::{gather_project_data, ProjectExportData};
+use crate::db::{models::*, queries::Queries};
+use anyhow::Result;
+use minijinja::{Environment, UndefinedBehavior};
+use serde_json::{json, Value};
+
+// Templates are compiled once; `inline` and `cell` keep user text from breaking headings and tables
+lazy_static::lazy_static! {
+    static ref TEMPLATES: Environment<'static> = templates();
+}
+
+/// Everything the document templates draw on: the project export plus the architecture,
+/// schema, data flow and UI tables
+pub struct DocumentData {
+    pub export: ProjectExportData,
+    pub architecture: Vec<SystemArchitecture>,
+    pub tables: Vec<(DatabaseTable, Vec<DatabaseColumn>)>,
+    pub relationships: Vec<DatabaseRelationship>,
+    pub data_flows: Vec<(DataFlow, Vec<DataFlowStep>)>,
+    pub ui_screens: Vec<UIScreen>,
+    pub ui_components: Vec<UIComponent>,
+    pub design_tokens: Vec<DesignToken>,
+    pub atomic_components: Vec<AtomicComponent>,
+}
+
+pub async fn gather_document_data(project_id: &str, queries: &Queries) -> Result<DocumentData> {
+    Ok(DocumentData {
+        export: gather_project_data(project_id, queries).await?,
+        architecture: queries.get_system_architecture(project_id, None)?,
+        tables: queries.get_database_tables(project_id)?,
+        relationships: queries.get_database_relationships(project_id)?,
+        data_flows: queries.get_data_flows(project_id)?,
+        ui_screens: queries.get_ui_screens(project_id)?,
+        ui_components: queries.get_ui_components(project_id)?,
+        design_tokens: queries.get_design_tokens(project_id)?,
+        atomic_components: queries.get_atomic_components(project_id)?,
+    })
+}
+
+/// Markdown for a document type. The same data always renders to the same bytes, which drift
+/// detection relies on: nothing time-dependent is included and whitespace is normalised.
+pub fn render_document(data: &DocumentData, document_type: DocumentType) -> Result<String> {
+    let context = match document_type {
+        DocumentType::ProductVision => product_vision_context(data),
+        DocumentType::UserEpics => user_epics_context(data),
+        DocumentType::SystemArchitecture => system_architecture_context(data),
+        DocumentType::DesignSystem => design_system_context(data),
+        DocumentType::DataFlow => data_flow_context(data),
+        DocumentType::EntityRelationshipDiagram => entity_relationship_context(data),
+    };
+
+    let template = TEMPLATES.get_template(document_type.as_str())?;
+    Ok(normalize_markdown(&template.render(context)?))
+}
+
+fn templates() -> Environment<'static> {
+    let mut env = Environment::new();
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+    env.add_filter("inline", |text: String| inline(&text));
+    env.add_filter("cell", |text: String| inline(&text).replace('|', "\\|"));
+
+    let sources = [
+        (DocumentType::ProductVision, include_str!("../../templates/documents/product_vision.md")),
+        (DocumentType::UserEpics, include_str!("../../templates/documents/user_epics.md")),
+        (DocumentType::SystemArchitecture, include_str!("../../templates/documents/system_architecture.md")),
+        (DocumentType::DesignSystem, include_str!("../../templates/documents/design_system.md")),
+        (DocumentType::DataFlow, include_str!("../../templates/documents/data_flow.md")),
+        (DocumentType::EntityRelationshipDiagram, include_str!("../../templates/documents/entity_relationship_diagram.md")),
+    ];
+    for (document_type, source) in sources {
+        env.add_template(document_type.as_str(), source)
+            .expect("document templates are valid");
+    }
+    env
+}
+
+/// One line of text with runs of whitespace collapsed
+fn inline(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Unix newlines, no trailing spaces, at most one blank line in a row and exactly one final newline
+fn normalize_markdown(markdown: &str) -> String {
+    let mut out = String::with_capacity(markdown.len());
+    let mut previous_blank = true;
+    for line in markdown.lines() {
+        let line = line.trim_end();
+        if line.is_empty() && previous_blank {
+            continue;
+        }
+        previous_blank = line.is_empty();
+        out.push_str(line);
+        out.push('\n');
+    }
+    while out.ends_with("\n\n") {
+        out.pop();
+    }
+    out
+}
+
+fn product_vision_context(data: &DocumentData) -> Value {
+    let export = &data.export;
+    let problem = export.core_problem.as_ref()
+        .map(|p| p.validated_problem.as_deref().unwrap_or(&p.original_input).trim().to_string());
+
+    let personas: Vec<Value> = export.personas.iter()
+        .map(|persona| json!({
+            "name": persona.name,
+            "role": persona.role,
+            "industry": persona.industry,
+            "pain_degree": persona.pain_degree,
+            "pain_points": export.pain_points.iter()
+                .filter(|p| p.persona_id == persona.id)
+                .map(|p| json!({ "description": p.description, "severity": p.severity.clone().unwrap_or_default() }))
+                .collect::<Vec<_>>(),
+        }))
+        .collect();
+
+    let solutions: Vec<Value> = export.solutions.iter()
+        .filter(|s| s.is_selected)
+        .map(|solution| json!({
+            "title": solution.title,
+            "description": solution.description.trim(),
+            "addresses": export.solution_mappings.iter()
+                .filter(|m| m.solution_id == solution.id)
+                .filter_map(|m| export.pain_points.iter().find(|p| p.id == m.pain_point_id))
+                .map(|p| p.description.as_str())
+                .collect::<Vec<_>>(),
+        }))
+        .collect();
+
+    json!({
+        "project_name": export.project.name,
+        "problem": problem.unwrap_or_default(),
+        "personas": personas,
+        "solutions": solutions,
+    })
+}
+
+fn user_epics_context(data: &DocumentData) -> Value {
+    let stories: Vec<Value> = data.export.user_stories.iter()
+        .enumerate()
+        .map(|(index, story)| json!({
+            "number": index + 1,
+            "title": story.title,
+            "as_a": story.as_a,
+            "i_want": story.i_want,
+            "so_that": story.so_that,
+            "priority": story.priority.clone().unwrap_or_default(),
+            "complexity_points": story.complexity_points,
+            "acceptance_criteria": story.acceptance_criteria,
+        }))
+        .collect();
+
+    json!({ "project_name": data.export.project.name, "stories": stories })
+}
+
+fn system_architecture_context(data: &DocumentData) -> Value {
+    let layers: Vec<Value> = data.architecture.iter()
+        .map(|layer| json!({
+            "layer": layer.layer,
+            "technology": layer.technology,
+            "justification": layer.justification.trim(),
+        }))
+        .collect();
+
+    json!({
+        "project_name": data.export.project.name,
+        "layers": layers,
+        "diagram": architecture_diagram(&data.architecture),
+    })
+}
+
+fn design_system_context(data: &DocumentData) -> Value {
+    // Tokens come back ordered by category, so consecutive runs are the groups
+    let mut token_groups: Vec<(String, Vec<Value>)> = Vec::new();
+    for token in &data.design_tokens {
+        let entry = json!({ "name": token.token_name, "value": token.token_value });
+        match token_groups.last_mut() {
+            Some((category, tokens)) if *category == token.token_category => tokens.push(entry),
+            _ => token_groups.push((token.token_category.clone(), vec![entry])),
+        }
+    }
+
+    let mut levels: Vec<&str> = vec!["atom", "molecule", "organism"];
+    for component in &data.atomic_components {
+        if !levels.contains(&component.component_level.as_str()) {
+            levels.push(&component.component_level);
+        }
+    }
+    let component_groups: Vec<Value> = levels.into_iter()
+        .filter_map(|level| {
+            let components: Vec<Value> = data.atomic_components.iter()
+                .filter(|c| c.component_level == level)
+                .map(|c| json!({
+                    "name": c.component_name,
+                    "description": c.description.as_deref().unwrap_or_default().trim(),
+                    "composed_of": c.composed_of,
+                }))
+                .collect();
+            (!components.is_empty()).then(|| json!({ "title": format!("{}s", capitalize(level)), "components": components }))
+        })
+        .collect();
+
+    let screens: Vec<Value> = data.ui_screens.iter()
+        .map(|screen| json!({
+            "name": screen.screen_name,
+            "route": screen.route_path.clone().unwrap_or_default(),
+            "description": screen.description.as_deref().unwrap_or_default().trim(),
+            "components": data.ui_components.iter()
+                .filter(|c| c.screen_id == screen.id)
+                .map(|c| json!({
+                    "name": c.component_name,
+                    "component_type": c.component_type.clone().unwrap_or_default(),
+                    "data": c.data_displayed.clone().unwrap_or_default(),
+                }))
+                .collect::<Vec<_>>(),
+        }))
+        .collect();
+
+    json!({
+        "project_name": data.export.project.name,
+        "token_groups": token_groups.into_iter()
+            .map(|(category, tokens)| json!({ "title": capitalize(&category), "tokens": tokens }))
+            .collect::<Vec<_>>(),
+        "component_groups": component_groups,
+        "screens": screens,
+    })
+}
+
+fn data_flow_context(data: &DocumentData) -> Value {
+    let flows: Vec<Value> = data.data_flows.iter()
+        .enumerate()
+        .map(|(index, (flow, steps))| {
+            let title = data.export.user_stories.iter()
+                .find(|s| s.id == flow.user_story_id)
+                .map(|s| s.title.as_str())
+                .unwrap_or(&flow.description);
+            json!({
+                "number": index + 1,
+                "title": title,
+                "description": flow.description.trim(),
+                "diagram": sequence_diagram(steps),
+                "steps": steps.iter()
+                    .map(|step| json!({
+                        "number": step.step_number,
+                        "source": step.source,
+                        "target": step.target,
+                        "action": step.action,
+                        "payload": step.data_payload.as_deref().map(|p| format!("`{}`", p)).unwrap_or_default(),
+                    }))
+                    .collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+
+    json!({ "project_name": data.export.project.name, "flows": flows })
+}
+
+fn entity_relationship_context(data: &DocumentData) -> Value {
+    let tables: Vec<Value> = data.tables.iter()
+        .map(|(table, columns)| json!({
+            "name": table.table_name,
+            "columns": columns.iter()
+                .map(|column| {
+                    let mut keys = Vec::new();
+                    if column.is_primary_key {
+                        keys.push("PK".to_string());
+                    }
+                    match &column.references_table {
+                        Some(referenced) => keys.push(format!("FK → {}", referenced)),
+                        None if column.is_foreign_key => keys.push("FK".to_string()),
+                        None => {}
+                    }
+                    json!({
+                        "name": column.column_name,
+                        "data_type": column.data_type,
+                        "keys": keys.join(", "),
+                        "constraints": column.constraints.join(", "),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        }))
+        .collect();
+
+    let relationships: Vec<Value> = data.relationships.iter()
+        .map(|r| json!({
+            "from": r.from_table,
+            "to": r.to_table,
+            "kind": r.relationship_type.replace(['-', '_'], " "),
+        }))
+        .collect();
+
+    json!({
+        "project_name": data.export.project.name,
+        "diagram": render_schema(SchemaFormat::Mermaid, &data.tables, &data.relationships).trim_end(),
+        "tables": tables,
+        "relationships": relationships,
+    })
+}
+
+/// Flowchart of the stack: the frontend calls the backend, which talks to every other layer
+fn architecture_diagram(layers: &[SystemArchitecture]) -> String {
+    let mut out = String::from("flowchart TD\n");
+    for (index, layer) in layers.iter().enumerate() {
+        out.push_str(&format!(
+            "    L{}[\"{}<br/>{}\"]\n",
+            index,
+            mermaid_text(&layer.layer),
+            mermaid_text(&layer.technology)
+        ));
+    }
+
+    let is = |layer: &SystemArchitecture, name: &str| layer.layer.eq_ignore_ascii_case(name);
+    if let Some(backend) = layers.iter().position(|l| is(l, "backend")) {
+        for (index, layer) in layers.iter().enumerate() {
+            if index == backend {
+                continue;
+            }
+            if is(layer, "frontend") {
+                out.push_str(&format!("    L{} --> L{}\n", index, backend));
+            } else {
+                out.push_str(&format!("    L{} --> L{}\n", backend, index));
+            }
+        }
+    }
+    out.trim_end().to_string()
+}
+
+/// Sequence diagram of a flow's steps; participants appear in the order they are first used
+fn sequence_diagram(steps: &[DataFlowStep]) -> String {
+    let mut participants: Vec<&str> = Vec::new();
+    for step in steps {
+        for name in [step.source.as_str(), step.target.as_str()] {
+            if !participants.contains(&name) {
+                participants.push(name);
+            }
+        }
+    }
+    let id = |name: &str| participants.iter().position(|p| *p == name).unwrap_or_default();
+
+    let mut out = String::from("sequenceDiagram\n");
+    for (index, name) in participants.iter().enumerate() {
+        out.push_str(&format!("    participant P{} as {}\n", index, mermaid_text(name)));
+    }
+    for step in steps {
+        out.push_str(&format!(
+            "    P{}->>P{}: {}\n",
+            id(&step.source),
+            id(&step.target),
+            mermaid_text(&step.action)
+        ));
+    }
+    out.trim_end().to_string()
+}
+
+/// Label text that cannot end a Mermaid statement or string early
+fn mermaid_text(text: &str) -> String {
+    inline(text).replace(';', "#59;").replace('"', "#quot;")
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn sample() -> DocumentData {
+        let story = UserStory {
+            id: "us1".into(),
+            project_id: "p1".into(),
+            title: "Stock alerts".into(),
+            as_a: "shop owner".into(),
+            i_want: "to be told when | stock is low".into(),
+            so_that: "I can reorder".into(),
+            acceptance_criteria: vec!["Alert below 10 units".into()],
+            priority: Some("High".into()),
+            complexity_points: None,
+            position: 0,
+            is_edited: false,
+            original_content: None,
+            edited_content: None,
+            created_at: Utc::now(),
+        };
+        let flow = DataFlow { id: "f1".into(), user_story_id: "us1".into(), description: "Data flow for alerts".into(), created_at: Utc::now() };
+        let step = |n: i32, action: &str, source: &str, target: &str| DataFlowStep {
+            id: format!("s{}", n),
+            data_flow_id: "f1".into(),
+            step_number: n,
+            action: action.into(),
+            source: source.into(),
+            target: target.into(),
+            data_payload: None,
+            created_at: Utc::now(),
+        };
+        let layer = |name: &str, technology: &str| SystemArchitecture {
+            id: name.into(),
+            project_id: "p1".into(),
+            layer: name.into(),
+            technology: technology.into(),
+            justification: "Because".into(),
+            version: 1,
+            created_at: Utc::now(),
+        };
+
+        DocumentData {
+            export: ProjectExportData {
+                project: Project { id: "p1".into(), name: "Inventory".into(), ..Default::default() },
+                core_problem: None,
+                personas: vec![],
+                pain_points: vec![],
+                solutions: vec![],
+                solution_mappings: vec![],
+                user_stories: vec![story],
+                canvas_state: None,
+            },
+            architecture: vec![layer("Frontend", "React"), layer("Backend", "Node.js"), layer("Database", "PostgreSQL")],
+            tables: vec![],
+            relationships: vec![],
+            data_flows: vec![(flow, vec![step(1, "Request alerts; filtered", "React", "API"), step(2, "Query \"stock\"", "API", "Database")])],
+            ui_screens: vec![],
+            ui_components: vec![],
+            design_tokens: vec![],
+            atomic_components: vec![],
+        }
+    }
+
+    #[test]
+    fn test_every_document_renders_stable_markdown() {
+        let data = sample();
+        for document_type in DocumentType::ALL {
+            let first = render_document(&data, document_type).unwrap();
+            assert_eq!(first, render_document(&data, document_type).unwrap());
+            assert!(first.starts_with("# ") && first.ends_with("\n") && !first.ends_with("\n\n"));
+            assert!(!first.contains("\n\n\n") && !first.lines().any(|l| l.ends_with(' ')));
+            assert!(!first.contains("none"), "{}", first);
+        }
+    }
+
+    #[test]
+    fn test_diagrams_and_tables_escape_user_text() {
+        let data = sample();
+
+        let architecture = render_document(&data, DocumentType::SystemArchitecture).unwrap();
+        assert!(architecture.contains("```mermaid\nflowchart TD\n    L0[\"Frontend<br/>React\"]"));
+        assert!(architecture.contains("    L0 --> L1\n    L1 --> L2\n```"));
+
+        let flows = render_document(&data, DocumentType::DataFlow).unwrap();
+        assert!(flows.contains("## Flow 1: Stock alerts"));
+        assert!(flows.contains("    P0->>P1: Request alerts#59; filtered\n    P1->>P2: Query #quot;stock#quot;\n```"));
+
+        let epics = render_document(&data, DocumentType::UserEpics).unwrap();
+        assert!(epics.contains("**I want** to be told when | stock is low\\\n"));
+        assert!(epics.contains("- **Priority:** High\n\n**Acceptance Criteria:**\n\n- [ ] Alert below 10 units\n"));
+    }
+}
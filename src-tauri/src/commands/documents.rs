@@ -0,0 +1,500 @@
+use crate::commands::document_drift::kept_edits;
+use crate::commands::document_render::{gather_document_data, render_document};
+use crate::commands::export_history::{new_export, ExportKind, Exported};
+use crate::commands::workspace::resolve_project_folder;
+use crate::db::{models::*, queries::Queries, DbPool};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, State};
+use uuid::Uuid;
+
+/// Folder under the project where generated documents are written
+pub const DOCS_DIR: &str = "docs";
+
+// One watcher per project docs folder, and the hash of each document file we are writing
+lazy_static::lazy_static! {
+    static ref DOCUMENT_WATCHERS: Arc<Mutex<HashMap<String, RecommendedWatcher>>> = Arc::new(Mutex::new(HashMap::new()));
+    static ref PENDING_WRITES: Mutex<HashMap<PathBuf, String>> = Mutex::new(HashMap::new());
+}
+
+/// A document file holds edits that writing it would lose
+#[derive(Debug)]
+pub struct DocumentEdited {
+    pub document_type: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for DocumentEdited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; resolve the document drift to replace it", self.reason)
+    }
+}
+
+impl std::error::Error for DocumentEdited {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentType {
+    ProductVision,
+    UserEpics,
+    SystemArchitecture,
+    DesignSystem,
+    DataFlow,
+    EntityRelationshipDiagram,
+}
+
+impl DocumentType {
+    pub const ALL: [DocumentType; 6] = [
+        DocumentType::ProductVision,
+        DocumentType::UserEpics,
+        DocumentType::SystemArchitecture,
+        DocumentType::DesignSystem,
+        DocumentType::DataFlow,
+        DocumentType::EntityRelationshipDiagram,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DocumentType::ProductVision => "product_vision",
+            DocumentType::UserEpics => "user_epics",
+            DocumentType::SystemArchitecture => "system_architecture",
+            DocumentType::DesignSystem => "design_system",
+            DocumentType::DataFlow => "data_flow",
+            DocumentType::EntityRelationshipDiagram => "entity_relationship_diagram",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<DocumentType> {
+        Self::ALL.into_iter().find(|t| t.as_str() == s)
+    }
+
+    /// Documents whose content this one builds on, so they must be generated first
+    pub fn dependencies(&self) -> &'static [DocumentType] {
+        match self {
+            DocumentType::ProductVision => &[],
+            DocumentType::UserEpics => &[DocumentType::ProductVision],
+            DocumentType::SystemArchitecture => &[DocumentType::ProductVision, DocumentType::UserEpics],
+            DocumentType::DesignSystem => &[DocumentType::ProductVision],
+            DocumentType::DataFlow => &[DocumentType::SystemArchitecture],
+            DocumentType::EntityRelationshipDiagram => &[DocumentType::SystemArchitecture, DocumentType::DataFlow],
+        }
+    }
+
+    /// Same names the frontend export uses
+    pub fn file_name(&self) -> String {
+        format!("{}.md", self.as_str())
+    }
+}
+
+/// Content generated elsewhere (e.g. the frontend export) to write as a document
+#[derive(Debug, Deserialize)]
+pub struct DocumentContent {
+    pub document_type: String,
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MaterializeDocumentsRequest {
+    pub project_id: String,
+    pub documents: Option<Vec<DocumentContent>>,
+    /// Also replace files with edits made outside the app
+    pub overwrite_edits: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SkippedDocument {
+    pub document_type: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MaterializedDocuments {
+    pub written: Vec<ProjectDocument>,
+    /// Documents left alone because their files hold edits
+    pub skipped: Vec<SkippedDocument>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectDocumentFile {
+    pub document: ProjectDocument,
+    pub content: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RenderedDocument {
+    pub document_type: String,
+    pub file_name: String,
+    pub content: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RenderedDocuments {
+    pub project_name: String,
+    pub documents: Vec<RenderedDocument>,
+}
+
+/// Payload of `project-document-changed`
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentChanged {
+    pub project_id: String,
+    pub document_type: String,
+    pub file_path: String,
+    pub is_synced: bool,
+}
+
+/// Write every document, with supplied content taking the place of the rendered one, into the project's
+/// `docs/` folder and start watching it for outside edits. Files with edits are skipped unless
+/// `overwrite_edits` is set.
+#[tauri::command]
+pub async fn materialize_project_documents(
+    app: AppHandle,
+    request: MaterializeDocumentsRequest,
+    pool: State<'_, DbPool>,
+) -> Result<MaterializedDocuments, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    let data = gather_document_data(&request.project_id, &queries)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut contents: Vec<(DocumentType, String)> = DocumentType::ALL
+        .into_iter()
+        .map(|t| render_document(&data, t).map(|content| (t, content)))
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    for supplied in request.documents.unwrap_or_default() {
+        let document_type = DocumentType::parse(&supplied.document_type)
+            .ok_or_else(|| format!("Unknown document type: {}", supplied.document_type))?;
+        contents.retain(|(t, _)| *t != document_type);
+        contents.push((document_type, supplied.content));
+    }
+
+    let overwrite = request.overwrite_edits.unwrap_or(false);
+    let mut written = Vec::new();
+    let mut skipped = Vec::new();
+    for (document_type, content) in contents {
+        let result = if overwrite {
+            overwrite_document(&queries, &request.project_id, document_type, &content)
+        } else {
+            write_document(&queries, &request.project_id, document_type, &content)
+        };
+        match result {
+            Ok(document) => written.push(document),
+            Err(e) => match e.downcast::<DocumentEdited>() {
+                Ok(edited) => skipped.push(SkippedDocument { document_type: edited.document_type, reason: edited.reason }),
+                Err(e) => return Err(e.to_string()),
+            },
+        }
+    }
+
+    watch_documents(&app, pool.inner().clone(), &request.project_id).map_err(|e| e.to_string())?;
+    Ok(MaterializedDocuments { written, skipped })
+}
+
+/// Render documents without writing them, e.g. for the export dialog, and log the export.
+/// Defaults to every type.
+#[tauri::command]
+pub async fn render_project_documents(
+    project_id: String,
+    document_types: Option<Vec<String>>,
+    pool: State<'_, DbPool>,
+) -> Result<RenderedDocuments, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    Ok(render_project_documents_with_record(&queries, project_id, document_types).await?.output)
+}
+
+pub async fn render_project_documents_with_record(
+    queries: &Queries,
+    project_id: String,
+    document_types: Option<Vec<String>>,
+) -> Result<Exported<RenderedDocuments>, String> {
+    let types = match &document_types {
+        Some(types) => types.iter()
+            .map(|t| DocumentType::parse(t).ok_or_else(|| format!("Unknown document type: {}", t)))
+            .collect::<Result<Vec<_>, _>>()?,
+        None => DocumentType::ALL.to_vec(),
+    };
+    let data = gather_document_data(&project_id, queries)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let documents = types.into_iter()
+        .map(|t| Ok(RenderedDocument {
+            document_type: t.as_str().to_string(),
+            file_name: t.file_name(),
+            content: render_document(&data, t).map_err(|e| e.to_string())?,
+        }))
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let args = serde_json::json!({ "project_id": project_id, "document_types": document_types });
+    let record = ExportRecord {
+        file_size_bytes: Some(documents.iter().map(|d| d.content.len() as i64).sum()),
+        included_sections: documents.iter().map(|d| d.document_type.clone()).collect(),
+        ..new_export(&project_id, ExportKind::Documents, "markdown", &args).map_err(|e| e.to_string())?
+    };
+    queries.record_export(&record).map_err(|e| e.to_string())?;
+
+    let output = RenderedDocuments { project_name: data.export.project.name, documents };
+    Ok(Exported { output, record })
+}
+
+#[tauri::command]
+pub async fn get_project_documents(
+    project_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<Vec<ProjectDocument>, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    queries.get_project_documents(&project_id).map_err(|e| e.to_string())
+}
+
+/// Read a document back from disk, refreshing its sync state
+#[tauri::command]
+pub async fn read_project_document(
+    project_id: String,
+    document_type: String,
+    pool: State<'_, DbPool>,
+) -> Result<ProjectDocumentFile, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    let document = queries
+        .get_project_document(&project_id, &document_type)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Document '{}' has not been written yet", document_type))?;
+    let content = fs::read_to_string(&document.file_path)
+        .map_err(|e| format!("Failed to read {}: {}", document.file_path, e))?;
+    let document = refresh_disk_state(&queries, document).map_err(|e| e.to_string())?;
+
+    Ok(ProjectDocumentFile { document, content })
+}
+
+#[tauri::command]
+pub async fn watch_project_documents(
+    app: AppHandle,
+    project_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<(), String> {
+    watch_documents(&app, pool.inner().clone(), &project_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn unwatch_project_documents(project_id: String) -> Result<(), String> {
+    DOCUMENT_WATCHERS.lock().unwrap().remove(&project_id);
+    Ok(())
+}
+
+pub fn docs_folder(queries: &Queries, project_id: &str) -> Result<PathBuf> {
+    Ok(resolve_project_folder(queries, project_id)?.join(DOCS_DIR))
+}
+
+pub fn hash_content(content: &[u8]) -> String {
+    Sha256::digest(content).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Write one document and record what was written. Fails with [`DocumentEdited`] when the file
+/// has edits made outside the app, or edits kept in a drift resolution.
+pub fn write_document(queries: &Queries, project_id: &str, document_type: DocumentType, content: &str) -> Result<ProjectDocument> {
+    if let Some(document) = queries.get_project_document(project_id, document_type.as_str())? {
+        if let Some(reason) = unsaved_edits(queries, &document)? {
+            return Err(DocumentEdited { document_type: document.document_type, reason }.into());
+        }
+    }
+    overwrite_document(queries, project_id, document_type, content)
+}
+
+/// Write one document whatever the file holds, e.g. when resolving drift
+pub fn overwrite_document(queries: &Queries, project_id: &str, document_type: DocumentType, content: &str) -> Result<ProjectDocument> {
+    let dir = docs_folder(queries, project_id)?;
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(document_type.file_name());
+    let hash = hash_content(content.as_bytes());
+
+    // The watcher ignores events for this content until the record below is saved
+    PENDING_WRITES.lock().unwrap().insert(path.clone(), hash.clone());
+    let result = replace_file(&path, content).and_then(|_| {
+        let existing = queries.get_project_document(project_id, document_type.as_str())?;
+        let document = ProjectDocument {
+            id: existing.as_ref().map(|d| d.id.clone()).unwrap_or_else(|| Uuid::new_v4().to_string()),
+            project_id: project_id.to_string(),
+            document_type: document_type.as_str().to_string(),
+            file_path: path.to_string_lossy().to_string(),
+            file_hash: Some(hash),
+            last_modified: modified_time(&path),
+            content: Some(content.to_string()),
+            is_synced: true,
+            created_at: existing.map(|d| d.created_at).unwrap_or_else(Utc::now),
+            updated_at: Utc::now(),
+        };
+        queries.save_project_document(&document)?;
+        Ok(document)
+    });
+    PENDING_WRITES.lock().unwrap().remove(&path);
+    result
+}
+
+/// Why overwriting a document's file would lose work, if it would
+fn unsaved_edits(queries: &Queries, document: &ProjectDocument) -> Result<Option<String>> {
+    // Nothing to lose when the file is gone
+    let Ok(bytes) = fs::read(&document.file_path) else {
+        return Ok(None);
+    };
+    if !document.is_synced || document.file_hash.as_deref() != Some(hash_content(&bytes).as_str()) {
+        return Ok(Some(format!("{} has edits made outside the app", document.file_path)));
+    }
+    let kept = kept_edits(queries, &document.project_id)?;
+    if document.file_hash.is_some() && kept.get(&document.document_type) == document.file_hash.as_ref() {
+        return Ok(Some(format!("{} holds edits kept when resolving drift", document.file_path)));
+    }
+    Ok(None)
+}
+
+/// Write through a temporary file in the same folder so the file is never seen half written
+fn replace_file(path: &Path, content: &str) -> Result<()> {
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let temp = path.with_file_name(format!(".{}.{}.tmp", file_name, Uuid::new_v4()));
+    fs::write(&temp, content).map_err(|e| anyhow!("Failed to write {}: {}", temp.display(), e))?;
+    fs::rename(&temp, path).map_err(|e| {
+        let _ = fs::remove_file(&temp);
+        anyhow!("Failed to write {}: {}", path.display(), e)
+    })
+}
+
+/// Compare the file on disk with what was last written and store the result
+pub fn refresh_disk_state(queries: &Queries, mut document: ProjectDocument) -> Result<ProjectDocument> {
+    let path = Path::new(&document.file_path);
+    let is_synced = match fs::read(path) {
+        Ok(bytes) => document.file_hash.as_deref() == Some(hash_content(&bytes).as_str()),
+        // A deleted file no longer matches either
+        Err(_) => false,
+    };
+    let modified = modified_time(path).unwrap_or_else(Utc::now);
+
+    if is_synced != document.is_synced || document.last_modified != Some(modified) {
+        queries.mark_project_document_disk_state(&document.id, modified, is_synced)?;
+        document.is_synced = is_synced;
+        document.last_modified = Some(modified);
+    }
+    Ok(document)
+}
+
+/// Take the file on disk as the new baseline for its document
+pub fn adopt_disk_content(queries: &Queries, mut document: ProjectDocument) -> Result<ProjectDocument> {
+    let path = Path::new(&document.file_path);
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", document.file_path, e))?;
+
+    document.file_hash = Some(hash_content(content.as_bytes()));
+    document.last_modified = modified_time(path);
+    document.content = Some(content);
+    document.is_synced = true;
+    document.updated_at = Utc::now();
+    queries.save_project_document(&document)?;
+    Ok(document)
+}
+
+fn modified_time(path: &Path) -> Option<DateTime<Utc>> {
+    fs::metadata(path).and_then(|m| m.modified()).ok().map(DateTime::<Utc>::from)
+}
+
+fn watch_documents(app: &AppHandle, pool: DbPool, project_id: &str) -> Result<()> {
+    let mut watchers = DOCUMENT_WATCHERS.lock().unwrap();
+    if watchers.contains_key(project_id) {
+        return Ok(());
+    }
+
+    let dir = docs_folder(&Queries::new(pool.clone()), project_id)?;
+    fs::create_dir_all(&dir)?;
+
+    let app = app.clone();
+    let watched_project = project_id.to_string();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        let Ok(event) = event else { return };
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
+            return;
+        }
+        let queries = Queries::new(pool.clone());
+        for path in &event.paths {
+            match on_file_event(&queries, &watched_project, path) {
+                Ok(Some(changed)) => { let _ = app.emit("project-document-changed", changed); }
+                Ok(None) => {}
+                Err(e) => eprintln!("Failed to update document state for {}: {}", path.display(), e),
+            }
+        }
+    })?;
+    watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+
+    watchers.insert(project_id.to_string(), watcher);
+    Ok(())
+}
+
+/// Handle a change to a file in a watched docs folder; returns what changed if it is a tracked document
+fn on_file_event(queries: &Queries, project_id: &str, path: &Path) -> Result<Option<DocumentChanged>> {
+    let pending = PENDING_WRITES.lock().unwrap().get(path).cloned();
+    if pending.is_some_and(|hash| fs::read(path).is_ok_and(|bytes| hash_content(&bytes) == hash)) {
+        return Ok(None);
+    }
+    let Some(document) = queries.get_project_document_by_path(&path.to_string_lossy())? else {
+        return Ok(None);
+    };
+    if document.project_id != project_id {
+        return Ok(None);
+    }
+
+    let was_synced = document.is_synced;
+    let document = refresh_disk_state(queries, document)?;
+    if document.is_synced == was_synced {
+        return Ok(None);
+    }
+    Ok(Some(DocumentChanged {
+        project_id: document.project_id,
+        document_type: document.document_type,
+        file_path: document.file_path,
+        is_synced: document.is_synced,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_db;
+
+    #[test]
+    fn test_outside_edits_flip_sync_state() {
+        let folder = std::env::temp_dir().join(format!("prob-docs-{}", Uuid::new_v4()));
+        let (_pool, queries) = test_db("p1", Some(&folder));
+
+        let document = write_document(&queries, "p1", DocumentType::ProductVision, "# Vision\n").unwrap();
+        assert!(document.file_path.ends_with("demo-app/docs/product_vision.md"));
+        assert_eq!(document.file_hash.as_deref(), Some(hash_content(b"# Vision\n").as_str()));
+        assert!(on_file_event(&queries, "p1", Path::new(&document.file_path)).unwrap().is_none());
+
+        fs::write(&document.file_path, "# Vision\n\nEdited by hand\n").unwrap();
+        let changed = on_file_event(&queries, "p1", Path::new(&document.file_path)).unwrap().unwrap();
+        assert!(!changed.is_synced);
+        assert!(!queries.get_project_document("p1", "product_vision").unwrap().unwrap().is_synced);
+        let refused = write_document(&queries, "p1", DocumentType::ProductVision, "# Vision\n").unwrap_err();
+        assert!(refused.is::<DocumentEdited>());
+        assert_eq!(fs::read_to_string(&document.file_path).unwrap(), "# Vision\n\nEdited by hand\n");
+
+        fs::write(&document.file_path, "# Vision\n").unwrap();
+        assert!(on_file_event(&queries, "p1", Path::new(&document.file_path)).unwrap().unwrap().is_synced);
+
+        PENDING_WRITES.lock().unwrap().insert(PathBuf::from(&document.file_path), hash_content(b"# Vision 2\n"));
+        fs::write(&document.file_path, "# Vision 2\n").unwrap();
+        assert!(on_file_event(&queries, "p1", Path::new(&document.file_path)).unwrap().is_none());
+        PENDING_WRITES.lock().unwrap().clear();
+        assert!(write_document(&queries, "p1", DocumentType::ProductVision, "# Vision 2\n").is_err());
+        overwrite_document(&queries, "p1", DocumentType::ProductVision, "# Vision 3\n").unwrap();
+        assert!(write_document(&queries, "p1", DocumentType::ProductVision, "# Vision 4\n").unwrap().is_synced);
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
@@ -0,0 +1,362 @@
+use crate::commands::canvas::{export_canvas_image_with_record, ExportCanvasRequest};
+use crate::commands::design_tokens::{export_design_tokens_with_record, ExportDesignTokensRequest};
+use crate::commands::documents::render_project_documents_with_record;
+use crate::commands::filesystem::resolve_within;
+use crate::commands::report_export::{export_report, ExportReportRequest};
+use crate::commands::schema_export::{export_database_schema_with_record, ExportSchemaRequest};
+use crate::commands::workspace::{export_project_with_record, resolve_project_folder};
+use crate::db::{models::*, queries::Queries, DbPool};
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, State};
+use uuid::Uuid;
+
+/// The command that produced an export. Stored as `kind` in `export_config` next to the
+/// command's arguments, so `re_export` can run it again.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportKind {
+    Project,
+    Report,
+    CanvasImage,
+    DatabaseSchema,
+    DesignTokens,
+    Documents,
+    /// Content generated by the frontend and saved with `save_export_file`
+    File,
+}
+
+impl ExportKind {
+    pub const ALL: [ExportKind; 7] = [
+        ExportKind::Project,
+        ExportKind::Report,
+        ExportKind::CanvasImage,
+        ExportKind::DatabaseSchema,
+        ExportKind::DesignTokens,
+        ExportKind::Documents,
+        ExportKind::File,
+    ];
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|k| k.as_str() == kind)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportKind::Project => "project",
+            ExportKind::Report => "report",
+            ExportKind::CanvasImage => "canvas_image",
+            ExportKind::DatabaseSchema => "database_schema",
+            ExportKind::DesignTokens => "design_tokens",
+            ExportKind::Documents => "documents",
+            ExportKind::File => "file",
+        }
+    }
+}
+
+/// Result of `re_export`: the new history entry and whatever the original command returns
+#[derive(Debug, Serialize)]
+pub struct ReExportResponse {
+    pub export: ExportRecord,
+    pub output: JsonValue,
+}
+
+/// What an export command returns, with the history entry it recorded
+#[derive(Debug)]
+pub struct Exported<T> {
+    pub output: T,
+    pub record: ExportRecord,
+}
+
+#[derive(Debug, Deserialize)]
+struct DocumentsConfig {
+    project_id: String,
+    document_types: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProjectConfig {
+    project_id: String,
+    format: String,
+}
+
+/// A history entry for an export of `project_id`, with `args` as its config. Callers fill in
+/// sections, file and size before recording it.
+pub fn new_export<T: Serialize>(project_id: &str, kind: ExportKind, format: &str, args: &T) -> Result<ExportRecord> {
+    let mut config = serde_json::to_value(args)?;
+    match config.as_object_mut() {
+        Some(object) => {
+            object.insert("kind".to_string(), JsonValue::String(kind.as_str().to_string()));
+        }
+        None => return Err(anyhow!("Export config must be an object")),
+    }
+
+    Ok(ExportRecord {
+        id: Uuid::new_v4().to_string(),
+        project_id: project_id.to_string(),
+        export_format: format.to_string(),
+        export_config: config,
+        file_path: None,
+        file_size_bytes: None,
+        included_sections: Vec::new(),
+        export_version: None,
+        created_at: Utc::now(),
+        created_by: None,
+    })
+}
+
+/// Export history of a project, newest first
+#[tauri::command]
+pub async fn list_exports(
+    project_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<Vec<ExportRecord>, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    queries.get_exports(&project_id).map_err(|e| e.to_string())
+}
+
+/// Run an earlier export again with the same config against the project's current data
+#[tauri::command]
+pub async fn re_export(
+    export_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<ReExportResponse, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    let previous = queries.get_export(&export_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Export not found".to_string())?;
+    let config = previous.export_config.clone();
+    let kind = config.get("kind")
+        .and_then(|k| k.as_str())
+        .and_then(ExportKind::parse)
+        .ok_or_else(|| "This export was recorded without a config and cannot be repeated".to_string())?;
+
+    let (output, recorded) = match kind {
+        ExportKind::Project => {
+            let config: ProjectConfig = args(config)?;
+            output(export_project_with_record(&queries, config.project_id, config.format).await?)?
+        }
+        ExportKind::Report => {
+            let request: ExportReportRequest = args(config)?;
+            let record = export_report(&queries, &request).await.map_err(|e| e.to_string())?;
+            output(Exported { output: record.clone(), record })?
+        }
+        ExportKind::CanvasImage => {
+            let request: ExportCanvasRequest = args(config)?;
+            output(export_canvas_image_with_record(&queries, request)?)?
+        }
+        ExportKind::DatabaseSchema => {
+            let request: ExportSchemaRequest = args(config)?;
+            output(export_database_schema_with_record(&queries, request)?)?
+        }
+        ExportKind::DesignTokens => {
+            let request: ExportDesignTokensRequest = args(config)?;
+            output(export_design_tokens_with_record(&queries, request)?)?
+        }
+        ExportKind::Documents => {
+            let config: DocumentsConfig = args(config)?;
+            output(render_project_documents_with_record(&queries, config.project_id, config.document_types).await?)?
+        }
+        ExportKind::File => {
+            return Err("Files saved from the export dialog were generated by the app; export them again from there".to_string());
+        }
+    };
+
+    let export = queries.get_export(&recorded)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Re-export was not recorded".to_string())?;
+
+    Ok(ReExportResponse { export, output })
+}
+
+/// Save content generated by the export dialog into the project's `exports` folder. The content
+/// is written as UTF-8 text; `file_name` gets an extension from `format` if it has none.
+#[tauri::command]
+pub async fn save_export_file(
+    project_id: String,
+    file_name: String,
+    content: String,
+    format: String,
+    sections: Option<Vec<String>>,
+    config: Option<JsonValue>,
+    pool: State<'_, DbPool>,
+) -> Result<ExportRecord, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    save_file(&queries, &project_id, &file_name, &content, &format, sections, config)
+        .map_err(|e| e.to_string())
+}
+
+/// Copy an exported file or folder into the `destination` folder, which must be the downloads folder or inside
+/// a workspace folder, or into the downloads folder. Existing files are kept. Returns the new path.
+#[tauri::command]
+pub async fn download_export(
+    app: AppHandle,
+    history_id: String,
+    destination: Option<String>,
+    pool: State<'_, DbPool>,
+) -> Result<String, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    let export = queries.get_export(&history_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Export not found".to_string())?;
+    let source = export.file_path
+        .map(PathBuf::from)
+        .ok_or_else(|| "This export was not saved to a file; use re_export to produce it again".to_string())?;
+    if !source.exists() {
+        return Err(format!("Exported file {} no longer exists", source.display()));
+    }
+
+    let downloads = app.path().download_dir().map_err(|e| e.to_string())?;
+    let folder = match destination {
+        Some(folder) => {
+            let mut roots = queries.list_workspace_folders().map_err(|e| e.to_string())?;
+            roots.push(downloads.to_string_lossy().to_string());
+            resolve_within(&folder, &roots).map_err(|e| e.to_string())?
+        }
+        None => downloads,
+    };
+    let name = source.file_name().ok_or_else(|| "Exported file has no name".to_string())?;
+    let target = unique_path(&folder.join(name));
+    // Exports that write several files, like design tokens, record their folder
+    let copied = if source.is_dir() { copy_dir(&source, &target) } else { fs::copy(&source, &target).map(|_| ()) };
+    copied.map_err(|e| format!("Failed to copy export to {}: {}", target.display(), e))?;
+
+    Ok(target.to_string_lossy().to_string())
+}
+
+fn save_file(
+    queries: &Queries,
+    project_id: &str,
+    file_name: &str,
+    content: &str,
+    format: &str,
+    sections: Option<Vec<String>>,
+    config: Option<JsonValue>,
+) -> Result<ExportRecord> {
+    // Only a bare file name is accepted, so the file cannot land outside the exports folder
+    let name = Path::new(file_name)
+        .file_name()
+        .filter(|name| name.to_str() == Some(file_name))
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| anyhow!("Invalid export file name: {}", file_name))?;
+    let name = if Path::new(name).extension().is_some() {
+        name.to_string()
+    } else {
+        format!("{}.{}", name, file_extension(format))
+    };
+
+    let folder = resolve_project_folder(queries, project_id)?.join("exports");
+    fs::create_dir_all(&folder)?;
+    let path = unique_path(&folder.join(name));
+    fs::write(&path, content)?;
+
+    let args = serde_json::json!({ "format": format, "options": config });
+    let record = ExportRecord {
+        file_path: Some(path.to_string_lossy().to_string()),
+        file_size_bytes: Some(content.len() as i64),
+        included_sections: sections.unwrap_or_default(),
+        ..new_export(project_id, ExportKind::File, format, &args)?
+    };
+    queries.record_export(&record)?;
+
+    Ok(record)
+}
+
+fn args<T: serde::de::DeserializeOwned>(config: JsonValue) -> Result<T, String> {
+    serde_json::from_value(config).map_err(|e| format!("Invalid export config: {}", e))
+}
+
+/// The command's output as JSON, and the id of the history entry it recorded
+fn output<T: Serialize>(exported: Exported<T>) -> Result<(JsonValue, String), String> {
+    let output = serde_json::to_value(exported.output).map_err(|e| e.to_string())?;
+    Ok((output, exported.record.id))
+}
+
+fn file_extension(format: &str) -> &str {
+    match format {
+        "markdown" => "md",
+        other => other,
+    }
+}
+
+fn copy_dir(source: &Path, target: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            copy_dir(&path, &target.join(entry.file_name()))?;
+        } else {
+            fs::copy(&path, target.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+/// `path`, or `name (n).ext` next to it if that is taken
+pub fn unique_path(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("export");
+    let extension = path.extension().and_then(|e| e.to_str());
+    (1..)
+        .map(|n| {
+            let name = match extension {
+                Some(extension) => format!("{} ({}).{}", stem, n, extension),
+                None => format!("{} ({})", stem, n),
+            };
+            path.with_file_name(name)
+        })
+        .find(|candidate| !candidate.exists())
+        .expect("an unused file name exists")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_db;
+
+    #[test]
+    fn test_export_config_records_kind_and_arguments() {
+        let request = ExportSchemaRequest {
+            project_id: "p1".to_string(),
+            format: "postgres".to_string(),
+            write_to_workspace: Some(true),
+        };
+        let record = new_export("p1", ExportKind::DatabaseSchema, "postgres", &request).unwrap();
+
+        assert_eq!(record.export_config["kind"], "database_schema");
+        let replayed: ExportSchemaRequest = serde_json::from_value(record.export_config).unwrap();
+        assert_eq!(replayed.format, "postgres");
+        assert_eq!(replayed.write_to_workspace, Some(true));
+    }
+
+    #[test]
+    fn test_saved_files_stay_in_exports_and_are_recorded() {
+        let folder = std::env::temp_dir().join(format!("prob-exports-{}", Uuid::new_v4()));
+        let (_pool, queries) = test_db("p1", Some(&folder));
+
+        let first = save_file(&queries, "p1", "spec", "# Spec\n", "markdown", Some(vec!["problem".to_string()]), None).unwrap();
+        let second = save_file(&queries, "p1", "spec", "# Spec\n", "markdown", None, None).unwrap();
+        assert!(first.file_path.as_deref().unwrap().ends_with("demo-app/exports/spec.md"));
+        assert!(second.file_path.as_deref().unwrap().ends_with("demo-app/exports/spec (1).md"));
+        assert!(save_file(&queries, "p1", "../escape.md", "x", "markdown", None, None).is_err());
+
+        let history = queries.get_exports("p1").unwrap();
+        assert_eq!(history.len(), 2);
+        let saved = history.iter().find(|e| e.id == first.id).unwrap();
+        assert_eq!(saved.included_sections, vec!["problem"]);
+        assert_eq!(saved.file_size_bytes, Some(7));
+        assert_eq!(saved.export_config["kind"], "file");
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
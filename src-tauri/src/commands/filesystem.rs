@@ -1,4 +1,13 @@
+use crate::commands::workspace::resolve_project_folder;
+use crate::db::{queries::Queries, DbPool};
+use anyhow::{anyhow, Result};
 use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::State;
+
+/// Terminal emulators `open_terminal` may launch on Linux, in the order they are tried
+const LINUX_TERMINALS: [&str; 5] = ["gnome-terminal", "konsole", "xterm", "kitty", "alacritty"];
 
 #[tauri::command]
 pub fn get_platform() -> String {
@@ -6,46 +15,186 @@ pub fn get_platform() -> String {
 }
 
 #[tauri::command]
-pub async fn open_terminal(directory: String) -> Result<(), String> {
-    use std::process::Command;
-    
+pub async fn open_terminal(directory: String, pool: State<'_, DbPool>) -> Result<(), String> {
+    let queries = Queries::new(pool.inner().clone());
+    let directory = resolve_sandboxed_dir(&queries, &directory).map_err(|e| e.to_string())?;
+
     let platform = env::consts::OS;
-    
-    let result = match platform {
+
+    let mut command = match platform {
         "macos" => {
-            Command::new("open")
-                .args(&["-a", "Terminal", &directory])
-                .spawn()
-        },
-        "windows" => {
-            Command::new("cmd")
-                .args(&["/c", "start", "cmd", "/k", "cd", &directory])
-                .spawn()
+            let mut command = Command::new("open");
+            command.arg("-a").arg("Terminal").arg(&directory);
+            command
         },
+        "windows" => windows_terminal_command(&directory),
         "linux" => {
-            // Try gnome-terminal first
-            let result = Command::new("gnome-terminal")
-                .arg("--working-directory")
-                .arg(&directory)
-                .spawn();
-                
-            if result.is_err() {
-                // Try xterm
-                Command::new("xterm")
-                    .arg("-e")
-                    .arg(format!("cd {} && bash", directory))
-                    .spawn()
-            } else {
-                result
-            }
+            let (name, path) = find_linux_terminal().ok_or_else(|| format!(
+                "No supported terminal emulator found. Install one of {} or set $TERMINAL to one of them.",
+                LINUX_TERMINALS.join(", ")
+            ))?;
+            linux_terminal_command(name, &path, &directory)
         },
         _ => {
             return Err(format!("Unsupported platform: {}", platform));
         }
     };
-    
-    match result {
+
+    match command.spawn() {
         Ok(_) => Ok(()),
         Err(e) => Err(format!("Failed to open terminal: {}", e))
     }
-}
\ No newline at end of file
+}
+
+/// Canonicalise `directory` and require it to be inside a registered workspace folder.
+/// Symlinks and `..` are resolved first, so neither can be used to step outside.
+pub fn resolve_sandboxed_dir(queries: &Queries, directory: &str) -> Result<PathBuf> {
+    let roots = queries.list_workspace_folders()?;
+    resolve_within(directory, &roots)
+}
+
+/// Directory for a terminal opened without one: the project's folder once it exists,
+/// otherwise the first workspace folder on disk
+pub fn default_sandboxed_dir(queries: &Queries, project_id: Option<&str>) -> Result<PathBuf> {
+    let project_folder = project_id
+        .and_then(|project_id| resolve_project_folder(queries, project_id).ok())
+        .filter(|folder| folder.is_dir());
+    if let Some(folder) = project_folder {
+        return resolve_sandboxed_dir(queries, &folder.to_string_lossy());
+    }
+
+    let roots = queries.list_workspace_folders()?;
+    let root = roots.iter()
+        .find(|root| Path::new(root).is_dir())
+        .ok_or_else(|| anyhow!("No workspace folder is configured; set one before opening a terminal"))?;
+    resolve_within(root, &roots)
+}
+
+/// `directory`, canonicalized, if it is inside one of `roots`
+pub fn resolve_within(directory: &str, roots: &[String]) -> Result<PathBuf> {
+    if directory.trim().is_empty() {
+        return Err(anyhow!("No directory given"));
+    }
+
+    let path = Path::new(directory).canonicalize()
+        .map_err(|e| anyhow!("Cannot open '{}': {}", directory, e))?;
+    if !path.is_dir() {
+        return Err(anyhow!("'{}' is not a directory", directory));
+    }
+
+    let roots: Vec<PathBuf> = roots.iter()
+        .filter_map(|root| Path::new(root).canonicalize().ok())
+        .collect();
+    if roots.is_empty() {
+        return Err(anyhow!("No workspace folder is configured; set one before opening a terminal"));
+    }
+    if !roots.iter().any(|root| path.starts_with(root)) {
+        return Err(anyhow!("'{}' is outside the workspace folders", directory));
+    }
+
+    Ok(strip_verbatim_prefix(path))
+}
+
+/// `canonicalize` returns `\\?\C:\...` on Windows, which cmd.exe refuses as a working directory
+fn strip_verbatim_prefix(path: PathBuf) -> PathBuf {
+    let text = path.to_string_lossy();
+    match text.strip_prefix(r"\\?\") {
+        Some(rest) if !rest.starts_with("UNC\\") => PathBuf::from(rest),
+        _ => path,
+    }
+}
+
+/// `$TERMINAL` if it names an allowed emulator, otherwise the first allowed one on `PATH`
+fn find_linux_terminal() -> Option<(&'static str, PathBuf)> {
+    if let Ok(preferred) = env::var("TERMINAL") {
+        let preferred_path = Path::new(&preferred);
+        let file_name = preferred_path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        if let Some(name) = LINUX_TERMINALS.iter().find(|name| **name == file_name) {
+            let path = if preferred_path.is_absolute() {
+                Some(preferred_path.to_path_buf()).filter(|p| p.is_file())
+            } else {
+                find_in_path(name)
+            };
+            if let Some(path) = path {
+                return Some((name, path));
+            }
+        }
+    }
+
+    LINUX_TERMINALS.iter().find_map(|name| find_in_path(name).map(|path| (*name, path)))
+}
+
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|path| path.is_file())
+}
+
+/// The directory is passed as its own argument and as the working directory, never through a shell
+fn linux_terminal_command(name: &str, path: &Path, directory: &Path) -> Command {
+    let mut command = Command::new(path);
+    command.current_dir(directory);
+    match name {
+        "gnome-terminal" => { command.arg("--working-directory").arg(directory); }
+        "konsole" => { command.arg("--workdir").arg(directory); }
+        "kitty" => { command.arg("--directory").arg(directory); }
+        "alacritty" => { command.arg("--working-directory").arg(directory); }
+        // xterm starts its shell in the current directory
+        _ => {}
+    }
+    command
+}
+
+#[cfg(target_os = "windows")]
+fn windows_terminal_command(directory: &Path) -> Command {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NEW_CONSOLE: u32 = 0x0000_0010;
+
+    let mut command = Command::new("cmd.exe");
+    command.current_dir(directory).creation_flags(CREATE_NEW_CONSOLE);
+    command
+}
+
+#[cfg(not(target_os = "windows"))]
+fn windows_terminal_command(directory: &Path) -> Command {
+    let mut command = Command::new("cmd.exe");
+    command.current_dir(directory);
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directories_must_resolve_inside_a_workspace_folder() {
+        let base = env::temp_dir().join(format!("prob-sandbox-{}", uuid::Uuid::new_v4()));
+        let workspace = base.join("workspace");
+        let project = workspace.join("my-app");
+        let outside = base.join("outside");
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        let roots = vec![workspace.to_string_lossy().to_string()];
+
+        let resolved = resolve_within(&project.to_string_lossy(), &roots).unwrap();
+        assert_eq!(resolved, project.canonicalize().unwrap());
+
+        let escape = project.join("..").join("..").join("outside");
+        assert!(resolve_within(&escape.to_string_lossy(), &roots).is_err());
+        assert!(resolve_within(&outside.to_string_lossy(), &roots).is_err());
+        assert!(resolve_within(&project.to_string_lossy(), &[]).is_err());
+        assert!(resolve_within("", &roots).is_err());
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn test_linux_terminal_gets_directory_as_an_argument() {
+        let directory = Path::new("/tmp/it's \"quoted\" && rm -rf ~");
+        let command = linux_terminal_command("kitty", Path::new("/usr/bin/kitty"), directory);
+
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(args, vec![std::ffi::OsStr::new("--directory"), directory.as_os_str()]);
+        assert_eq!(command.get_current_dir(), Some(directory));
+    }
+}
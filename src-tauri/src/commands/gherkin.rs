@@ -0,0 +1,696 @@
+use crate::db::{models::*, queries::Queries, DbPool};
+use crate::commands::workspace::{resolve_project_folder, slugify};
+use anyhow::Result;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use tauri::State;
+
+const FEATURES_DIR: &str = "features";
+const STORY_TAG_PREFIX: &str = "@story:";
+
+#[derive(Debug, Serialize)]
+pub struct FeatureExportResponse {
+    pub features_dir: String,
+    pub files_written: Vec<String>,
+    /// Files of exported stories left under an old name after a rename or reorder
+    pub files_removed: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeatureImportResponse {
+    pub updated_stories: Vec<String>,
+    pub unchanged_stories: Vec<String>,
+    pub unmatched_files: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Scenario {
+    pub name: String,
+    pub given: Vec<String>,
+    pub when: Vec<String>,
+    pub then: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedFeature {
+    pub story_id: Option<String>,
+    pub title: String,
+    pub as_a: Option<String>,
+    pub i_want: Option<String>,
+    pub so_that: Option<String>,
+    pub scenarios: Vec<Scenario>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum StepKind {
+    Given,
+    When,
+    Then,
+}
+
+// Commands
+
+/// Write one `.feature` file per user story into the project's workspace folder
+#[tauri::command]
+pub async fn generate_feature_files(
+    db: State<'_, DbPool>,
+    project_id: String,
+) -> Result<FeatureExportResponse, String> {
+    let queries = Queries::new(db.inner().clone());
+
+    let features_dir = resolve_project_folder(&queries, &project_id)
+        .map_err(|e| e.to_string())?
+        .join(FEATURES_DIR);
+
+    let stories = queries
+        .get_user_stories(&project_id)
+        .map_err(|e| e.to_string())?;
+
+    fs::create_dir_all(&features_dir)
+        .map_err(|e| format!("Failed to create features directory: {}", e))?;
+
+    let mut files_written = Vec::new();
+    for story in &stories {
+        let path = features_dir.join(feature_file_name(story));
+        fs::write(&path, render_feature(story))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        files_written.push(path.to_string_lossy().to_string());
+    }
+
+    let files_removed = remove_stale_feature_files(&features_dir, &stories)
+        .map_err(|e| e.to_string())?;
+
+    Ok(FeatureExportResponse {
+        features_dir: features_dir.to_string_lossy().to_string(),
+        files_written,
+        files_removed,
+    })
+}
+
+/// Read edited `.feature` files back and update the matching user stories
+#[tauri::command]
+pub async fn import_feature_files(
+    db: State<'_, DbPool>,
+    project_id: String,
+) -> Result<FeatureImportResponse, String> {
+    let queries = Queries::new(db.inner().clone());
+
+    let features_dir = resolve_project_folder(&queries, &project_id)
+        .map_err(|e| e.to_string())?
+        .join(FEATURES_DIR);
+
+    let stories = queries
+        .get_user_stories(&project_id)
+        .map_err(|e| e.to_string())?;
+
+    let mut response = FeatureImportResponse {
+        updated_stories: Vec::new(),
+        unchanged_stories: Vec::new(),
+        unmatched_files: Vec::new(),
+        warnings: Vec::new(),
+    };
+
+    for path in list_feature_files(&features_dir).map_err(|e| e.to_string())? {
+        let file_name = path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+        let feature = match parse_feature(&content) {
+            Ok(feature) => feature,
+            Err(e) => {
+                response.warnings.push(format!("{}: {}", file_name, e));
+                response.unmatched_files.push(file_name);
+                continue;
+            }
+        };
+
+        response.warnings.extend(
+            feature.warnings.iter().map(|warning| format!("{}: {}", file_name, warning))
+        );
+
+        let Some(story) = match_story(&feature, &stories) else {
+            response.unmatched_files.push(file_name);
+            continue;
+        };
+
+        let mut updated = story.clone();
+        let (changed, skipped) = apply_feature(&mut updated, &feature);
+        response.warnings.extend(
+            skipped.iter().map(|name| format!("{}: scenario '{}' has no Then step and was skipped", file_name, name))
+        );
+
+        if changed {
+            queries
+                .update_user_story(&updated)
+                .map_err(|e| e.to_string())?;
+            response.updated_stories.push(updated.id);
+        } else {
+            response.unchanged_stories.push(updated.id);
+        }
+    }
+
+    Ok(response)
+}
+
+// Rendering
+
+pub fn feature_file_name(story: &UserStory) -> String {
+    format!("{:02}-{}.feature", story.position + 1, slugify(&story.title))
+}
+
+/// Render a user story and its acceptance criteria as a Gherkin feature
+pub fn render_feature(story: &UserStory) -> String {
+    let mut tags = vec![format!("{}{}", STORY_TAG_PREFIX, story.id)];
+    if let Some(priority) = &story.priority {
+        tags.push(format!("@priority-{}", slugify(priority)));
+    }
+
+    let mut out = String::new();
+    out.push_str(&tags.join(" "));
+    out.push('\n');
+    out.push_str(&format!("Feature: {}\n", single_line(&story.title)));
+    out.push_str(&format!("  As a {}\n", single_line(&story.as_a)));
+    out.push_str(&format!("  I want {}\n", single_line(&story.i_want)));
+    out.push_str(&format!("  So that {}\n", single_line(&story.so_that)));
+
+    if story.acceptance_criteria.is_empty() {
+        out.push_str("\n  # No acceptance criteria yet\n");
+    }
+
+    for criterion in &story.acceptance_criteria {
+        let scenario = criterion_to_scenario(criterion, story);
+        out.push('\n');
+        out.push_str(&format!("  Scenario: {}\n", scenario.name));
+        write_steps(&mut out, "Given", &scenario.given);
+        write_steps(&mut out, "When", &scenario.when);
+        write_steps(&mut out, "Then", &scenario.then);
+    }
+
+    out
+}
+
+fn write_steps(out: &mut String, keyword: &str, steps: &[String]) {
+    for (i, step) in steps.iter().enumerate() {
+        let keyword = if i == 0 { keyword } else { "And" };
+        out.push_str(&format!("    {} {}\n", keyword, step));
+    }
+}
+
+/// Turn a free-text criterion into a scenario. Criteria already written as
+/// "Given ..., when ..., then ..." keep their steps; anything else becomes the
+/// Then step of a scenario built from the story's role and goal.
+fn criterion_to_scenario(criterion: &str, story: &UserStory) -> Scenario {
+    let text = single_line(criterion);
+
+    if let Some((given, when, then)) = split_structured_criterion(&text) {
+        return Scenario {
+            name: text.clone(),
+            given: given.into_iter().collect(),
+            when: when.into_iter().collect(),
+            then: vec![then],
+        };
+    }
+
+    Scenario {
+        name: text.clone(),
+        given: vec![default_given(story)],
+        when: vec![default_when(story)],
+        then: vec![text],
+    }
+}
+
+/// Delete feature files tagged with one of `stories` that no longer have that story's file
+/// name. Files without a known story tag are the user's own and are left alone.
+fn remove_stale_feature_files(dir: &Path, stories: &[UserStory]) -> Result<Vec<String>> {
+    let mut removed = Vec::new();
+    for path in list_feature_files(dir)? {
+        let Some(story_id) = fs::read_to_string(&path).ok().and_then(|content| story_tag(&content)) else {
+            continue;
+        };
+        let Some(story) = stories.iter().find(|s| s.id == story_id) else {
+            continue;
+        };
+        let stale = path.file_name().is_some_and(|name| name.to_string_lossy() != feature_file_name(story));
+        if stale {
+            fs::remove_file(&path)?;
+            removed.push(path.to_string_lossy().to_string());
+        }
+    }
+    Ok(removed)
+}
+
+/// The `@story:` tag above the `Feature:` line
+fn story_tag(content: &str) -> Option<String> {
+    content.lines()
+        .map(str::trim)
+        .take_while(|line| !line.starts_with("Feature:"))
+        .filter(|line| line.starts_with('@'))
+        .flat_map(str::split_whitespace)
+        .find_map(|tag| tag.strip_prefix(STORY_TAG_PREFIX).map(str::to_string))
+}
+
+fn split_structured_criterion(text: &str) -> Option<(Option<String>, Option<String>, String)> {
+    let lower = text.to_ascii_lowercase();
+    let then_at = find_clause(&lower, "then")?;
+    let then = text[then_at.1..].trim().to_string();
+    let head = &text[..then_at.0];
+    let head_lower = &lower[..then_at.0];
+
+    if let Some(rest) = strip_keyword(head, head_lower, "given") {
+        let rest_lower = rest.to_ascii_lowercase();
+        return match find_clause(&rest_lower, "when") {
+            Some((start, end)) => Some((
+                Some(rest[..start].trim().to_string()),
+                Some(rest[end..].trim().to_string()),
+                then,
+            )),
+            None => Some((Some(rest.trim().to_string()), None, then)),
+        };
+    }
+
+    strip_keyword(head, head_lower, "when")
+        .map(|rest| (None, Some(rest.trim().to_string()), then))
+}
+
+/// Find `", keyword "` (or `" keyword "`) and return the byte range it covers
+fn find_clause(lower: &str, keyword: &str) -> Option<(usize, usize)> {
+    let with_comma = format!(", {} ", keyword);
+    if let Some(start) = lower.find(&with_comma) {
+        return Some((start, start + with_comma.len()));
+    }
+    let bare = format!(" {} ", keyword);
+    lower.find(&bare).map(|start| (start, start + bare.len()))
+}
+
+fn strip_keyword<'a>(text: &'a str, lower: &str, keyword: &str) -> Option<&'a str> {
+    let prefix = format!("{} ", keyword);
+    if lower.starts_with(&prefix) {
+        Some(&text[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+fn default_given(story: &UserStory) -> String {
+    let role = single_line(&story.as_a);
+    format!("I am {} {}", indefinite_article(&role), role)
+}
+
+fn default_when(story: &UserStory) -> String {
+    let goal = single_line(&story.i_want);
+    match goal.strip_prefix("to ") {
+        Some(action) => format!("I {}", action),
+        None => format!("I want {}", goal),
+    }
+}
+
+fn indefinite_article(word: &str) -> &'static str {
+    match word.chars().next().map(|c| c.to_ascii_lowercase()) {
+        Some('a' | 'e' | 'i' | 'o' | 'u') => "an",
+        _ => "a",
+    }
+}
+
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Parsing
+
+/// Parse the subset of Gherkin that `render_feature` produces, plus the usual
+/// hand edits: And/But steps, Background, extra scenarios and comments.
+pub fn parse_feature(content: &str) -> Result<ParsedFeature> {
+    let mut feature = ParsedFeature::default();
+    let mut seen_feature = false;
+    let mut background: Option<Scenario> = None;
+    let mut in_background = false;
+    let mut in_examples = false;
+    let mut in_docstring = false;
+    let mut last_kind: Option<StepKind> = None;
+
+    for (index, raw_line) in content.lines().enumerate() {
+        let line_number = index + 1;
+        let line = raw_line.trim();
+
+        if line.starts_with("\"\"\"") || line.starts_with("```") {
+            if !in_docstring {
+                feature.warnings.push(format!("line {}: doc strings are not mapped to acceptance criteria", line_number));
+            }
+            in_docstring = !in_docstring;
+            continue;
+        }
+        if in_docstring || line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with('@') {
+            if !seen_feature {
+                for tag in line.split_whitespace() {
+                    if let Some(id) = tag.strip_prefix(STORY_TAG_PREFIX) {
+                        feature.story_id = Some(id.to_string());
+                    }
+                }
+            }
+            continue;
+        }
+
+        if let Some(title) = line.strip_prefix("Feature:") {
+            feature.title = title.trim().to_string();
+            seen_feature = true;
+            continue;
+        }
+
+        if line.starts_with("Background:") {
+            background = Some(Scenario::default());
+            in_background = true;
+            in_examples = false;
+            last_kind = None;
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix("Scenario Outline:")
+            .or_else(|| line.strip_prefix("Scenario Template:"))
+        {
+            feature.warnings.push(format!("line {}: scenario outline examples are not expanded", line_number));
+            feature.scenarios.push(Scenario { name: name.trim().to_string(), ..Default::default() });
+            in_background = false;
+            in_examples = false;
+            last_kind = None;
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix("Scenario:").or_else(|| line.strip_prefix("Example:")) {
+            feature.scenarios.push(Scenario { name: name.trim().to_string(), ..Default::default() });
+            in_background = false;
+            in_examples = false;
+            last_kind = None;
+            continue;
+        }
+
+        if line.starts_with("Examples:") || line.starts_with("Scenarios:") {
+            in_examples = true;
+            continue;
+        }
+
+        if line.starts_with("Rule:") {
+            continue;
+        }
+
+        if line.starts_with('|') {
+            if !in_examples {
+                feature.warnings.push(format!("line {}: data tables are not mapped to acceptance criteria", line_number));
+            }
+            continue;
+        }
+
+        if let Some((keyword, text)) = split_step(line) {
+            let kind = match keyword {
+                "Given" => StepKind::Given,
+                "When" => StepKind::When,
+                "Then" => StepKind::Then,
+                _ => match last_kind {
+                    Some(kind) => kind,
+                    None => {
+                        feature.warnings.push(format!("line {}: '{}' step has nothing to continue", line_number, keyword));
+                        continue;
+                    }
+                },
+            };
+            last_kind = Some(kind);
+
+            let target = if in_background {
+                background.as_mut()
+            } else {
+                feature.scenarios.last_mut()
+            };
+
+            match target {
+                Some(scenario) => match kind {
+                    StepKind::Given => scenario.given.push(text),
+                    StepKind::When => scenario.when.push(text),
+                    StepKind::Then => scenario.then.push(text),
+                },
+                None => feature.warnings.push(format!("line {}: step outside of a scenario", line_number)),
+            }
+            continue;
+        }
+
+        // Free text between "Feature:" and the first scenario is the story narrative
+        if seen_feature && feature.scenarios.is_empty() && !in_background {
+            if let Some(role) = line.strip_prefix("As an ").or_else(|| line.strip_prefix("As a ")) {
+                feature.as_a = Some(role.trim().trim_end_matches(',').to_string());
+            } else if let Some(goal) = line.strip_prefix("I want ") {
+                feature.i_want = Some(goal.trim().trim_end_matches(',').to_string());
+            } else if let Some(benefit) = line.strip_prefix("So that ") {
+                feature.so_that = Some(benefit.trim().to_string());
+            }
+        }
+    }
+
+    if !seen_feature {
+        return Err(anyhow::anyhow!("Feature file has no 'Feature:' line"));
+    }
+    // Importing this would wipe every criterion of the story
+    if !feature.scenarios.is_empty() && feature.scenarios.iter().all(|s| s.then.is_empty()) {
+        return Err(anyhow::anyhow!("No scenario has a 'Then' step"));
+    }
+
+    if let Some(background) = background {
+        for scenario in &mut feature.scenarios {
+            let mut given = background.given.clone();
+            given.append(&mut scenario.given);
+            scenario.given = given;
+
+            let mut when = background.when.clone();
+            when.append(&mut scenario.when);
+            scenario.when = when;
+        }
+    }
+
+    Ok(feature)
+}
+
+fn split_step(line: &str) -> Option<(&str, String)> {
+    for keyword in ["Given", "When", "Then", "And", "But", "*"] {
+        if let Some(rest) = line.strip_prefix(keyword) {
+            if rest.starts_with(' ') {
+                return Some((keyword, rest.trim().to_string()));
+            }
+        }
+    }
+    None
+}
+
+// Mapping back onto stories
+
+fn match_story<'a>(feature: &ParsedFeature, stories: &'a [UserStory]) -> Option<&'a UserStory> {
+    if let Some(id) = &feature.story_id {
+        if let Some(story) = stories.iter().find(|s| &s.id == id) {
+            return Some(story);
+        }
+    }
+
+    stories.iter().find(|s| s.title.trim().eq_ignore_ascii_case(feature.title.trim()))
+}
+
+/// Apply a parsed feature to its story. Returns whether anything changed, and
+/// the names of scenarios skipped for lacking an outcome.
+pub fn apply_feature(story: &mut UserStory, feature: &ParsedFeature) -> (bool, Vec<String>) {
+    let original = story.clone();
+    let mut skipped = Vec::new();
+
+    let criteria: Vec<String> = feature.scenarios.iter()
+        .filter_map(|scenario| {
+            let criterion = scenario_to_criterion(scenario, &original);
+            if criterion.is_none() {
+                skipped.push(scenario.name.clone());
+            }
+            criterion
+        })
+        .collect();
+
+    if !feature.title.is_empty() {
+        story.title = feature.title.clone();
+    }
+    if let Some(as_a) = &feature.as_a {
+        story.as_a = as_a.clone();
+    }
+    if let Some(i_want) = &feature.i_want {
+        story.i_want = i_want.clone();
+    }
+    if let Some(so_that) = &feature.so_that {
+        story.so_that = so_that.clone();
+    }
+    story.acceptance_criteria = criteria;
+
+    let changed = story.title != original.title
+        || story.as_a != original.as_a
+        || story.i_want != original.i_want
+        || story.so_that != original.so_that
+        || story.acceptance_criteria != original.acceptance_criteria;
+
+    if changed {
+        story.is_edited = true;
+        story.edited_content = Some(format!(
+            "As a {}, I want {}, so that {}",
+            story.as_a, story.i_want, story.so_that
+        ));
+    }
+
+    (changed, skipped)
+}
+
+fn scenario_to_criterion(scenario: &Scenario, story: &UserStory) -> Option<String> {
+    if scenario.then.is_empty() {
+        return None;
+    }
+    let then = scenario.then.join(" and ");
+
+    // A structured criterion keeps its own wording, commas or not
+    if let Some((given, when, structured_then)) = split_structured_criterion(&scenario.name) {
+        if scenario.given == given.into_iter().collect::<Vec<_>>()
+            && scenario.when == when.into_iter().collect::<Vec<_>>()
+            && scenario.then == [structured_then]
+        {
+            return Some(scenario.name.clone());
+        }
+    }
+
+    if scenario.given == [default_given(story)] && scenario.when == [default_when(story)] {
+        return Some(then);
+    }
+
+    let mut parts = Vec::new();
+    if !scenario.given.is_empty() {
+        parts.push(format!("Given {}", scenario.given.join(" and ")));
+    }
+    if !scenario.when.is_empty() {
+        let keyword = if parts.is_empty() { "When" } else { "when" };
+        parts.push(format!("{} {}", keyword, scenario.when.join(" and ")));
+    }
+    parts.push(format!("then {}", then));
+    Some(parts.join(", "))
+}
+
+fn list_feature_files(dir: &Path) -> Result<Vec<std::path::PathBuf>> {
+    if !dir.exists() {
+        return Err(anyhow::anyhow!("No features directory at {}", dir.display()));
+    }
+
+    let mut files: Vec<_> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().map(|ext| ext == "feature").unwrap_or(false))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn story(criteria: &[&str]) -> UserStory {
+        UserStory {
+            id: "story-1".to_string(),
+            project_id: "project-1".to_string(),
+            title: "Inventory Level Monitoring".to_string(),
+            as_a: "small business owner".to_string(),
+            i_want: "to receive alerts when stock is low".to_string(),
+            so_that: "I can reorder in time".to_string(),
+            acceptance_criteria: criteria.iter().map(|c| c.to_string()).collect(),
+            priority: Some("High".to_string()),
+            complexity_points: Some(5),
+            position: 0,
+            is_edited: false,
+            original_content: None,
+            edited_content: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_round_trip_leaves_story_unchanged() {
+        let mut original = story(&[
+            "Alert triggers when stock < 10 units",
+            "Given a product with no supplier, when stock runs out, then the owner is asked to add one",
+        ]);
+
+        let rendered = render_feature(&original);
+        assert!(rendered.contains("@story:story-1 @priority-high"));
+        assert!(rendered.contains("Given I am a small business owner"));
+        assert!(rendered.contains("When I receive alerts when stock is low"));
+        assert!(rendered.contains("Then Alert triggers when stock < 10 units"));
+        assert!(rendered.contains("When stock runs out"));
+
+        let feature = parse_feature(&rendered).unwrap();
+        assert_eq!(feature.story_id.as_deref(), Some("story-1"));
+
+        let (changed, skipped) = apply_feature(&mut original, &feature);
+        assert!(!changed);
+        assert!(skipped.is_empty());
+    }
+
+    #[test]
+    fn test_edited_feature_updates_criteria() {
+        let mut original = story(&["Alert triggers when stock < 10 units"]);
+        let edited = "\
+@story:story-1
+Feature: Inventory Level Monitoring
+  As a small business owner
+  I want to receive alerts when stock is low
+  So that I can reorder in time
+
+  Background:
+    Given I am signed in
+
+  Scenario: Low stock alert
+    When stock drops below 5 units
+    Then an email is sent
+    And an SMS is sent
+
+  Scenario: Missing outcome
+    When nothing happens
+";
+
+        let feature = parse_feature(edited).unwrap();
+        let (changed, skipped) = apply_feature(&mut original, &feature);
+
+        assert!(changed);
+        assert!(original.is_edited);
+        assert_eq!(skipped, vec!["Missing outcome".to_string()]);
+        assert_eq!(
+            original.acceptance_criteria,
+            vec!["Given I am signed in, when stock drops below 5 units, then an email is sent and an SMS is sent".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_missing_feature_line_is_an_error() {
+        assert!(parse_feature("Scenario: orphan\n  Then nothing").is_err());
+    }
+
+    #[test]
+    fn test_criteria_without_commas_round_trip() {
+        let mut original = story(&[
+            "Given a product with no supplier when stock runs out then the owner is asked to add one",
+            "When the page loads then totals are shown",
+        ]);
+
+        let feature = parse_feature(&render_feature(&original)).unwrap();
+        let (changed, _) = apply_feature(&mut original, &feature);
+        assert!(!changed);
+    }
+
+    #[test]
+    fn test_feature_without_any_outcome_is_an_error() {
+        let content = "Feature: Inventory\n  Scenario: One\n    When stock drops\n  Scenario: Two\n    Given a product\n";
+        assert!(parse_feature(content).is_err());
+    }
+}
@@ -1,10 +1,33 @@
 pub mod problem;
 pub mod personas;
 pub mod canvas;
+pub mod canvas_diff;
+pub mod canvas_export;
+pub mod canvas_layout;
+pub mod canvas_reconcile;
 pub mod data_sync;
 pub mod workspace;
 pub mod filesystem;
 pub mod terminal;
+pub mod terminal_profiles;
+pub mod terminal_recording;
+pub mod gherkin;
+pub mod architecture;
+pub mod schema_export;
+pub mod design_tokens;
+pub mod components;
+pub mod repository;
+pub mod workflow;
+pub mod recent_flows;
+pub mod documents;
+pub mod document_render;
+pub mod document_drift;
+pub mod document_queue;
+pub mod report_export;
+pub mod report_pdf;
+pub mod report_docx;
+pub mod export_history;
+pub mod ui_state;
 
 use crate::db::{Queries, DbPool, Workspace};
 use serde::{Deserialize, Serialize};
@@ -17,7 +40,16 @@ pub use problem::{validate_problem, save_problem_validation, get_problem_history
 pub use personas::{generate_personas, regenerate_personas, lock_persona, select_persona, get_personas, create_test_persona_data};
 
 // Re-export canvas commands
-pub use canvas::{save_canvas_state, load_canvas_state, export_canvas_image, calculate_layout};
+pub use canvas::{save_canvas_state, load_canvas_state, export_canvas_image, calculate_layout, get_canvas_history, restore_canvas_version, create_canvas_checkpoint, branch_canvas, apply_canvas_retention};
+
+// Re-export canvas diff commands
+pub use canvas_diff::{diff_canvas_versions, apply_canvas_patch};
+
+// Re-export canvas reconciliation commands
+pub use canvas_reconcile::{reconcile_canvas, regenerate_canvas_from_domain, apply_canvas_deletions};
+
+// Re-export UI state commands
+pub use ui_state::{save_ui_state, load_ui_state, flush_ui_state, save_ui_selection, load_ui_selection, start_ui_animation, get_active_animations, finish_ui_animation, save_canvas_transition, get_canvas_transition};
 
 // Re-export data sync commands
 pub use data_sync::{populate_sqlite_test_data, verify_test_data_consistency, clear_sqlite_test_data};
@@ -29,7 +61,48 @@ pub use workspace::{create_new_project, delete_project_with_data, rename_project
 pub use filesystem::{get_platform, open_terminal};
 
 // Re-export terminal commands
-pub use terminal::{start_terminal_session, write_to_terminal, close_terminal_session, resize_terminal};
+pub use terminal::{start_terminal_session, write_to_terminal, close_terminal_session, resize_terminal, list_terminal_sessions, attach_terminal_session, start_terminal_recording, stop_terminal_recording, run_terminal_command};
+pub use terminal_profiles::{get_terminal_profiles, save_terminal_profile, delete_terminal_profile, get_terminal_command_history};
+pub use terminal_recording::{list_terminal_recordings, replay_terminal_recording};
+
+// Re-export gherkin commands
+pub use gherkin::{generate_feature_files, import_feature_files};
+
+// Re-export architecture commands
+pub use architecture::{generate_system_design, get_architecture_version, get_system_design};
+
+// Re-export schema export commands
+pub use schema_export::{validate_database_schema, export_database_schema};
+
+// Re-export design token commands
+pub use design_tokens::export_design_tokens;
+
+// Re-export atomic component commands
+pub use components::{validate_component_tree, scaffold_components};
+
+// Re-export repository setup commands
+pub use repository::setup_repository;
+
+// Re-export workflow commands
+pub use workflow::{advance_project_step, get_workflow_progress};
+
+// Re-export recent flow commands
+pub use recent_flows::{get_recent_flows, get_project_progress, mark_flow_opened, update_flow_status};
+
+// Re-export document commands
+pub use documents::{materialize_project_documents, render_project_documents, get_project_documents, read_project_document, watch_project_documents, unwatch_project_documents};
+
+// Re-export document drift commands
+pub use document_drift::{detect_document_drift, get_document_drift_reports, resolve_document_drift};
+
+// Re-export document queue commands
+pub use document_queue::{enqueue_project_documents, cancel_document_generation, get_document_generation_queue};
+
+// Re-export report export commands
+pub use report_export::export_project_report;
+
+// Re-export export history commands
+pub use export_history::{list_exports, re_export, save_export_file, download_export};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AppState {
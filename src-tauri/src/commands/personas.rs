@@ -1,4 +1,5 @@
 use crate::db::{models::*, queries::Queries, DbPool};
+use crate::commands::workflow::sync_project_step_or_log;
 use serde::{Deserialize, Serialize};
 use tauri::State;
 use uuid::Uuid;
@@ -126,9 +127,7 @@ pub async fn select_persona(
         .map_err(|e| e.to_string())?;
     
     // Update project step to next workflow step
-    queries
-        .update_project_step(&project_id, "SolutionDiscovery")
-        .map_err(|e| e.to_string())?;
+    sync_project_step_or_log(&queries, &project_id, &WorkflowStep::SolutionDiscovery, "persona_selection");
     
     // Get the updated personas and return the active one
     let personas = queries
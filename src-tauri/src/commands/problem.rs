@@ -1,4 +1,5 @@
 use crate::db::{models::*, queries::Queries, DbPool};
+use crate::commands::workflow::sync_project_step_or_log;
 use serde::{Deserialize, Serialize};
 use tauri::State;
 use uuid::Uuid;
@@ -98,9 +99,7 @@ pub async fn save_problem_validation(
     
     // Update project status to next step if valid
     if problem_to_save.is_valid {
-        queries
-            .update_project_step(&problem_to_save.project_id, "SolutionDiscovery")
-            .map_err(|e| e.to_string())?;
+        sync_project_step_or_log(&queries, &problem_to_save.project_id, &WorkflowStep::SolutionDiscovery, "problem_validation");
     }
     
     Ok(problem_to_save)
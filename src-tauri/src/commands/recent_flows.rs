@@ -0,0 +1,356 @@
+use crate::db::{models::*, queries::Queries, DbPool};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::State;
+use uuid::Uuid;
+
+const FLOW_STATUSES: [&str; 4] = ["draft", "in-progress", "completed", "archived"];
+const LAST_OPENED_SETTING: &str = "last_opened_at";
+/// Safety net for activity the invalidation hooks cannot see
+const CACHE_TTL_SECONDS: i64 = 3600;
+
+/// Shape expected by `useRecentFlows` on the frontend
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentFlow {
+    pub id: String,
+    pub workspace_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub status: String,
+    pub current_step: String,
+    pub progress: FlowProgress,
+    pub completion_percentage: i32,
+    pub tags: Vec<String>,
+    pub modified_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub canvas_preview: Option<String>,
+    pub last_opened_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlowProgress {
+    pub problem: bool,
+    pub personas: bool,
+    pub pain_points: bool,
+    pub solutions: bool,
+    pub specs: bool,
+}
+
+#[tauri::command]
+pub async fn get_recent_flows(
+    limit: Option<usize>,
+    workspace_id: Option<String>,
+    include_archived: Option<bool>,
+    pool: State<'_, DbPool>,
+) -> Result<Vec<RecentFlow>, String> {
+    let queries = Queries::new(pool.inner().clone());
+    let include_archived = include_archived.unwrap_or(false);
+
+    let projects = queries
+        .list_projects(workspace_id.as_deref())
+        .map_err(|e| e.to_string())?;
+
+    let mut owners: HashMap<String, Option<String>> = HashMap::new();
+    let mut flows = Vec::new();
+    for project in projects {
+        let owner = match owners.get(&project.workspace_id) {
+            Some(owner) => owner.clone(),
+            None => {
+                let owner = queries.get_workspace(&project.workspace_id)
+                    .map_err(|e| e.to_string())?
+                    .map(|workspace| workspace.user_id);
+                owners.insert(project.workspace_id.clone(), owner.clone());
+                owner
+            }
+        };
+
+        let flow = build_recent_flow(&queries, project, owner).map_err(|e| e.to_string())?;
+        if include_archived || flow.status != "archived" {
+            flows.push(flow);
+        }
+    }
+
+    flows.sort_by_key(|flow| std::cmp::Reverse(flow.last_opened_at.unwrap_or(flow.modified_at).max(flow.modified_at)));
+    flows.truncate(limit.unwrap_or(10));
+
+    Ok(flows)
+}
+
+/// Progress summary for a single project
+#[tauri::command]
+pub async fn get_project_progress(
+    project_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<RecentFlow, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    let project = queries
+        .get_project(&project_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Project not found".to_string())?;
+    let owner = queries
+        .get_workspace(&project.workspace_id)
+        .map_err(|e| e.to_string())?
+        .map(|workspace| workspace.user_id);
+
+    build_recent_flow(&queries, project, owner).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn mark_flow_opened(
+    flow_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<(), String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    queries
+        .get_project(&flow_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Project not found".to_string())?;
+
+    queries
+        .set_project_setting(&flow_id, LAST_OPENED_SETTING, &Utc::now().to_rfc3339())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_flow_status(
+    flow_id: String,
+    status: String,
+    pool: State<'_, DbPool>,
+) -> Result<(), String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    if !FLOW_STATUSES.contains(&status.as_str()) {
+        return Err(format!("Invalid flow status '{}'; expected one of {}", status, FLOW_STATUSES.join(", ")));
+    }
+
+    queries
+        .get_project(&flow_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Project not found".to_string())?;
+
+    queries
+        .update_project_status(&flow_id, &status)
+        .map_err(|e| e.to_string())
+}
+
+fn build_recent_flow(queries: &Queries, project: Project, owner: Option<String>) -> Result<RecentFlow> {
+    let summary = match queries.get_recent_flow_cache(&project.id)? {
+        Some(cached) => cached,
+        None => {
+            let summary = summarize_project(queries, &project, owner)?;
+            queries.save_recent_flow_cache(&summary, CACHE_TTL_SECONDS)?;
+            summary
+        }
+    };
+
+    let progress = progress_from_segments(&summary.progress_segments);
+    let last_opened_at = queries
+        .get_project_setting(&project.id, LAST_OPENED_SETTING)?
+        .and_then(|value| DateTime::parse_from_rfc3339(&value).ok())
+        .map(|value| value.with_timezone(&Utc));
+
+    let status = if FLOW_STATUSES.contains(&project.status.as_str()) {
+        project.status.clone()
+    } else {
+        derived_status(summary.completion_percentage).to_string()
+    };
+
+    let tags = queries.get_solutions_with_mappings(&project.id)?
+        .into_iter()
+        .filter(|(solution, _)| solution.is_selected)
+        .filter_map(|(solution, _)| solution.solution_type)
+        .fold(Vec::new(), |mut tags, tag| {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+            tags
+        });
+
+    Ok(RecentFlow {
+        id: project.id,
+        workspace_id: project.workspace_id,
+        name: project.name,
+        description: Some(summary.problem_statement).filter(|statement| !statement.is_empty()),
+        status,
+        current_step: project.current_step,
+        progress,
+        completion_percentage: summary.completion_percentage,
+        tags,
+        modified_at: summary.last_activity,
+        created_at: project.created_at,
+        canvas_preview: None,
+        last_opened_at,
+    })
+}
+
+/// Compute the five progress segments from the project's actual data
+fn summarize_project(queries: &Queries, project: &Project, owner: Option<String>) -> Result<RecentFlowCache> {
+    let problem = queries.get_latest_core_problem(&project.id)?;
+    let personas = match &problem {
+        Some(problem) => queries.get_personas(&problem.id)?,
+        None => Vec::new(),
+    };
+
+    let mut has_pain_points = false;
+    for persona in &personas {
+        if !queries.get_pain_points(&persona.id)?.is_empty() {
+            has_pain_points = true;
+            break;
+        }
+    }
+
+    let segments = [
+        ("Problem", problem.as_ref().map(|p| p.is_valid).unwrap_or(false)),
+        ("Personas", personas.iter().any(|p| p.is_active)),
+        ("Pain Points", has_pain_points),
+        ("Solutions", queries.get_solutions_with_mappings(&project.id)?.iter().any(|(s, _)| s.is_selected)),
+        ("Specs", !queries.get_user_stories(&project.id)?.is_empty()),
+    ];
+    let complete = segments.iter().filter(|(_, complete)| *complete).count();
+
+    let problem_statement = problem
+        .map(|p| p.validated_problem.unwrap_or(p.original_input))
+        .unwrap_or_default();
+    let last_activity = queries.get_project_last_activity(&project.id)?
+        .ok_or_else(|| anyhow!("Project has no activity timestamp"))?;
+
+    Ok(RecentFlowCache {
+        id: Uuid::new_v4().to_string(),
+        user_id: owner,
+        project_id: project.id.clone(),
+        problem_statement,
+        last_activity,
+        progress_segments: serde_json::Value::Array(
+            segments.iter()
+                .map(|(name, complete)| serde_json::json!({ "name": name, "complete": complete }))
+                .collect(),
+        ),
+        completion_percentage: (complete * 100 / segments.len()) as i32,
+        cache_key: format!("project:{}", project.id),
+        expires_at: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    })
+}
+
+fn progress_from_segments(segments: &serde_json::Value) -> FlowProgress {
+    let complete = |name: &str| segments.as_array()
+        .and_then(|segments| segments.iter().find(|s| s["name"] == name))
+        .and_then(|segment| segment["complete"].as_bool())
+        .unwrap_or(false);
+
+    FlowProgress {
+        problem: complete("Problem"),
+        personas: complete("Personas"),
+        pain_points: complete("Pain Points"),
+        solutions: complete("Solutions"),
+        specs: complete("Specs"),
+    }
+}
+
+fn derived_status(completion_percentage: i32) -> &'static str {
+    match completion_percentage {
+        100.. => "completed",
+        1..=99 => "in-progress",
+        _ => "draft",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_db;
+
+    fn completion(queries: &Queries) -> i32 {
+        let project = queries.get_project("p1").unwrap().unwrap();
+        build_recent_flow(queries, project, Some("u1".to_string())).unwrap().completion_percentage
+    }
+
+    #[test]
+    fn test_completion_follows_the_data_and_writes_clear_the_cache() {
+        let (_pool, queries) = test_db("p1", None);
+        let now = Utc::now();
+        assert_eq!(completion(&queries), 0);
+        assert!(queries.get_recent_flow_cache("p1").unwrap().is_some());
+
+        queries.create_core_problem(&CoreProblem {
+            id: "cp1".to_string(),
+            project_id: "p1".to_string(),
+            original_input: "Patients miss appointments".to_string(),
+            validated_problem: None,
+            is_valid: true,
+            validation_feedback: None,
+            version: 1,
+            created_at: now,
+        }).unwrap();
+        assert_eq!(completion(&queries), 20);
+
+        queries.create_personas(&[Persona {
+            id: "pe1".to_string(),
+            core_problem_id: "cp1".to_string(),
+            name: "Sarah".to_string(),
+            industry: "Healthcare".to_string(),
+            role: "Clinic manager".to_string(),
+            pain_degree: 4,
+            position: 0,
+            is_locked: false,
+            is_active: true,
+            generation_batch: None,
+            created_at: now,
+        }]).unwrap();
+        queries.create_pain_points(&[PainPoint {
+            id: "pp1".to_string(),
+            persona_id: "pe1".to_string(),
+            description: "No-shows cost money".to_string(),
+            severity: None,
+            impact_area: None,
+            position: 0,
+            is_locked: false,
+            generation_batch: None,
+            created_at: now,
+        }]).unwrap();
+        assert_eq!(completion(&queries), 60);
+
+        queries.create_solutions_with_mappings(&[Solution {
+            id: "s1".to_string(),
+            project_id: "p1".to_string(),
+            persona_id: "pe1".to_string(),
+            title: "Reminders".to_string(),
+            description: "Text patients before visits".to_string(),
+            solution_type: None,
+            complexity: None,
+            position: 0,
+            is_locked: false,
+            is_selected: true,
+            generation_batch: None,
+            created_at: now,
+        }], &[]).unwrap();
+        queries.create_user_stories(&[UserStory {
+            id: "us1".to_string(),
+            project_id: "p1".to_string(),
+            title: "Get reminded".to_string(),
+            as_a: "patient".to_string(),
+            i_want: "to receive reminders".to_string(),
+            so_that: "I do not miss visits".to_string(),
+            acceptance_criteria: Vec::new(),
+            priority: None,
+            complexity_points: None,
+            position: 0,
+            is_edited: false,
+            original_content: None,
+            edited_content: None,
+            created_at: now,
+        }]).unwrap();
+        assert_eq!(completion(&queries), 100);
+
+        queries.delete_domain_entities("p1", &[], &[], &["s1".to_string()]).unwrap();
+        assert!(queries.get_recent_flow_cache("p1").unwrap().is_none());
+        assert_eq!(completion(&queries), 80);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::commands::report_export::{Report, ReportBlock};
+use anyhow::Result;
+use std::fmt::Write as _;
+use std::io::{Cursor, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Width of images and tables: 6", which fits Letter and A4 with 1" margins. In EMU (914400 per inch).
+const CONTENT_WIDTH_EMU: u64 = 6 * 914_400;
+/// Same width in twentieths of a point, for table grids
+const CONTENT_WIDTH_TWIPS: f32 = 6.0 * 1440.0;
+
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+  <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+  <Default Extension="xml" ContentType="application/xml"/>
+  <Default Extension="png" ContentType="image/png"/>
+  <Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>
+  <Override PartName="/word/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"/>
+</Types>"#;
+
+const PACKAGE_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>
+</Relationships>"#;
+
+const STYLES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:docDefaults>
+    <w:rPrDefault><w:rPr><w:rFonts w:ascii="Calibri" w:hAnsi="Calibri" w:cs="Calibri"/><w:sz w:val="21"/></w:rPr></w:rPrDefault>
+    <w:pPrDefault><w:pPr><w:spacing w:after="120" w:line="276" w:lineRule="auto"/></w:pPr></w:pPrDefault>
+  </w:docDefaults>
+  <w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/></w:style>
+  <w:style w:type="paragraph" w:styleId="Title"><w:name w:val="Title"/><w:basedOn w:val="Normal"/><w:pPr><w:spacing w:after="60"/></w:pPr><w:rPr><w:b/><w:sz w:val="44"/></w:rPr></w:style>
+  <w:style w:type="paragraph" w:styleId="Subtitle"><w:name w:val="Subtitle"/><w:basedOn w:val="Normal"/><w:pPr><w:spacing w:after="240"/></w:pPr><w:rPr><w:color w:val="666666"/></w:rPr></w:style>
+  <w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:basedOn w:val="Normal"/><w:pPr><w:keepNext/><w:spacing w:before="360" w:after="120"/><w:outlineLvl w:val="0"/></w:pPr><w:rPr><w:b/><w:sz w:val="30"/></w:rPr></w:style>
+  <w:style w:type="paragraph" w:styleId="Heading2"><w:name w:val="heading 2"/><w:basedOn w:val="Normal"/><w:pPr><w:keepNext/><w:spacing w:before="160" w:after="80"/><w:outlineLvl w:val="1"/></w:pPr><w:rPr><w:b/><w:sz w:val="23"/></w:rPr></w:style>
+  <w:style w:type="paragraph" w:styleId="ListBullet"><w:name w:val="List Bullet"/><w:basedOn w:val="Normal"/><w:pPr><w:spacing w:after="40"/><w:ind w:left="360" w:hanging="240"/></w:pPr></w:style>
+</w:styles>"#;
+
+const DOCUMENT_RELS_START: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rIdStyles" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>
+"#;
+
+/// Write a report as a minimal WordprocessingML package
+pub fn render_docx(report: &Report) -> Result<Vec<u8>> {
+    let mut body = String::new();
+    paragraph(&mut body, Some("Title"), &report.title);
+    paragraph(&mut body, Some("Subtitle"), &report.subtitle);
+
+    let mut images = Vec::new();
+    for block in &report.blocks {
+        match block {
+            ReportBlock::Heading(text) => paragraph(&mut body, Some("Heading1"), text),
+            ReportBlock::Subheading(text) => paragraph(&mut body, Some("Heading2"), text),
+            ReportBlock::Paragraph(text) => paragraph(&mut body, None, text),
+            ReportBlock::Bullets(items) => {
+                for item in items {
+                    paragraph(&mut body, Some("ListBullet"), &format!("\u{2022}\t{}", item));
+                }
+            }
+            ReportBlock::Table { headers, rows, widths } => table(&mut body, headers, rows, widths),
+            ReportBlock::Image(image) => {
+                images.push(&image.png);
+                let id = images.len();
+                let cx = CONTENT_WIDTH_EMU;
+                let cy = CONTENT_WIDTH_EMU * image.height as u64 / image.width.max(1) as u64;
+                let _ = write!(
+                    body,
+                    r#"<w:p><w:r><w:drawing><wp:inline distT="0" distB="0" distL="0" distR="0"><wp:extent cx="{cx}" cy="{cy}"/><wp:docPr id="{id}" name="Image {id}"/><a:graphic xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main"><a:graphicData uri="http://schemas.openxmlformats.org/drawingml/2006/picture"><pic:pic xmlns:pic="http://schemas.openxmlformats.org/drawingml/2006/picture"><pic:nvPicPr><pic:cNvPr id="{id}" name="image{id}.png"/><pic:cNvPicPr/></pic:nvPicPr><pic:blipFill><a:blip r:embed="rIdImage{id}"/><a:stretch><a:fillRect/></a:stretch></pic:blipFill><pic:spPr><a:xfrm><a:off x="0" y="0"/><a:ext cx="{cx}" cy="{cy}"/></a:xfrm><a:prstGeom prst="rect"><a:avLst/></a:prstGeom></pic:spPr></pic:pic></a:graphicData></a:graphic></wp:inline></w:drawing></w:r></w:p>"#
+                );
+            }
+        }
+    }
+
+    let document = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" xmlns:wp="http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing"><w:body>{}<w:sectPr><w:pgSz w:w="12240" w:h="15840"/><w:pgMar w:top="1440" w:right="1440" w:bottom="1440" w:left="1440" w:header="720" w:footer="720" w:gutter="0"/></w:sectPr></w:body></w:document>"#,
+        body
+    );
+
+    let mut document_rels = DOCUMENT_RELS_START.to_string();
+    for id in 1..=images.len() {
+        let _ = writeln!(
+            document_rels,
+            r#"  <Relationship Id="rIdImage{id}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="media/image{id}.png"/>"#
+        );
+    }
+    document_rels.push_str("</Relationships>");
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let parts = [
+        ("[Content_Types].xml", CONTENT_TYPES.as_bytes()),
+        ("_rels/.rels", PACKAGE_RELS.as_bytes()),
+        ("word/document.xml", document.as_bytes()),
+        ("word/styles.xml", STYLES.as_bytes()),
+        ("word/_rels/document.xml.rels", document_rels.as_bytes()),
+    ];
+    for (name, data) in parts {
+        zip.start_file(name, options)?;
+        zip.write_all(data)?;
+    }
+    for (i, png) in images.iter().enumerate() {
+        // PNG data is already compressed
+        zip.start_file(format!("word/media/image{}.png", i + 1), options.compression_method(CompressionMethod::Stored))?;
+        zip.write_all(png)?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+fn paragraph(body: &mut String, style: Option<&str>, text: &str) {
+    body.push_str("<w:p>");
+    if let Some(style) = style {
+        let _ = write!(body, r#"<w:pPr><w:pStyle w:val="{}"/></w:pPr>"#, style);
+    }
+    runs(body, text, false);
+    body.push_str("</w:p>");
+}
+
+/// Text runs, with line breaks and tabs as Word elements
+fn runs(body: &mut String, text: &str, bold: bool) {
+    for (i, line) in text.split('\n').enumerate() {
+        body.push_str("<w:r>");
+        if bold {
+            body.push_str("<w:rPr><w:b/></w:rPr>");
+        }
+        if i > 0 {
+            body.push_str("<w:br/>");
+        }
+        for (j, part) in line.split('\t').enumerate() {
+            if j > 0 {
+                body.push_str("<w:tab/>");
+            }
+            let _ = write!(body, r#"<w:t xml:space="preserve">{}</w:t>"#, escape_xml(part));
+        }
+        body.push_str("</w:r>");
+    }
+}
+
+fn table(body: &mut String, headers: &[String], rows: &[Vec<String>], widths: &[f32]) {
+    let total: f32 = widths.iter().sum();
+    let columns: Vec<u32> = widths.iter().map(|w| (w / total * CONTENT_WIDTH_TWIPS) as u32).collect();
+
+    body.push_str(r#"<w:tbl><w:tblPr><w:tblW w:w="0" w:type="auto"/><w:tblBorders>"#);
+    for side in ["top", "bottom", "insideH"] {
+        let _ = write!(body, r#"<w:{} w:val="single" w:sz="4" w:space="0" w:color="BFBFBF"/>"#, side);
+    }
+    body.push_str("</w:tblBorders></w:tblPr><w:tblGrid>");
+    for width in &columns {
+        let _ = write!(body, r#"<w:gridCol w:w="{}"/>"#, width);
+    }
+    body.push_str("</w:tblGrid>");
+
+    let header_row = std::iter::once((headers, true));
+    for (cells, header) in header_row.chain(rows.iter().map(|row| (row.as_slice(), false))) {
+        body.push_str("<w:tr>");
+        if header {
+            body.push_str("<w:trPr><w:tblHeader/></w:trPr>");
+        }
+        for (cell, width) in cells.iter().zip(&columns) {
+            let _ = write!(body, r#"<w:tc><w:tcPr><w:tcW w:w="{}" w:type="dxa"/>"#, width);
+            if header {
+                body.push_str(r#"<w:shd w:val="clear" w:color="auto" w:fill="EBEBEB"/>"#);
+            }
+            body.push_str(r#"</w:tcPr><w:p><w:pPr><w:spacing w:after="0"/></w:pPr>"#);
+            runs(body, cell, header);
+            body.push_str("</w:p></w:tc>");
+        }
+        body.push_str("</w:tr>");
+    }
+    body.push_str("</w:tbl><w:p/>");
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            // Control characters are not allowed in XML 1.0
+            c if (c as u32) < 0x20 => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,501 @@
+use crate::commands::canvas_export::{rasterize_svg_pixmap, render_canvas_svg};
+use crate::commands::export_history::{new_export, unique_path, ExportKind};
+use crate::commands::report_docx::render_docx;
+use crate::commands::report_pdf::render_pdf;
+use crate::commands::workspace::{gather_project_data, resolve_project_folder, slugify, ProjectExportData};
+use crate::db::{models::*, queries::Queries, DbPool};
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use tauri::State;
+
+/// Bumped whenever the report layout changes, and stored with every export
+pub const REPORT_EXPORT_VERSION: &str = "1.0";
+
+const CANVAS_WIDTH: u32 = 1600;
+const CANVAS_HEIGHT: u32 = 1000;
+
+/// Format of a rendered project report
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Pdf,
+    Docx,
+}
+
+impl ReportFormat {
+    pub fn parse(format: &str) -> Option<Self> {
+        match format.to_lowercase().as_str() {
+            "pdf" => Some(ReportFormat::Pdf),
+            "docx" => Some(ReportFormat::Docx),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportFormat::Pdf => "pdf",
+            ReportFormat::Docx => "docx",
+        }
+    }
+}
+
+/// Parts of a project report; ids match the export dialog's section ids
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportSection {
+    Problem,
+    Personas,
+    PainPoints,
+    Solutions,
+    Votes,
+    UserStories,
+    Canvas,
+}
+
+impl ReportSection {
+    pub const ALL: [ReportSection; 7] = [
+        ReportSection::Problem,
+        ReportSection::Personas,
+        ReportSection::PainPoints,
+        ReportSection::Solutions,
+        ReportSection::Votes,
+        ReportSection::UserStories,
+        ReportSection::Canvas,
+    ];
+
+    pub fn parse(section: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|s| s.as_str() == section)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportSection::Problem => "problem",
+            ReportSection::Personas => "personas",
+            ReportSection::PainPoints => "painPoints",
+            ReportSection::Solutions => "solutions",
+            ReportSection::Votes => "votes",
+            ReportSection::UserStories => "userStories",
+            ReportSection::Canvas => "canvas",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportReportRequest {
+    pub project_id: String,
+    /// "pdf" or "docx"
+    pub format: String,
+    /// Section ids to include; defaults to all. Ids the report does not know are ignored.
+    pub sections: Option<Vec<String>>,
+    pub created_by: Option<String>,
+}
+
+/// A format-neutral report that the PDF and DOCX writers lay out
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub title: String,
+    pub subtitle: String,
+    pub blocks: Vec<ReportBlock>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ReportBlock {
+    Heading(String),
+    Subheading(String),
+    Paragraph(String),
+    Bullets(Vec<String>),
+    Table {
+        headers: Vec<String>,
+        rows: Vec<Vec<String>>,
+        /// Relative column widths
+        widths: Vec<f32>,
+    },
+    Image(ReportImage),
+}
+
+/// The canvas as both PNG (for DOCX) and RGB flattened onto white (for PDF)
+#[derive(Debug, Clone)]
+pub struct ReportImage {
+    pub width: u32,
+    pub height: u32,
+    pub png: Vec<u8>,
+    pub rgb: Vec<u8>,
+}
+
+/// Render a project report as PDF or DOCX into the project's `exports` folder and record it
+/// in the export history
+#[tauri::command]
+pub async fn export_project_report(
+    request: ExportReportRequest,
+    pool: State<'_, DbPool>,
+) -> Result<ExportRecord, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    export_report(&queries, &request).await.map_err(|e| e.to_string())
+}
+
+pub async fn export_report(queries: &Queries, request: &ExportReportRequest) -> Result<ExportRecord> {
+    let format = ReportFormat::parse(&request.format)
+        .ok_or_else(|| anyhow!("Unsupported report format '{}'; expected pdf or docx", request.format))?;
+    let sections = match &request.sections {
+        Some(ids) => ReportSection::ALL.iter().copied()
+            .filter(|s| ids.iter().any(|id| id == s.as_str()))
+            .collect(),
+        None => ReportSection::ALL.to_vec(),
+    };
+    if sections.is_empty() {
+        return Err(anyhow!("No report sections selected"));
+    }
+
+    let data = gather_project_data(&request.project_id, queries).await?;
+    let session = queries.get_latest_focus_group_session(&request.project_id)?;
+    let report = build_report(&data, session.as_ref(), &sections)?;
+
+    let bytes = match format {
+        ReportFormat::Pdf => render_pdf(&report)?,
+        ReportFormat::Docx => render_docx(&report)?,
+    };
+
+    let folder = resolve_project_folder(queries, &request.project_id)?.join("exports");
+    fs::create_dir_all(&folder)
+        .with_context(|| format!("Failed to create {}", folder.display()))?;
+    let now = Utc::now();
+    // Exports within the same second would otherwise share a name
+    let path = unique_path(&folder.join(format!(
+        "{}-report-{}.{}",
+        slugify(&data.project.name),
+        now.format("%Y%m%d-%H%M%S"),
+        format.as_str()
+    )));
+    fs::write(&path, &bytes).with_context(|| format!("Failed to write {}", path.display()))?;
+
+    let record = ExportRecord {
+        file_path: Some(path.to_string_lossy().to_string()),
+        file_size_bytes: Some(bytes.len() as i64),
+        included_sections: sections.iter().map(|s| s.as_str().to_string()).collect(),
+        export_version: Some(REPORT_EXPORT_VERSION.to_string()),
+        created_by: request.created_by.clone(),
+        ..new_export(&request.project_id, ExportKind::Report, format.as_str(), request)?
+    };
+    queries.record_export(&record)?;
+
+    Ok(record)
+}
+
+/// Lay out the selected sections of a project
+pub fn build_report(
+    data: &ProjectExportData,
+    session: Option<&FocusGroupSession>,
+    sections: &[ReportSection],
+) -> Result<Report> {
+    let persona_names: HashMap<&str, &str> = data.personas.iter()
+        .map(|p| (p.id.as_str(), p.name.as_str()))
+        .collect();
+    let mut votes: HashMap<&str, i32> = HashMap::new();
+    for vote in session.map(|s| s.persona_votes.as_slice()).unwrap_or_default() {
+        *votes.entry(vote.solution_id.as_str()).or_default() += vote.votes;
+    }
+
+    let mut blocks = Vec::new();
+    for section in sections {
+        match section {
+            ReportSection::Problem => {
+                blocks.push(ReportBlock::Heading("Problem Statement".to_string()));
+                match &data.core_problem {
+                    Some(problem) => {
+                        let statement = problem.validated_problem.as_deref().unwrap_or(&problem.original_input);
+                        blocks.push(ReportBlock::Paragraph(statement.to_string()));
+                        if let Some(feedback) = problem.validation_feedback.as_deref().filter(|f| !f.trim().is_empty()) {
+                            blocks.push(ReportBlock::Subheading("Validation Feedback".to_string()));
+                            blocks.push(ReportBlock::Paragraph(feedback.to_string()));
+                        }
+                    }
+                    None => blocks.push(ReportBlock::Paragraph("No problem statement has been captured yet.".to_string())),
+                }
+            }
+            ReportSection::Personas => {
+                blocks.push(ReportBlock::Heading("Personas".to_string()));
+                if data.personas.is_empty() {
+                    blocks.push(ReportBlock::Paragraph("No personas have been generated yet.".to_string()));
+                    continue;
+                }
+                blocks.push(ReportBlock::Table {
+                    headers: vec!["Persona".into(), "Role".into(), "Industry".into(), "Pain degree".into()],
+                    rows: data.personas.iter()
+                        .map(|p| vec![
+                            p.name.clone(),
+                            p.role.clone(),
+                            p.industry.clone(),
+                            format!("{}/5", p.pain_degree),
+                        ])
+                        .collect(),
+                    widths: vec![3.0, 3.0, 3.0, 1.5],
+                });
+            }
+            ReportSection::PainPoints => {
+                blocks.push(ReportBlock::Heading("Pain Points".to_string()));
+                if data.pain_points.is_empty() {
+                    blocks.push(ReportBlock::Paragraph("No pain points have been identified yet.".to_string()));
+                    continue;
+                }
+                for persona in &data.personas {
+                    let points: Vec<String> = data.pain_points.iter()
+                        .filter(|p| p.persona_id == persona.id)
+                        .map(|p| match &p.severity {
+                            Some(severity) => format!("{} ({})", p.description, severity),
+                            None => p.description.clone(),
+                        })
+                        .collect();
+                    if !points.is_empty() {
+                        blocks.push(ReportBlock::Subheading(persona.name.clone()));
+                        blocks.push(ReportBlock::Bullets(points));
+                    }
+                }
+            }
+            ReportSection::Solutions => {
+                blocks.push(ReportBlock::Heading("Solution Matrix".to_string()));
+                if data.solutions.is_empty() {
+                    blocks.push(ReportBlock::Paragraph("No solutions have been proposed yet.".to_string()));
+                    continue;
+                }
+                blocks.push(ReportBlock::Table {
+                    headers: vec![
+                        "Solution".into(), "Persona".into(), "Type".into(), "Complexity".into(),
+                        "Pain points".into(), "Votes".into(), "Selected".into(),
+                    ],
+                    rows: data.solutions.iter()
+                        .map(|s| vec![
+                            s.title.clone(),
+                            persona_names.get(s.persona_id.as_str()).copied().unwrap_or("-").to_string(),
+                            s.solution_type.clone().unwrap_or_else(|| "-".to_string()),
+                            s.complexity.clone().unwrap_or_else(|| "-".to_string()),
+                            data.solution_mappings.iter().filter(|m| m.solution_id == s.id).count().to_string(),
+                            votes.get(s.id.as_str()).copied().unwrap_or(0).to_string(),
+                            if s.is_selected { "Yes" } else { "No" }.to_string(),
+                        ])
+                        .collect(),
+                    widths: vec![3.5, 2.0, 1.6, 1.6, 1.3, 1.0, 1.2],
+                });
+            }
+            ReportSection::Votes => {
+                blocks.push(ReportBlock::Heading("Focus Group Votes".to_string()));
+                let Some(session) = session else {
+                    blocks.push(ReportBlock::Paragraph("No focus group session has been completed yet.".to_string()));
+                    continue;
+                };
+                let cast: i32 = session.persona_votes.iter().map(|v| v.votes).sum();
+                blocks.push(ReportBlock::Paragraph(format!(
+                    "{} of {} votes cast in a {} session.",
+                    cast,
+                    session.total_votes_available,
+                    session.session_type.replace('_', " ")
+                )));
+
+                let mut ranked: Vec<&Solution> = data.solutions.iter().collect();
+                ranked.sort_by_key(|s| std::cmp::Reverse(votes.get(s.id.as_str()).copied().unwrap_or(0)));
+                blocks.push(ReportBlock::Table {
+                    headers: vec!["Solution".into(), "Votes".into(), "Share".into()],
+                    rows: ranked.iter()
+                        .map(|s| {
+                            let count = votes.get(s.id.as_str()).copied().unwrap_or(0);
+                            let share = if cast > 0 { count as f64 * 100.0 / cast as f64 } else { 0.0 };
+                            vec![s.title.clone(), count.to_string(), format!("{:.0}%", share)]
+                        })
+                        .collect(),
+                    widths: vec![5.0, 1.0, 1.0],
+                });
+
+                for persona in &data.personas {
+                    let cast_by: Vec<String> = session.persona_votes.iter()
+                        .filter(|v| v.persona_id == persona.id && v.votes > 0)
+                        .map(|v| {
+                            let title = data.solutions.iter()
+                                .find(|s| s.id == v.solution_id)
+                                .map(|s| s.title.as_str())
+                                .unwrap_or("Unknown solution");
+                            format!("{} x {}", v.votes, title)
+                        })
+                        .collect();
+                    if !cast_by.is_empty() {
+                        blocks.push(ReportBlock::Paragraph(format!("{}: {}", persona.name, cast_by.join(", "))));
+                    }
+                }
+            }
+            ReportSection::UserStories => {
+                blocks.push(ReportBlock::Heading("User Stories".to_string()));
+                if data.user_stories.is_empty() {
+                    blocks.push(ReportBlock::Paragraph("No user stories have been written yet.".to_string()));
+                    continue;
+                }
+                for story in &data.user_stories {
+                    let mut title = story.title.clone();
+                    if let Some(priority) = &story.priority {
+                        title.push_str(&format!(" ({} priority)", priority));
+                    }
+                    blocks.push(ReportBlock::Subheading(title));
+                    blocks.push(ReportBlock::Paragraph(format!(
+                        "As a {}, I want {} so that {}.",
+                        story.as_a,
+                        story.i_want,
+                        story.so_that.trim_end_matches('.')
+                    )));
+                    if !story.acceptance_criteria.is_empty() {
+                        blocks.push(ReportBlock::Bullets(story.acceptance_criteria.clone()));
+                    }
+                }
+            }
+            ReportSection::Canvas => {
+                blocks.push(ReportBlock::Heading("Canvas".to_string()));
+                match canvas_image(data.canvas_state.as_ref())? {
+                    Some(image) => blocks.push(ReportBlock::Image(image)),
+                    None => blocks.push(ReportBlock::Paragraph("The canvas is empty.".to_string())),
+                }
+            }
+        }
+    }
+
+    Ok(Report {
+        title: format!("{} - Project Report", data.project.name),
+        subtitle: format!("Generated {}", Utc::now().format("%B %-d, %Y")),
+        blocks,
+    })
+}
+
+fn canvas_image(canvas: Option<&CanvasState>) -> Result<Option<ReportImage>> {
+    let Some(canvas) = canvas else { return Ok(None) };
+    let nodes = canvas.nodes.as_array().cloned().unwrap_or_default();
+    if nodes.is_empty() {
+        return Ok(None);
+    }
+    let edges = canvas.edges.as_array().cloned().unwrap_or_default();
+
+    let svg = render_canvas_svg(&nodes, &edges, CANVAS_WIDTH, CANVAS_HEIGHT);
+    let pixmap = rasterize_svg_pixmap(&svg, CANVAS_WIDTH, CANVAS_HEIGHT)?;
+    let png = pixmap.encode_png().map_err(|e| anyhow!("Failed to encode PNG: {}", e))?;
+
+    // Pixels are premultiplied, so compositing onto white is `c + (255 - a)`
+    let rgb = pixmap.data()
+        .chunks_exact(4)
+        .flat_map(|px| {
+            let background = 255 - px[3];
+            [px[0] + background, px[1] + background, px[2] + background]
+        })
+        .collect();
+
+    Ok(Some(ReportImage { width: CANVAS_WIDTH, height: CANVAS_HEIGHT, png, rgb }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_data() -> ProjectExportData {
+        let now = Utc::now();
+        ProjectExportData {
+            project: Project {
+                id: "p1".into(),
+                workspace_id: "w1".into(),
+                name: "Clinic Scheduler".into(),
+                status: "focus_group".into(),
+                current_step: "focus_group".into(),
+                langgraph_state: None,
+                created_at: now,
+                updated_at: now,
+            },
+            core_problem: Some(CoreProblem {
+                id: "cp1".into(),
+                project_id: "p1".into(),
+                original_input: "Clinics lose money to no-shows".into(),
+                validated_problem: None,
+                is_valid: true,
+                validation_feedback: None,
+                version: 1,
+                created_at: now,
+            }),
+            personas: vec![Persona {
+                id: "pe1".into(),
+                core_problem_id: "cp1".into(),
+                name: "Sarah".into(),
+                industry: "Healthcare".into(),
+                role: "Practice manager".into(),
+                pain_degree: 4,
+                position: 0,
+                is_locked: false,
+                is_active: true,
+                generation_batch: None,
+                created_at: now,
+            }],
+            pain_points: Vec::new(),
+            solutions: vec![Solution {
+                id: "s1".into(),
+                project_id: "p1".into(),
+                persona_id: "pe1".into(),
+                title: "SMS reminders".into(),
+                description: "Remind patients a day ahead".into(),
+                solution_type: None,
+                complexity: Some("low".into()),
+                position: 0,
+                is_locked: false,
+                is_selected: true,
+                generation_batch: None,
+                created_at: now,
+            }],
+            solution_mappings: Vec::new(),
+            user_stories: Vec::new(),
+            canvas_state: None,
+        }
+    }
+
+    #[test]
+    fn test_report_tallies_votes_per_solution() {
+        let session = FocusGroupSession {
+            id: "fg1".into(),
+            project_id: "p1".into(),
+            session_type: "dot_voting".into(),
+            total_votes_available: 3,
+            persona_votes: vec![
+                PersonaVote { persona_id: "pe1".into(), solution_id: "s1".into(), votes: 2 },
+                PersonaVote { persona_id: "pe2".into(), solution_id: "s1".into(), votes: 1 },
+            ],
+            status: "completed".into(),
+            completed_at: None,
+            created_at: Utc::now(),
+        };
+        let report = build_report(&sample_data(), Some(&session), &[ReportSection::Solutions, ReportSection::Votes]).unwrap();
+
+        let ReportBlock::Table { rows, .. } = &report.blocks[1] else { panic!("expected the solution matrix") };
+        assert_eq!(rows[0], vec!["SMS reminders", "Sarah", "-", "low", "0", "3", "Yes"]);
+        assert!(matches!(&report.blocks[3], ReportBlock::Paragraph(p) if p == "3 of 3 votes cast in a dot voting session."));
+    }
+
+    #[test]
+    fn test_report_renders_as_pdf_and_docx() {
+        let report = build_report(&sample_data(), None, &ReportSection::ALL).unwrap();
+
+        let pdf = render_pdf(&report).unwrap();
+        assert!(pdf.starts_with(b"%PDF-"));
+
+        let docx = render_docx(&report).unwrap();
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(docx)).unwrap();
+        let mut document = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name("word/document.xml").unwrap(), &mut document).unwrap();
+        assert!(document.contains("Clinic Scheduler - Project Report"));
+        assert!(document.contains("Practice manager"));
+    }
+
+    #[tokio::test]
+    async fn test_exports_in_the_same_second_get_their_own_files() {
+        let folder = std::env::temp_dir().join(format!("prob-reports-{}", uuid::Uuid::new_v4()));
+        let (_pool, queries) = crate::db::test_db("p1", Some(&folder));
+        let request = ExportReportRequest { project_id: "p1".into(), format: "pdf".into(), sections: None, created_by: None };
+
+        let first = export_report(&queries, &request).await.unwrap();
+        let second = export_report(&queries, &request).await.unwrap();
+        assert_ne!(first.file_path, second.file_path);
+        assert!(std::path::Path::new(first.file_path.as_deref().unwrap()).is_file());
+        assert!(std::path::Path::new(second.file_path.as_deref().unwrap()).is_file());
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
@@ -0,0 +1,368 @@
+use crate::commands::report_export::{Report, ReportBlock, ReportImage};
+use anyhow::Result;
+use miniz_oxide::deflate::compress_to_vec_zlib;
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+
+// A4 in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 56.0;
+const CONTENT_WIDTH: f32 = PAGE_WIDTH - 2.0 * MARGIN;
+const BODY_SIZE: f32 = 10.5;
+const TABLE_SIZE: f32 = 9.0;
+const CELL_PADDING: f32 = 4.0;
+const COMPRESSION_LEVEL: u8 = 6;
+
+const REGULAR: Name = Name(b"F1");
+const BOLD: Name = Name(b"F2");
+
+/// Advance widths of Helvetica for ASCII 32..=126, in thousandths of the font size
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, // space - /
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, // 0 - ?
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778, // @ - O
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556, // P - _
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556, // ` - o
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584, // p - ~
+];
+
+/// Lay out a report on A4 pages with the standard Helvetica fonts, so no font files are embedded
+pub fn render_pdf(report: &Report) -> Result<Vec<u8>> {
+    let mut layout = Layout::new();
+    layout.title(&report.title, &report.subtitle);
+    for (i, block) in report.blocks.iter().enumerate() {
+        // Headings stay on the same page as the start of what follows
+        let keep_with_next = match report.blocks.get(i + 1) {
+            Some(ReportBlock::Image(image)) => image_height(image) + 8.0,
+            _ => 2.0 * BODY_SIZE * 1.4,
+        };
+        match block {
+            ReportBlock::Heading(text) => layout.heading(text, 15.0, 18.0, keep_with_next),
+            ReportBlock::Subheading(text) => layout.heading(text, 11.5, 8.0, keep_with_next),
+            ReportBlock::Paragraph(text) => layout.paragraph(text),
+            ReportBlock::Bullets(items) => layout.bullets(items),
+            ReportBlock::Table { headers, rows, widths } => layout.table(headers, rows, widths),
+            ReportBlock::Image(image) => layout.image(image),
+        }
+    }
+    Ok(layout.finish(&report.title))
+}
+
+/// Pages are kept open until the end so footers can show the page count
+struct Layout {
+    pages: Vec<Content>,
+    y: f32,
+    images: Vec<ReportImage>,
+}
+
+impl Layout {
+    fn new() -> Self {
+        Layout { pages: vec![Content::new()], y: PAGE_HEIGHT - MARGIN, images: Vec::new() }
+    }
+
+    fn page(&mut self) -> &mut Content {
+        self.pages.last_mut().expect("layout always has a page")
+    }
+
+    /// Start a new page unless `height` still fits above the bottom margin
+    fn reserve(&mut self, height: f32) {
+        if self.y - height < MARGIN && self.y < PAGE_HEIGHT - MARGIN {
+            self.pages.push(Content::new());
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+
+    fn text(&mut self, font: Name, size: f32, x: f32, baseline: f32, text: &str) {
+        let encoded = win_ansi(text);
+        self.page()
+            .begin_text()
+            .set_font(font, size)
+            .next_line(x, baseline)
+            .show(Str(&encoded))
+            .end_text();
+    }
+
+    /// Write wrapped lines from the current position and move below them
+    fn lines(&mut self, lines: &[String], font: Name, size: f32, x: f32) {
+        let leading = size * 1.4;
+        for line in lines {
+            self.reserve(leading);
+            self.y -= leading;
+            self.text(font, size, x, self.y + size * 0.3, line);
+        }
+    }
+
+    fn title(&mut self, title: &str, subtitle: &str) {
+        let lines = wrap(title, true, 22.0, CONTENT_WIDTH);
+        self.lines(&lines, BOLD, 22.0, MARGIN);
+        self.page().set_fill_gray(0.4);
+        self.lines(&[subtitle.to_string()], REGULAR, BODY_SIZE, MARGIN);
+        self.page().set_fill_gray(0.0);
+        self.y -= 12.0;
+    }
+
+    fn heading(&mut self, text: &str, size: f32, space_before: f32, keep_with_next: f32) {
+        let lines = wrap(text, true, size, CONTENT_WIDTH);
+        self.reserve(space_before + lines.len() as f32 * size * 1.4 + keep_with_next);
+        self.y -= space_before;
+        self.lines(&lines, BOLD, size, MARGIN);
+        self.y -= 2.0;
+    }
+
+    fn paragraph(&mut self, text: &str) {
+        let lines = wrap(text, false, BODY_SIZE, CONTENT_WIDTH);
+        self.lines(&lines, REGULAR, BODY_SIZE, MARGIN);
+        self.y -= 6.0;
+    }
+
+    fn bullets(&mut self, items: &[String]) {
+        let indent = 14.0;
+        for item in items {
+            let lines = wrap(item, false, BODY_SIZE, CONTENT_WIDTH - indent);
+            // Reserving the first line first keeps the bullet on the same page as its text
+            self.reserve(BODY_SIZE * 1.4);
+            let first_baseline = self.y - BODY_SIZE * 1.1;
+            self.text(REGULAR, BODY_SIZE, MARGIN + 4.0, first_baseline, "\u{2022}");
+            self.lines(&lines, REGULAR, BODY_SIZE, MARGIN + indent);
+        }
+        self.y -= 6.0;
+    }
+
+    fn table(&mut self, headers: &[String], rows: &[Vec<String>], widths: &[f32]) {
+        let total: f32 = widths.iter().sum();
+        let columns: Vec<f32> = widths.iter().map(|w| w / total * CONTENT_WIDTH).collect();
+
+        self.y -= 4.0;
+        // Never leave the header alone at the bottom of a page
+        let first_row = rows.first().map(|row| row_height(row, &columns, false)).unwrap_or(0.0);
+        self.reserve(row_height(headers, &columns, true) + first_row);
+        self.table_row(headers, &columns, true);
+        for row in rows {
+            let height = row_height(row, &columns, false);
+            if self.y - height < MARGIN {
+                self.reserve(height);
+                self.table_row(headers, &columns, true);
+            }
+            self.table_row(row, &columns, false);
+        }
+        self.y -= 10.0;
+    }
+
+    fn table_row(&mut self, cells: &[String], columns: &[f32], header: bool) {
+        let height = row_height(cells, columns, header);
+        self.reserve(height);
+        let top = self.y;
+        let bottom = top - height;
+
+        if header {
+            self.page()
+                .set_fill_gray(0.92)
+                .rect(MARGIN, bottom, CONTENT_WIDTH, height)
+                .fill_nonzero()
+                .set_fill_gray(0.0);
+        }
+
+        let font = if header { BOLD } else { REGULAR };
+        let leading = TABLE_SIZE * 1.35;
+        let mut x = MARGIN;
+        for (cell, width) in cells.iter().zip(columns) {
+            let lines = wrap(cell, header, TABLE_SIZE, width - 2.0 * CELL_PADDING);
+            for (i, line) in lines.iter().enumerate() {
+                let baseline = top - CELL_PADDING - TABLE_SIZE - i as f32 * leading;
+                self.text(font, TABLE_SIZE, x + CELL_PADDING, baseline, line);
+            }
+            x += width;
+        }
+
+        self.page()
+            .set_stroke_gray(0.75)
+            .set_line_width(0.5)
+            .move_to(MARGIN, bottom)
+            .line_to(MARGIN + CONTENT_WIDTH, bottom)
+            .stroke();
+        self.y = bottom;
+    }
+
+    fn image(&mut self, image: &ReportImage) {
+        let width = CONTENT_WIDTH;
+        let height = image_height(image);
+        self.reserve(height + 8.0);
+        self.y -= height + 8.0;
+
+        let name = format!("Im{}", self.images.len() + 1);
+        let y = self.y;
+        self.page()
+            .save_state()
+            .transform([width, 0.0, 0.0, height, MARGIN, y])
+            .x_object(Name(name.as_bytes()))
+            .restore_state();
+        self.images.push(image.clone());
+        self.y -= 8.0;
+    }
+
+    fn finish(mut self, title: &str) -> Vec<u8> {
+        let total = self.pages.len();
+        for (i, page) in self.pages.iter_mut().enumerate() {
+            let footer = win_ansi(&format!("Page {} of {}", i + 1, total));
+            let x = PAGE_WIDTH - MARGIN - text_width(&footer, false, 8.0);
+            page.set_fill_gray(0.5)
+                .begin_text()
+                .set_font(REGULAR, 8.0)
+                .next_line(x, MARGIN / 2.0)
+                .show(Str(&footer))
+                .end_text();
+        }
+
+        let mut pdf = Pdf::new();
+        let mut next = 1;
+        let mut alloc = || {
+            next += 1;
+            Ref::new(next - 1)
+        };
+        let catalog_id = alloc();
+        let page_tree_id = alloc();
+        let info_id = alloc();
+        let regular_id = alloc();
+        let bold_id = alloc();
+        let image_ids: Vec<Ref> = self.images.iter().map(|_| alloc()).collect();
+        let page_ids: Vec<(Ref, Ref)> = self.pages.iter().map(|_| (alloc(), alloc())).collect();
+
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        pdf.pages(page_tree_id)
+            .kids(page_ids.iter().map(|(page, _)| *page))
+            .count(page_ids.len() as i32);
+        pdf.document_info(info_id).title(TextStr(title)).producer(TextStr("prob"));
+        pdf.type1_font(regular_id).base_font(Name(b"Helvetica")).encoding_predefined(Name(b"WinAnsiEncoding"));
+        pdf.type1_font(bold_id).base_font(Name(b"Helvetica-Bold")).encoding_predefined(Name(b"WinAnsiEncoding"));
+
+        for (image, id) in self.images.iter().zip(&image_ids) {
+            let data = compress_to_vec_zlib(&image.rgb, COMPRESSION_LEVEL);
+            let mut xobject = pdf.image_xobject(*id, &data);
+            xobject.filter(Filter::FlateDecode);
+            xobject.width(image.width as i32);
+            xobject.height(image.height as i32);
+            xobject.color_space().device_rgb();
+            xobject.bits_per_component(8);
+        }
+
+        let image_names: Vec<String> = (1..=image_ids.len()).map(|i| format!("Im{}", i)).collect();
+        for (content, (page_id, content_id)) in self.pages.into_iter().zip(&page_ids) {
+            let mut page = pdf.page(*page_id);
+            page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT));
+            page.parent(page_tree_id);
+            page.contents(*content_id);
+            let mut resources = page.resources();
+            resources.fonts().pair(REGULAR, regular_id).pair(BOLD, bold_id);
+            // Every page shares the image dictionary; unused entries cost nothing
+            let mut x_objects = resources.x_objects();
+            for (name, id) in image_names.iter().zip(&image_ids) {
+                x_objects.pair(Name(name.as_bytes()), *id);
+            }
+            x_objects.finish();
+            resources.finish();
+            page.finish();
+
+            let data = compress_to_vec_zlib(&content.finish(), COMPRESSION_LEVEL);
+            pdf.stream(*content_id, &data).filter(Filter::FlateDecode);
+        }
+
+        pdf.finish()
+    }
+}
+
+/// Images span the content width, but never more than a page
+fn image_height(image: &ReportImage) -> f32 {
+    (CONTENT_WIDTH * image.height as f32 / image.width.max(1) as f32).min(PAGE_HEIGHT - 2.0 * MARGIN - 40.0)
+}
+
+fn row_height(cells: &[String], columns: &[f32], header: bool) -> f32 {
+    let lines = cells.iter()
+        .zip(columns)
+        .map(|(cell, width)| wrap(cell, header, TABLE_SIZE, width - 2.0 * CELL_PADDING).len())
+        .max()
+        .unwrap_or(1);
+    lines as f32 * TABLE_SIZE * 1.35 + 2.0 * CELL_PADDING
+}
+
+/// Break text into lines no wider than `max_width`; explicit newlines start new lines
+fn wrap(text: &str, bold: bool, size: f32, max_width: f32) -> Vec<String> {
+    let fits = |line: &str| text_width(&win_ansi(line), bold, size) <= max_width;
+    let mut lines = Vec::new();
+
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+            if fits(&candidate) {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            // Words wider than the line are split wherever they overflow
+            for c in word.chars() {
+                line.push(c);
+                if !fits(&line) && line.chars().count() > 1 {
+                    line.pop();
+                    lines.push(std::mem::replace(&mut line, c.to_string()));
+                }
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Width in points of WinAnsi-encoded text
+fn text_width(encoded: &[u8], bold: bool, size: f32) -> f32 {
+    let units: u32 = encoded.iter()
+        .map(|&b| match b {
+            32..=126 => HELVETICA_WIDTHS[(b - 32) as usize] as u32,
+            0x95 => 350,
+            _ => 556,
+        })
+        .sum();
+    // Helvetica-Bold is on average about 6% wider; close enough for wrapping
+    let scale = if bold { 1.06 } else { 1.0 };
+    units as f32 * scale * size / 1000.0
+}
+
+/// Encode text for the standard fonts; characters outside WinAnsi become '?'
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c {
+            ' '..='~' => c as u8,
+            '\u{a0}'..='\u{ff}' => c as u32 as u8,
+            '\u{20ac}' => 0x80,
+            '\u{2026}' => 0x85,
+            '\u{2018}' => 0x91,
+            '\u{2019}' => 0x92,
+            '\u{201c}' => 0x93,
+            '\u{201d}' => 0x94,
+            '\u{2022}' => 0x95,
+            '\u{2013}' => 0x96,
+            '\u{2014}' => 0x97,
+            '\u{2122}' => 0x99,
+            '\t' => b' ',
+            _ => b'?',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_respects_width_and_splits_long_words() {
+        let lines = wrap("The quick brown fox jumps over the lazy dog", false, 10.0, 100.0);
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|l| text_width(&win_ansi(l), false, 10.0) <= 100.0));
+        assert_eq!(lines.join(" "), "The quick brown fox jumps over the lazy dog");
+
+        let long = wrap(&"x".repeat(60), false, 10.0, 50.0);
+        assert!(long.len() > 1);
+        assert_eq!(long.concat().len(), 60);
+    }
+}
@@ -0,0 +1,583 @@
+use crate::db::{models::*, queries::Queries, DbPool};
+use crate::commands::workflow::sync_project_step;
+use crate::commands::design_tokens::render_token_files;
+use crate::commands::gherkin::{feature_file_name, render_feature};
+use crate::commands::schema_export::{render_schema, validate_schema, SchemaFormat};
+use crate::commands::workspace::slugify;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::State;
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+pub struct RepositorySetupRequest {
+    pub project_id: String,
+    pub target_dir: String,
+    pub dry_run: Option<bool>,
+    pub initialize_git: Option<bool>, // defaults to true
+}
+
+#[derive(Debug, Serialize)]
+pub struct RepositorySetupResponse {
+    pub target_dir: String,
+    pub dry_run: bool,
+    pub files: Vec<PlannedFile>,
+    pub tree: String,
+    pub git_initialized: bool,
+    pub commit: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedFile {
+    pub path: String, // relative to the target directory, always '/'-separated
+    pub bytes: usize,
+    #[serde(skip)]
+    pub content: String,
+}
+
+/// Everything the starter repository is generated from
+struct RepositorySource {
+    project: Project,
+    core_problem: Option<CoreProblem>,
+    solutions: Vec<Solution>,
+    architecture: Vec<SystemArchitecture>,
+    tables: Vec<(DatabaseTable, Vec<DatabaseColumn>)>,
+    relationships: Vec<DatabaseRelationship>,
+    screens: Vec<UIScreen>,
+    tokens: Vec<DesignToken>,
+    stories: Vec<UserStory>,
+}
+
+/// Generate a starter repository for a finished project. With `dry_run`
+/// only the planned file tree is returned and nothing touches the disk.
+#[tauri::command]
+pub async fn setup_repository(
+    request: RepositorySetupRequest,
+    pool: State<'_, DbPool>,
+) -> Result<RepositorySetupResponse, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    generate_repository(&queries, &request)
+}
+
+fn generate_repository(queries: &Queries, request: &RepositorySetupRequest) -> Result<RepositorySetupResponse, String> {
+    let dry_run = request.dry_run.unwrap_or(false);
+
+    let target = PathBuf::from(request.target_dir.trim());
+    if !target.is_absolute() {
+        return Err("Target directory must be an absolute path".to_string());
+    }
+    if target.is_file() {
+        return Err(format!("Target {} is a file", target.display()));
+    }
+    if target.is_dir() && fs::read_dir(&target).map_err(|e| e.to_string())?.next().is_some() {
+        return Err(format!("Target directory {} is not empty", target.display()));
+    }
+
+    let source = load_source(queries, &request.project_id).map_err(|e| e.to_string())?;
+    if source.architecture.is_empty() {
+        return Err("Generate the system design before setting up the repository".to_string());
+    }
+    if source.stories.is_empty() {
+        return Err("Generate user stories before setting up the repository".to_string());
+    }
+
+    let (files, mut warnings) = plan_repository(&source);
+    let root_name = target.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| slugify(&source.project.name));
+    let tree = render_tree(&root_name, &files);
+
+    let mut response = RepositorySetupResponse {
+        target_dir: target.to_string_lossy().to_string(),
+        dry_run,
+        files,
+        tree,
+        git_initialized: false,
+        commit: None,
+        warnings: Vec::new(),
+    };
+
+    if dry_run {
+        response.warnings = warnings;
+        return Ok(response);
+    }
+
+    for file in &response.files {
+        let path = target.join(&file.path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        fs::write(&path, &file.content)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+
+    if request.initialize_git.unwrap_or(true) {
+        match initialize_git(&target, &source.project.name) {
+            Ok(commit) => {
+                response.git_initialized = true;
+                response.commit = Some(commit);
+            }
+            Err(e) => warnings.push(format!("Files were written but git setup failed: {}", e)),
+        }
+    }
+    // The files are written either way, so a failed step sync is reported rather than returned
+    if let Err(e) = sync_project_step(queries, &request.project_id, &WorkflowStep::RepositorySetup, "repository_setup") {
+        warnings.push(format!("Project step was not moved to '{}': {}", WorkflowStep::RepositorySetup.to_string(), e));
+    }
+    response.warnings = warnings;
+
+    queries
+        .append_state_event(&LangGraphStateEvent {
+            id: Uuid::new_v4().to_string(),
+            project_id: request.project_id.clone(),
+            event_type: "repository_generated".to_string(),
+            event_data: serde_json::json!({
+                "targetDir": response.target_dir,
+                "fileCount": response.files.len(),
+                "commit": response.commit,
+            }),
+            event_metadata: None,
+            sequence_number: 0,
+            created_at: Utc::now(),
+            created_by: Some("repository_setup".to_string()),
+        })
+        .map_err(|e| e.to_string())?;
+
+    Ok(response)
+}
+
+fn load_source(queries: &Queries, project_id: &str) -> anyhow::Result<RepositorySource> {
+    let project = queries.get_project(project_id)?
+        .ok_or_else(|| anyhow::anyhow!("Project not found"))?;
+
+    let solutions = queries.get_solutions_with_mappings(project_id)?
+        .into_iter()
+        .map(|(solution, _)| solution)
+        .filter(|solution| solution.is_selected)
+        .collect();
+
+    Ok(RepositorySource {
+        core_problem: queries.get_latest_core_problem(project_id)?,
+        solutions,
+        architecture: queries.get_system_architecture(project_id, None)?,
+        tables: queries.get_database_tables(project_id)?,
+        relationships: queries.get_database_relationships(project_id)?,
+        screens: queries.get_ui_screens(project_id)?,
+        tokens: queries.get_design_tokens(project_id)?,
+        stories: queries.get_user_stories(project_id)?,
+        project,
+    })
+}
+
+// Planning
+
+fn plan_repository(source: &RepositorySource) -> (Vec<PlannedFile>, Vec<String>) {
+    let mut files = Vec::new();
+    let mut warnings = Vec::new();
+    let mut add = |path: String, content: String| files.push(PlannedFile {
+        bytes: content.len(),
+        path,
+        content,
+    });
+
+    add("README.md".to_string(), render_readme(source));
+    add(".gitignore".to_string(), "node_modules/\ndist/\nbuild/\ntarget/\n.env\n.env.*\n.DS_Store\n".to_string());
+    add("docs/problem.md".to_string(), render_problem_doc(source));
+    add("docs/architecture.md".to_string(), render_architecture_doc(source));
+    add("docs/user-stories.md".to_string(), render_story_index(source));
+
+    if source.screens.is_empty() {
+        warnings.push("No UI screens designed; docs/screens.md was skipped".to_string());
+    } else {
+        add("docs/screens.md".to_string(), render_screens_doc(source));
+    }
+
+    if source.tables.is_empty() {
+        warnings.push("No database tables designed; schema files were skipped".to_string());
+    } else {
+        let issues = validate_schema(&source.tables, &source.relationships);
+        let errors: Vec<&str> = issues.iter()
+            .filter(|i| i.severity == "error")
+            .map(|i| i.message.as_str())
+            .collect();
+        if errors.is_empty() {
+            for format in [SchemaFormat::Postgres, SchemaFormat::Sqlite, SchemaFormat::Mermaid] {
+                add(format!("schema/{}", format.filename()), render_schema(format, &source.tables, &source.relationships));
+            }
+        } else {
+            warnings.push(format!("Schema files were skipped: {}", errors.join("; ")));
+        }
+    }
+
+    if source.tokens.is_empty() {
+        warnings.push("No design tokens defined; design-tokens/ was skipped".to_string());
+    } else {
+        let (token_files, issues) = render_token_files(&source.tokens);
+        warnings.extend(issues.into_iter().map(|issue| issue.message));
+        for (name, content) in token_files {
+            add(format!("design-tokens/{}", name), content);
+        }
+    }
+
+    for story in &source.stories {
+        add(format!("features/{}", feature_file_name(story)), render_feature(story));
+        add(format!("backlog/{}", backlog_file_name(story)), render_backlog_issue(story));
+    }
+
+    add(".github/ISSUE_TEMPLATE/user-story.md".to_string(), ISSUE_TEMPLATE.to_string());
+
+    (files, warnings)
+}
+
+const ISSUE_TEMPLATE: &str = "---\nname: User story\nabout: Describe a user-facing capability\nlabels: user-story\n---\n\n\
+**As a** ...\n**I want** ...\n**So that** ...\n\n## Acceptance criteria\n\n- [ ] ...\n";
+
+fn backlog_file_name(story: &UserStory) -> String {
+    format!("{:02}-{}.md", story.position + 1, slugify(&story.title))
+}
+
+fn render_readme(source: &RepositorySource) -> String {
+    let mut out = format!("# {}\n\n", source.project.name);
+
+    if let Some(problem) = &source.core_problem {
+        out.push_str(&format!("{}\n\n", problem.validated_problem.as_deref().unwrap_or(&problem.original_input)));
+    }
+
+    if !source.solutions.is_empty() {
+        out.push_str("## Solutions\n\n");
+        for solution in &source.solutions {
+            out.push_str(&format!("- **{}**: {}\n", solution.title, solution.description));
+        }
+        out.push('\n');
+    }
+
+    out.push_str("## Tech stack\n\n");
+    out.push_str(&markdown_table(
+        &["Layer", "Technology"],
+        source.architecture.iter().map(|a| vec![a.layer.clone(), a.technology.clone()]).collect(),
+    ));
+
+    out.push_str("\n## Repository layout\n\n");
+    out.push_str("- `docs/` - problem statement, architecture and screens\n");
+    out.push_str("- `backlog/` - one issue per user story\n");
+    out.push_str("- `features/` - Gherkin acceptance tests generated from the user stories\n");
+    if !source.tables.is_empty() {
+        out.push_str("- `schema/` - database DDL and ER diagram\n");
+    }
+    if !source.tokens.is_empty() {
+        out.push_str("- `design-tokens/` - CSS variables, Tailwind theme and Style Dictionary tokens\n");
+    }
+
+    out
+}
+
+fn render_problem_doc(source: &RepositorySource) -> String {
+    let mut out = String::from("# Problem\n\n");
+
+    match &source.core_problem {
+        Some(problem) => {
+            if let Some(validated) = &problem.validated_problem {
+                out.push_str(&format!("{}\n\n", validated));
+            }
+            out.push_str(&format!("## Original input\n\n> {}\n", problem.original_input.replace('\n', "\n> ")));
+            if let Some(feedback) = &problem.validation_feedback {
+                out.push_str(&format!("\n## Validation feedback\n\n{}\n", feedback));
+            }
+        }
+        None => out.push_str("_No problem statement recorded._\n"),
+    }
+
+    if !source.solutions.is_empty() {
+        out.push_str("\n## Selected solutions\n\n");
+        for solution in &source.solutions {
+            out.push_str(&format!("### {}\n\n{}\n\n", solution.title, solution.description));
+        }
+    }
+
+    out
+}
+
+fn render_architecture_doc(source: &RepositorySource) -> String {
+    let mut out = String::from("# Architecture\n\n## Tech stack\n\n");
+    out.push_str(&markdown_table(
+        &["Layer", "Technology", "Why"],
+        source.architecture.iter()
+            .map(|a| vec![a.layer.clone(), a.technology.clone(), a.justification.clone()])
+            .collect(),
+    ));
+
+    if !source.tables.is_empty() {
+        out.push_str("\n## Data model\n\n```mermaid\n");
+        out.push_str(&render_schema(SchemaFormat::Mermaid, &source.tables, &source.relationships));
+        out.push_str("```\n");
+    }
+
+    out
+}
+
+fn render_screens_doc(source: &RepositorySource) -> String {
+    let mut out = String::from("# Screens\n\n");
+    out.push_str(&markdown_table(
+        &["Screen", "Route", "Description"],
+        source.screens.iter()
+            .map(|s| vec![
+                s.screen_name.clone(),
+                s.route_path.clone().map(|r| format!("`{}`", r)).unwrap_or_default(),
+                s.description.clone().unwrap_or_default(),
+            ])
+            .collect(),
+    ));
+    out
+}
+
+fn render_story_index(source: &RepositorySource) -> String {
+    let mut out = String::from("# User stories\n\n");
+    out.push_str(&markdown_table(
+        &["#", "Story", "Priority", "Points"],
+        source.stories.iter()
+            .map(|story| vec![
+                (story.position + 1).to_string(),
+                format!("[{}](../backlog/{})", story.title, backlog_file_name(story)),
+                story.priority.clone().unwrap_or_default(),
+                story.complexity_points.map(|p| p.to_string()).unwrap_or_default(),
+            ])
+            .collect(),
+    ));
+    out
+}
+
+fn render_backlog_issue(story: &UserStory) -> String {
+    let mut out = format!("---\ntitle: \"{}\"\nlabels: [user-story", story.title.replace('"', "'"));
+    if let Some(priority) = &story.priority {
+        out.push_str(&format!(", priority-{}", slugify(priority)));
+    }
+    out.push_str("]\n---\n\n");
+
+    out.push_str(&format!("**As a** {}\n**I want** {}\n**So that** {}\n", story.as_a, story.i_want, story.so_that));
+    if let Some(points) = story.complexity_points {
+        out.push_str(&format!("\n**Estimate:** {} points\n", points));
+    }
+
+    out.push_str("\n## Acceptance criteria\n\n");
+    if story.acceptance_criteria.is_empty() {
+        out.push_str("- [ ] _To be defined_\n");
+    }
+    for criterion in &story.acceptance_criteria {
+        out.push_str(&format!("- [ ] {}\n", criterion));
+    }
+
+    out.push_str(&format!("\nAcceptance tests: [`features/{}`](../features/{})\n", feature_file_name(story), feature_file_name(story)));
+    out
+}
+
+fn markdown_table(headers: &[&str], rows: Vec<Vec<String>>) -> String {
+    let escape = |cell: &str| cell.replace('|', "\\|").replace('\n', " ");
+    let mut out = format!("| {} |\n|{}|\n", headers.join(" | "), vec!["---"; headers.len()].join("|"));
+    for row in rows {
+        out.push_str(&format!("| {} |\n", row.iter().map(|c| escape(c)).collect::<Vec<_>>().join(" | ")));
+    }
+    out
+}
+
+/// Render planned paths as an indented tree, directories before files
+fn render_tree(root_name: &str, files: &[PlannedFile]) -> String {
+    #[derive(Default)]
+    struct Dir {
+        dirs: BTreeMap<String, Dir>,
+        files: Vec<String>,
+    }
+
+    fn write(dir: &Dir, prefix: &str, out: &mut String) {
+        let entries: Vec<(String, Option<&Dir>)> = dir.dirs.iter()
+            .map(|(name, child)| (format!("{}/", name), Some(child)))
+            .chain(dir.files.iter().map(|name| (name.clone(), None)))
+            .collect();
+
+        for (index, (name, child)) in entries.iter().enumerate() {
+            let last = index + 1 == entries.len();
+            out.push_str(&format!("{}{}{}\n", prefix, if last { "└── " } else { "├── " }, name));
+            if let Some(child) = child {
+                write(child, &format!("{}{}", prefix, if last { "    " } else { "│   " }), out);
+            }
+        }
+    }
+
+    fn sort_files(dir: &mut Dir) {
+        dir.files.sort();
+        for child in dir.dirs.values_mut() {
+            sort_files(child);
+        }
+    }
+
+    let mut root = Dir::default();
+    for file in files {
+        let mut parts: Vec<&str> = file.path.split('/').collect();
+        let name = parts.pop().unwrap_or_default();
+        let dir = parts.into_iter().fold(&mut root, |dir, part| dir.dirs.entry(part.to_string()).or_default());
+        dir.files.push(name.to_string());
+    }
+    sort_files(&mut root);
+
+    let mut out = format!("{}/\n", root_name);
+    write(&root, "", &mut out);
+    out
+}
+
+// Git
+
+/// Run `git init` and commit everything, returning the new commit hash
+fn initialize_git(dir: &Path, project_name: &str) -> Result<String, String> {
+    git(dir, &["init", "-q"])?;
+    git(dir, &["symbolic-ref", "HEAD", "refs/heads/main"])?;
+    git(dir, &["add", "-A"])?;
+
+    // Fall back to a local identity so the commit works on machines without git config
+    let has_identity = git(dir, &["config", "user.email"])
+        .map(|email| !email.is_empty())
+        .unwrap_or(false);
+    let message = format!("Initial commit: scaffold {}", project_name);
+    let mut args = Vec::new();
+    if !has_identity {
+        args.extend(["-c", "user.name=prob", "-c", "user.email=prob@localhost"]);
+    }
+    args.extend(["commit", "-q", "-m", message.as_str()]);
+    git(dir, &args)?;
+
+    git(dir, &["rev-parse", "HEAD"])
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(format!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_db;
+
+    fn planned(paths: &[&str]) -> Vec<PlannedFile> {
+        paths.iter()
+            .map(|path| PlannedFile { path: path.to_string(), bytes: 0, content: String::new() })
+            .collect()
+    }
+
+    #[test]
+    fn test_tree_lists_directories_before_files() {
+        let tree = render_tree("demo", &planned(&["README.md", "docs/problem.md", "docs/architecture.md", ".gitignore"]));
+
+        assert_eq!(tree, "demo/\n├── docs/\n│   ├── architecture.md\n│   └── problem.md\n├── .gitignore\n└── README.md\n");
+    }
+    /// A project with the tech stack, one table and one story that setup needs
+    fn designed_project(folder: &Path) -> Queries {
+        let (_pool, queries) = test_db("p1", Some(folder));
+        let architecture = SystemArchitecture {
+            id: "a1".to_string(),
+            project_id: "p1".to_string(),
+            layer: "Frontend".to_string(),
+            technology: "React + TypeScript".to_string(),
+            justification: "Typed components".to_string(),
+            version: 1,
+            created_at: Utc::now(),
+        };
+        let table = DatabaseTable { id: "t1".to_string(), project_id: "p1".to_string(), table_name: "users".to_string(), created_at: Utc::now() };
+        let column = DatabaseColumn {
+            id: "c1".to_string(),
+            table_id: "t1".to_string(),
+            column_name: "id".to_string(),
+            data_type: "UUID".to_string(),
+            is_primary_key: true,
+            is_foreign_key: false,
+            references_table: None,
+            constraints: vec!["NOT NULL".to_string()],
+            created_at: Utc::now(),
+        };
+        queries.save_system_design("p1", &[architecture], &[(table, vec![column])], &[], &[]).unwrap();
+        queries.create_user_stories(&[UserStory {
+            id: "us1".to_string(),
+            project_id: "p1".to_string(),
+            title: "Sign up".to_string(),
+            as_a: "visitor".to_string(),
+            i_want: "to create an account".to_string(),
+            so_that: "I can save my work".to_string(),
+            acceptance_criteria: vec!["Given a new email, when I sign up, then I am logged in".to_string()],
+            priority: None,
+            complexity_points: None,
+            position: 0,
+            is_edited: false,
+            original_content: None,
+            edited_content: None,
+            created_at: Utc::now(),
+        }]).unwrap();
+        queries
+    }
+
+    fn request(target: &Path, dry_run: bool) -> RepositorySetupRequest {
+        RepositorySetupRequest {
+            project_id: "p1".to_string(),
+            target_dir: target.to_string_lossy().to_string(),
+            dry_run: Some(dry_run),
+            initialize_git: Some(true),
+        }
+    }
+
+    #[test]
+    fn test_dry_run_plans_without_writing() {
+        let folder = std::env::temp_dir().join(format!("prob-repo-{}", Uuid::new_v4()));
+        let queries = designed_project(&folder);
+        let target = folder.join("starter");
+
+        let response = generate_repository(&queries, &request(&target, true)).unwrap();
+        assert!(response.dry_run);
+        assert!(response.files.iter().any(|f| f.path == "README.md"));
+        assert!(response.tree.starts_with("starter/\n"));
+        assert!(!target.exists());
+        assert!(response.commit.is_none());
+
+        fs::remove_dir_all(&folder).ok();
+    }
+
+    #[test]
+    fn test_setup_writes_files_and_commits_once() {
+        let folder = std::env::temp_dir().join(format!("prob-repo-{}", Uuid::new_v4()));
+        let queries = designed_project(&folder);
+        let target = folder.join("starter");
+
+        let response = generate_repository(&queries, &request(&target, false)).unwrap();
+        for path in ["README.md", "docs/problem.md", "docs/architecture.md", "schema/schema.postgres.sql", "features/01-sign-up.feature"] {
+            assert!(target.join(path).is_file(), "{} was not written", path);
+        }
+
+        let has_git = Command::new("git").arg("--version").output().is_ok_and(|o| o.status.success());
+        if has_git {
+            assert!(response.git_initialized, "{:?}", response.warnings);
+            assert_eq!(git(&target, &["rev-parse", "HEAD"]).ok(), response.commit);
+            assert_eq!(git(&target, &["log", "--format=%s"]).unwrap(), "Initial commit: scaffold Demo App");
+        }
+
+        let refused = generate_repository(&queries, &request(&target, false)).unwrap_err();
+        assert!(refused.contains("is not empty"));
+
+        fs::remove_dir_all(&folder).ok();
+    }
+}
@@ -0,0 +1,863 @@
+use crate::db::{models::*, queries::Queries, DbPool};
+use crate::commands::export_history::{new_export, ExportKind, Exported};
+use crate::commands::workspace::resolve_project_folder;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use tauri::State;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportSchemaRequest {
+    pub project_id: String,
+    pub format: String, // "sqlite", "postgres", "mermaid", "prisma" or "diesel"
+    pub write_to_workspace: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportSchemaResponse {
+    pub format: String,
+    pub filename: String,
+    pub content: String,
+    pub file_path: Option<String>,
+    pub issues: Vec<SchemaIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SchemaIssue {
+    pub severity: String, // "error" or "warning"
+    pub table: String,
+    pub column: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SchemaFormat {
+    Sqlite,
+    Postgres,
+    Mermaid,
+    Prisma,
+    Diesel,
+}
+
+impl SchemaFormat {
+    pub fn parse(format: &str) -> Result<Self, String> {
+        match format.to_lowercase().as_str() {
+            "sqlite" => Ok(SchemaFormat::Sqlite),
+            "postgres" | "postgresql" => Ok(SchemaFormat::Postgres),
+            "mermaid" => Ok(SchemaFormat::Mermaid),
+            "prisma" => Ok(SchemaFormat::Prisma),
+            "diesel" => Ok(SchemaFormat::Diesel),
+            other => Err(format!("Unsupported schema format: {}", other)),
+        }
+    }
+
+    pub fn filename(&self) -> &'static str {
+        match self {
+            SchemaFormat::Sqlite => "schema.sqlite.sql",
+            SchemaFormat::Postgres => "schema.postgres.sql",
+            SchemaFormat::Mermaid => "schema.mmd",
+            SchemaFormat::Prisma => "schema.prisma",
+            SchemaFormat::Diesel => "schema.rs",
+        }
+    }
+}
+
+/// Portable view of a designed column type, parsed from `DatabaseColumn.data_type`
+#[derive(Debug, Clone, PartialEq)]
+enum ColumnType {
+    Uuid,
+    Varchar(Option<u32>),
+    Text,
+    Integer,
+    BigInt,
+    Float,
+    Decimal(Option<(u32, u32)>),
+    Boolean,
+    Timestamp,
+    Date,
+    Json,
+    Other(String),
+}
+
+type TableSpec = (DatabaseTable, Vec<DatabaseColumn>);
+
+const RESERVED_WORDS: [&str; 14] = [
+    "user", "order", "group", "table", "select", "where", "from", "references",
+    "check", "default", "index", "key", "primary", "column",
+];
+
+const RUST_KEYWORDS: [&str; 12] = [
+    "type", "struct", "enum", "fn", "impl", "mod", "match", "ref", "self", "use", "where", "move",
+];
+
+// Commands
+
+/// Check primary and foreign keys of the designed schema without exporting it
+#[tauri::command]
+pub async fn validate_database_schema(
+    project_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<Vec<SchemaIssue>, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    let tables = queries
+        .get_database_tables(&project_id)
+        .map_err(|e| e.to_string())?;
+    let relationships = queries
+        .get_database_relationships(&project_id)
+        .map_err(|e| e.to_string())?;
+
+    Ok(validate_schema(&tables, &relationships))
+}
+
+/// Render the designed schema as DDL, an ER diagram or an ORM schema file
+#[tauri::command]
+pub async fn export_database_schema(
+    request: ExportSchemaRequest,
+    pool: State<'_, DbPool>,
+) -> Result<ExportSchemaResponse, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    Ok(export_database_schema_with_record(&queries, request)?.output)
+}
+
+pub fn export_database_schema_with_record(queries: &Queries, request: ExportSchemaRequest) -> Result<Exported<ExportSchemaResponse>, String> {
+    let format = SchemaFormat::parse(&request.format)?;
+
+    let tables = queries
+        .get_database_tables(&request.project_id)
+        .map_err(|e| e.to_string())?;
+    let relationships = queries
+        .get_database_relationships(&request.project_id)
+        .map_err(|e| e.to_string())?;
+
+    if tables.is_empty() {
+        return Err("No database tables designed for this project yet".to_string());
+    }
+
+    let issues = validate_schema(&tables, &relationships);
+    let errors: Vec<&SchemaIssue> = issues.iter().filter(|i| i.severity == "error").collect();
+    if !errors.is_empty() {
+        let summary = errors.iter()
+            .map(|issue| issue.message.clone())
+            .collect::<Vec<_>>()
+            .join("; ");
+        return Err(format!("Schema has {} error(s): {}", errors.len(), summary));
+    }
+
+    let content = render_schema(format, &tables, &relationships);
+
+    let file_path = if request.write_to_workspace.unwrap_or(false) {
+        let schema_dir = resolve_project_folder(queries, &request.project_id)
+            .map_err(|e| e.to_string())?
+            .join("schema");
+        fs::create_dir_all(&schema_dir)
+            .map_err(|e| format!("Failed to create schema directory: {}", e))?;
+
+        let path = schema_dir.join(format.filename());
+        fs::write(&path, &content)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Some(path.to_string_lossy().to_string())
+    } else {
+        None
+    };
+
+    let record = ExportRecord {
+        file_path: file_path.clone(),
+        file_size_bytes: Some(content.len() as i64),
+        included_sections: vec!["database".to_string()],
+        ..new_export(&request.project_id, ExportKind::DatabaseSchema, &request.format.to_lowercase(), &request)
+            .map_err(|e| e.to_string())?
+    };
+    queries.record_export(&record).map_err(|e| e.to_string())?;
+
+    let output = ExportSchemaResponse {
+        format: request.format,
+        filename: format.filename().to_string(),
+        content,
+        file_path,
+        issues,
+    };
+    Ok(Exported { output, record })
+}
+
+// Validation
+
+/// Report missing primary keys, dangling `references_table` values and
+/// relationships that point at tables that were never designed.
+pub fn validate_schema(tables: &[TableSpec], relationships: &[DatabaseRelationship]) -> Vec<SchemaIssue> {
+    let mut issues = Vec::new();
+    let mut seen_tables = HashSet::new();
+
+    for (table, columns) in tables {
+        if !seen_tables.insert(table.table_name.as_str()) {
+            issues.push(issue("error", &table.table_name, None, format!("Table '{}' is defined more than once", table.table_name)));
+        }
+
+        if columns.is_empty() {
+            issues.push(issue("error", &table.table_name, None, format!("Table '{}' has no columns", table.table_name)));
+            continue;
+        }
+
+        if !columns.iter().any(|c| c.is_primary_key) {
+            issues.push(issue("error", &table.table_name, None, format!("Table '{}' has no primary key", table.table_name)));
+        }
+
+        let mut seen_columns = HashSet::new();
+        for column in columns {
+            if !seen_columns.insert(column.column_name.as_str()) {
+                issues.push(issue("error", &table.table_name, Some(&column.column_name),
+                    format!("Column '{}.{}' is defined more than once", table.table_name, column.column_name)));
+            }
+
+            if let ColumnType::Other(raw) = parse_column_type(&column.data_type) {
+                issues.push(issue("warning", &table.table_name, Some(&column.column_name),
+                    format!("Column '{}.{}' has unrecognised type '{}'; it is passed through as-is", table.table_name, column.column_name, raw)));
+            }
+
+            match (&column.references_table, column.is_foreign_key) {
+                (None, true) => issues.push(issue("error", &table.table_name, Some(&column.column_name),
+                    format!("Foreign key '{}.{}' does not name the table it references", table.table_name, column.column_name))),
+                (Some(reference), is_foreign_key) => {
+                    let (ref_table, ref_column) = split_reference(reference);
+                    match tables.iter().find(|(t, _)| t.table_name == ref_table) {
+                        None => issues.push(issue("error", &table.table_name, Some(&column.column_name),
+                            format!("Foreign key '{}.{}' references missing table '{}'", table.table_name, column.column_name, ref_table))),
+                        Some((_, ref_columns)) => {
+                            let target = ref_column.map(|c| c.to_string())
+                                .or_else(|| single_primary_key(ref_columns).map(|c| c.column_name.clone()));
+                            match target {
+                                None => issues.push(issue("error", &table.table_name, Some(&column.column_name),
+                                    format!("Foreign key '{}.{}' references '{}', which has no single-column primary key", table.table_name, column.column_name, ref_table))),
+                                Some(target) if !ref_columns.iter().any(|c| c.column_name == target) =>
+                                    issues.push(issue("error", &table.table_name, Some(&column.column_name),
+                                        format!("Foreign key '{}.{}' references missing column '{}.{}'", table.table_name, column.column_name, ref_table, target))),
+                                Some(_) => {}
+                            }
+                        }
+                    }
+                    if !is_foreign_key {
+                        issues.push(issue("warning", &table.table_name, Some(&column.column_name),
+                            format!("Column '{}.{}' references '{}' but is not flagged as a foreign key", table.table_name, column.column_name, ref_table)));
+                    }
+                }
+                (None, false) => {}
+            }
+        }
+    }
+
+    for relationship in relationships {
+        for end in [&relationship.from_table, &relationship.to_table] {
+            if !tables.iter().any(|(t, _)| &t.table_name == end) {
+                issues.push(issue("error", end, None, format!(
+                    "Relationship {} -> {} references missing table '{}'",
+                    relationship.from_table, relationship.to_table, end
+                )));
+            }
+        }
+    }
+
+    issues
+}
+
+fn issue(severity: &str, table: &str, column: Option<&str>, message: String) -> SchemaIssue {
+    SchemaIssue {
+        severity: severity.to_string(),
+        table: table.to_string(),
+        column: column.map(|c| c.to_string()),
+        message,
+    }
+}
+
+// Rendering
+
+pub fn render_schema(format: SchemaFormat, tables: &[TableSpec], relationships: &[DatabaseRelationship]) -> String {
+    match format {
+        SchemaFormat::Sqlite | SchemaFormat::Postgres => render_ddl(format, tables),
+        SchemaFormat::Mermaid => render_mermaid(tables, relationships),
+        SchemaFormat::Prisma => render_prisma(tables),
+        SchemaFormat::Diesel => render_diesel(tables),
+    }
+}
+
+fn render_ddl(format: SchemaFormat, tables: &[TableSpec]) -> String {
+    let dialect = if format == SchemaFormat::Sqlite { "SQLite" } else { "PostgreSQL" };
+    let mut out = format!("-- {} schema generated by prob\n", dialect);
+    if format == SchemaFormat::Sqlite {
+        out.push_str("PRAGMA foreign_keys = ON;\n");
+    }
+
+    for (table, columns) in order_by_dependencies(tables) {
+        let primary_keys: Vec<&DatabaseColumn> = columns.iter().filter(|c| c.is_primary_key).collect();
+        let mut lines = Vec::new();
+
+        for column in columns {
+            let mut line = format!("    {} {}", quote_ident(&column.column_name), ddl_type(format, &column.data_type));
+            if column.is_primary_key && primary_keys.len() == 1 {
+                line.push_str(" PRIMARY KEY");
+            }
+            for constraint in &column.constraints {
+                if let Some(constraint) = ddl_constraint(format, constraint) {
+                    line.push(' ');
+                    line.push_str(&constraint);
+                }
+            }
+            lines.push(line);
+        }
+
+        if primary_keys.len() > 1 {
+            let names: Vec<String> = primary_keys.iter().map(|c| quote_ident(&c.column_name)).collect();
+            lines.push(format!("    PRIMARY KEY ({})", names.join(", ")));
+        }
+
+        for column in columns {
+            if let Some((ref_table, ref_column)) = resolve_reference(column, tables) {
+                lines.push(format!(
+                    "    FOREIGN KEY ({}) REFERENCES {}({})",
+                    quote_ident(&column.column_name),
+                    quote_ident(&ref_table),
+                    quote_ident(&ref_column)
+                ));
+            }
+        }
+
+        out.push_str(&format!("\nCREATE TABLE IF NOT EXISTS {} (\n{}\n);\n", quote_ident(&table.table_name), lines.join(",\n")));
+    }
+
+    out
+}
+
+fn ddl_type(format: SchemaFormat, data_type: &str) -> String {
+    let sqlite = format == SchemaFormat::Sqlite;
+    match parse_column_type(data_type) {
+        ColumnType::Uuid => if sqlite { "TEXT".into() } else { "UUID".into() },
+        ColumnType::Varchar(Some(len)) if !sqlite => format!("VARCHAR({})", len),
+        ColumnType::Varchar(_) | ColumnType::Text => "TEXT".into(),
+        ColumnType::Integer => "INTEGER".into(),
+        ColumnType::BigInt => if sqlite { "INTEGER".into() } else { "BIGINT".into() },
+        ColumnType::Float => if sqlite { "REAL".into() } else { "DOUBLE PRECISION".into() },
+        ColumnType::Decimal(Some((precision, scale))) if !sqlite => format!("NUMERIC({}, {})", precision, scale),
+        ColumnType::Decimal(_) => "NUMERIC".into(),
+        ColumnType::Boolean => if sqlite { "INTEGER".into() } else { "BOOLEAN".into() },
+        ColumnType::Timestamp => if sqlite { "TEXT".into() } else { "TIMESTAMPTZ".into() },
+        ColumnType::Date => if sqlite { "TEXT".into() } else { "DATE".into() },
+        ColumnType::Json => if sqlite { "TEXT".into() } else { "JSONB".into() },
+        ColumnType::Other(raw) => raw,
+    }
+}
+
+/// Translate a stored constraint for the target dialect; returns None when it has no equivalent
+fn ddl_constraint(format: SchemaFormat, constraint: &str) -> Option<String> {
+    let upper = constraint.trim().to_uppercase();
+    if upper == "PRIMARY KEY" {
+        return None;
+    }
+    if format == SchemaFormat::Sqlite {
+        if upper.contains("GEN_RANDOM_UUID") || upper.contains("UUID_GENERATE") {
+            return None;
+        }
+        if upper == "DEFAULT NOW()" {
+            return Some("DEFAULT CURRENT_TIMESTAMP".to_string());
+        }
+    }
+    Some(constraint.trim().to_string())
+}
+
+fn render_mermaid(tables: &[TableSpec], relationships: &[DatabaseRelationship]) -> String {
+    let mut out = String::from("erDiagram\n");
+
+    for (table, columns) in tables {
+        out.push_str(&format!("    {} {{\n", mermaid_ident(&table.table_name)));
+        for column in columns {
+            let mut keys = Vec::new();
+            if column.is_primary_key {
+                keys.push("PK");
+            }
+            if column.is_foreign_key || column.references_table.is_some() {
+                keys.push("FK");
+            }
+            let base_type = column.data_type.split('(').next().unwrap_or("").trim();
+            out.push_str(&format!(
+                "        {} {}{}\n",
+                mermaid_ident(base_type),
+                mermaid_ident(&column.column_name),
+                if keys.is_empty() { String::new() } else { format!(" {}", keys.join(",")) }
+            ));
+        }
+        out.push_str("    }\n");
+    }
+
+    // Explicit relationships first, then any foreign key they do not already cover
+    let mut drawn = HashSet::new();
+    for relationship in relationships {
+        out.push_str(&format!(
+            "    {} {} {} : \"{}\"\n",
+            mermaid_ident(&relationship.from_table),
+            mermaid_cardinality(&relationship.relationship_type),
+            mermaid_ident(&relationship.to_table),
+            relationship.relationship_type
+        ));
+        drawn.insert((relationship.from_table.clone(), relationship.to_table.clone()));
+        drawn.insert((relationship.to_table.clone(), relationship.from_table.clone()));
+    }
+
+    for (table, columns) in tables {
+        for column in columns {
+            if let Some((ref_table, _)) = resolve_reference(column, tables) {
+                if drawn.insert((ref_table.clone(), table.table_name.clone())) {
+                    out.push_str(&format!(
+                        "    {} ||--o{{ {} : \"{}\"\n",
+                        mermaid_ident(&ref_table),
+                        mermaid_ident(&table.table_name),
+                        column.column_name
+                    ));
+                }
+            }
+        }
+    }
+
+    out
+}
+
+fn mermaid_cardinality(relationship_type: &str) -> &'static str {
+    match relationship_type.to_lowercase().replace('_', "-").as_str() {
+        "one-to-one" => "||--||",
+        "many-to-one" => "}o--||",
+        "many-to-many" => "}o--o{",
+        _ => "||--o{",
+    }
+}
+
+fn mermaid_ident(name: &str) -> String {
+    let ident: String = name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect();
+    if ident.is_empty() { "unknown".to_string() } else { ident }
+}
+
+fn render_prisma(tables: &[TableSpec]) -> String {
+    let mut out = String::from(
+        "// Prisma schema generated by prob\n\n\
+         generator client {\n  provider = \"prisma-client-js\"\n}\n\n\
+         datasource db {\n  provider = \"postgresql\"\n  url      = env(\"DATABASE_URL\")\n}\n"
+    );
+
+    // Back-relation fields each parent model needs: (parent, child, relation name)
+    let mut back_relations: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    for (table, columns) in tables {
+        for column in columns {
+            if let Some((ref_table, _)) = resolve_reference(column, tables) {
+                back_relations.entry(ref_table)
+                    .or_default()
+                    .push((table.table_name.clone(), relation_name(&table.table_name, &column.column_name)));
+            }
+        }
+    }
+
+    for (table, columns) in tables {
+        let mut fields: Vec<(String, String, String)> = Vec::new();
+        let mut used_names: HashSet<String> = columns.iter().map(|c| c.column_name.clone()).collect();
+        let primary_keys: Vec<&DatabaseColumn> = columns.iter().filter(|c| c.is_primary_key).collect();
+
+        for column in columns {
+            let optional = !column.is_primary_key && !is_not_null(column);
+            let (prisma_type, native) = prisma_type(&column.data_type);
+            let mut attributes = Vec::new();
+            if column.is_primary_key && primary_keys.len() == 1 {
+                attributes.push("@id".to_string());
+            }
+            if column.constraints.iter().any(|c| c.trim().eq_ignore_ascii_case("UNIQUE")) {
+                attributes.push("@unique".to_string());
+            }
+            if let Some(default) = column.constraints.iter().find_map(|c| prisma_default(c)) {
+                attributes.push(default);
+            }
+            if let Some(native) = native {
+                attributes.push(native.to_string());
+            }
+            fields.push((
+                column.column_name.clone(),
+                format!("{}{}", prisma_type, if optional { "?" } else { "" }),
+                attributes.join(" "),
+            ));
+        }
+
+        for column in columns {
+            if let Some((ref_table, ref_column)) = resolve_reference(column, tables) {
+                let base = column.column_name.strip_suffix("_id").unwrap_or(&ref_table).to_string();
+                let field_name = unique_name(&base, &mut used_names);
+                let optional = !is_not_null(column);
+                fields.push((
+                    field_name,
+                    format!("{}{}", ref_table, if optional { "?" } else { "" }),
+                    format!(
+                        "@relation(\"{}\", fields: [{}], references: [{}])",
+                        relation_name(&table.table_name, &column.column_name),
+                        column.column_name,
+                        ref_column
+                    ),
+                ));
+            }
+        }
+
+        if let Some(children) = back_relations.get(&table.table_name) {
+            for (child, relation) in children {
+                let field_name = unique_name(child, &mut used_names);
+                fields.push((field_name, format!("{}[]", child), format!("@relation(\"{}\")", relation)));
+            }
+        }
+
+        let name_width = fields.iter().map(|f| f.0.len()).max().unwrap_or(0);
+        let type_width = fields.iter().map(|f| f.1.len()).max().unwrap_or(0);
+
+        out.push_str(&format!("\nmodel {} {{\n", table.table_name));
+        for (name, field_type, attributes) in &fields {
+            let line = format!("  {:name_width$} {:type_width$} {}", name, field_type, attributes);
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        if primary_keys.len() > 1 {
+            let names: Vec<&str> = primary_keys.iter().map(|c| c.column_name.as_str()).collect();
+            out.push_str(&format!("\n  @@id([{}])\n", names.join(", ")));
+        }
+        out.push_str("}\n");
+    }
+
+    out
+}
+
+fn prisma_type(data_type: &str) -> (String, Option<&'static str>) {
+    match parse_column_type(data_type) {
+        ColumnType::Uuid => ("String".into(), Some("@db.Uuid")),
+        ColumnType::Varchar(_) | ColumnType::Text => ("String".into(), None),
+        ColumnType::Integer => ("Int".into(), None),
+        ColumnType::BigInt => ("BigInt".into(), None),
+        ColumnType::Float => ("Float".into(), None),
+        ColumnType::Decimal(_) => ("Decimal".into(), None),
+        ColumnType::Boolean => ("Boolean".into(), None),
+        ColumnType::Timestamp => ("DateTime".into(), None),
+        ColumnType::Date => ("DateTime".into(), Some("@db.Date")),
+        ColumnType::Json => ("Json".into(), None),
+        ColumnType::Other(raw) => (format!("Unsupported(\"{}\")", raw), None),
+    }
+}
+
+fn prisma_default(constraint: &str) -> Option<String> {
+    let value = constraint.trim().strip_prefix("DEFAULT ")
+        .or_else(|| constraint.trim().strip_prefix("default "))?
+        .trim();
+    let upper = value.to_uppercase();
+
+    if upper == "CURRENT_TIMESTAMP" || upper == "NOW()" {
+        Some("@default(now())".to_string())
+    } else if upper.contains("UUID") {
+        Some("@default(uuid())".to_string())
+    } else if upper == "TRUE" || upper == "FALSE" {
+        Some(format!("@default({})", value.to_lowercase()))
+    } else if value.parse::<f64>().is_ok() {
+        Some(format!("@default({})", value))
+    } else if value.starts_with('\'') && value.ends_with('\'') && value.len() >= 2 {
+        Some(format!("@default(\"{}\")", &value[1..value.len() - 1]))
+    } else {
+        None
+    }
+}
+
+fn relation_name(table: &str, column: &str) -> String {
+    format!("{}_{}", table, column)
+}
+
+fn unique_name(base: &str, used: &mut HashSet<String>) -> String {
+    let mut name = base.to_string();
+    let mut suffix = 2;
+    while used.contains(&name) {
+        name = format!("{}_{}", base, suffix);
+        suffix += 1;
+    }
+    used.insert(name.clone());
+    name
+}
+
+fn render_diesel(tables: &[TableSpec]) -> String {
+    let mut out = String::from("// @generated by prob schema export (PostgreSQL backend)\n");
+
+    for (table, columns) in tables {
+        let primary_keys: Vec<String> = columns.iter()
+            .filter(|c| c.is_primary_key)
+            .map(|c| diesel_ident(&c.column_name))
+            .collect();
+
+        out.push_str(&format!("\ndiesel::table! {{\n    {} ({}) {{\n", table.table_name, primary_keys.join(", ")));
+        for column in columns {
+            let sql_type = diesel_type(&column.data_type);
+            let sql_type = if column.is_primary_key || is_not_null(column) {
+                sql_type
+            } else {
+                format!("Nullable<{}>", sql_type)
+            };
+            if diesel_ident(&column.column_name) != column.column_name {
+                out.push_str(&format!("        #[sql_name = \"{}\"]\n", column.column_name));
+            }
+            out.push_str(&format!("        {} -> {},\n", diesel_ident(&column.column_name), sql_type));
+        }
+        out.push_str("    }\n}\n");
+    }
+
+    // Diesel allows one joinable! per table pair and none for self references
+    let mut joined = HashSet::new();
+    let mut joinables = Vec::new();
+    for (table, columns) in tables {
+        for column in columns {
+            if let Some((ref_table, _)) = resolve_reference(column, tables) {
+                if ref_table != table.table_name && joined.insert((table.table_name.clone(), ref_table.clone())) {
+                    joinables.push(format!(
+                        "diesel::joinable!({} -> {} ({}));",
+                        table.table_name, ref_table, diesel_ident(&column.column_name)
+                    ));
+                }
+            }
+        }
+    }
+    if !joinables.is_empty() {
+        out.push('\n');
+        out.push_str(&joinables.join("\n"));
+        out.push('\n');
+    }
+
+    let mut names: Vec<&str> = tables.iter().map(|(t, _)| t.table_name.as_str()).collect();
+    names.sort();
+    out.push_str("\ndiesel::allow_tables_to_appear_in_same_query!(\n");
+    for name in names {
+        out.push_str(&format!("    {},\n", name));
+    }
+    out.push_str(");\n");
+
+    out
+}
+
+fn diesel_type(data_type: &str) -> String {
+    match parse_column_type(data_type) {
+        ColumnType::Uuid => "Uuid",
+        ColumnType::Varchar(_) => "Varchar",
+        ColumnType::Text | ColumnType::Other(_) => "Text",
+        ColumnType::Integer => "Int4",
+        ColumnType::BigInt => "Int8",
+        ColumnType::Float => "Float8",
+        ColumnType::Decimal(_) => "Numeric",
+        ColumnType::Boolean => "Bool",
+        ColumnType::Timestamp => "Timestamptz",
+        ColumnType::Date => "Date",
+        ColumnType::Json => "Jsonb",
+    }.to_string()
+}
+
+fn diesel_ident(name: &str) -> String {
+    if RUST_KEYWORDS.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.to_string()
+    }
+}
+
+// Shared helpers
+
+fn parse_column_type(data_type: &str) -> ColumnType {
+    let normalized = data_type.trim().to_uppercase();
+    let (base, args) = match normalized.find('(') {
+        Some(start) => (
+            normalized[..start].trim().to_string(),
+            normalized[start + 1..].trim_end_matches(')').split(',')
+                .filter_map(|arg| arg.trim().parse::<u32>().ok())
+                .collect::<Vec<_>>(),
+        ),
+        None => (normalized.clone(), Vec::new()),
+    };
+
+    match base.as_str() {
+        "UUID" | "GUID" => ColumnType::Uuid,
+        "VARCHAR" | "CHARACTER VARYING" | "CHAR" | "STRING" => ColumnType::Varchar(args.first().copied()),
+        "TEXT" | "CLOB" => ColumnType::Text,
+        "INT" | "INTEGER" | "INT4" | "SMALLINT" | "SERIAL" => ColumnType::Integer,
+        "BIGINT" | "INT8" | "BIGSERIAL" => ColumnType::BigInt,
+        "FLOAT" | "DOUBLE" | "DOUBLE PRECISION" | "REAL" | "FLOAT8" => ColumnType::Float,
+        "DECIMAL" | "NUMERIC" | "MONEY" => ColumnType::Decimal(match args.as_slice() {
+            [precision, scale] => Some((*precision, *scale)),
+            [precision] => Some((*precision, 0)),
+            _ => None,
+        }),
+        "BOOLEAN" | "BOOL" => ColumnType::Boolean,
+        "TIMESTAMP" | "TIMESTAMPTZ" | "DATETIME" | "TIMESTAMP WITH TIME ZONE" => ColumnType::Timestamp,
+        "DATE" => ColumnType::Date,
+        "JSON" | "JSONB" => ColumnType::Json,
+        _ => ColumnType::Other(data_type.trim().to_string()),
+    }
+}
+
+/// Accepts "users", "users.id" and "users(id)"
+fn split_reference(reference: &str) -> (String, Option<String>) {
+    let reference = reference.trim();
+    if let Some(start) = reference.find('(') {
+        let column = reference[start + 1..].trim_end_matches(')').trim().to_string();
+        return (reference[..start].trim().to_string(), Some(column).filter(|c| !c.is_empty()));
+    }
+    match reference.split_once('.') {
+        Some((table, column)) => (table.trim().to_string(), Some(column.trim().to_string())),
+        None => (reference.to_string(), None),
+    }
+}
+
+fn single_primary_key(columns: &[DatabaseColumn]) -> Option<&DatabaseColumn> {
+    let mut keys = columns.iter().filter(|c| c.is_primary_key);
+    match (keys.next(), keys.next()) {
+        (Some(key), None) => Some(key),
+        _ => None,
+    }
+}
+
+/// Resolve a column's reference to (table, column), or None if it has none or it dangles
+fn resolve_reference(column: &DatabaseColumn, tables: &[TableSpec]) -> Option<(String, String)> {
+    let (ref_table, ref_column) = split_reference(column.references_table.as_ref()?);
+    let (_, ref_columns) = tables.iter().find(|(t, _)| t.table_name == ref_table)?;
+    let ref_column = match ref_column {
+        Some(column) => column,
+        None => single_primary_key(ref_columns)?.column_name.clone(),
+    };
+    Some((ref_table, ref_column))
+}
+
+fn is_not_null(column: &DatabaseColumn) -> bool {
+    column.constraints.iter().any(|c| c.trim().eq_ignore_ascii_case("NOT NULL"))
+}
+
+fn quote_ident(name: &str) -> String {
+    let simple = name.chars().next().map(|c| c.is_ascii_lowercase() || c == '_').unwrap_or(false)
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if simple && !RESERVED_WORDS.contains(&name) {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+}
+
+/// Order tables so referenced tables are created first; cycles keep their original order
+fn order_by_dependencies(tables: &[TableSpec]) -> Vec<&TableSpec> {
+    let mut ordered: Vec<&TableSpec> = Vec::new();
+    let mut placed: HashSet<&str> = HashSet::new();
+    let mut remaining: Vec<&TableSpec> = tables.iter().collect();
+
+    while !remaining.is_empty() {
+        let ready = remaining.iter().position(|(table, columns)| {
+            columns.iter().all(|column| match resolve_reference(column, tables) {
+                Some((ref_table, _)) => ref_table == table.table_name || placed.contains(ref_table.as_str()),
+                None => true,
+            })
+        });
+
+        let next = remaining.remove(ready.unwrap_or(0));
+        placed.insert(next.0.table_name.as_str());
+        ordered.push(next);
+    }
+
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn table(name: &str, columns: Vec<DatabaseColumn>) -> TableSpec {
+        (
+            DatabaseTable {
+                id: name.to_string(),
+                project_id: "project-1".to_string(),
+                table_name: name.to_string(),
+                created_at: Utc::now(),
+            },
+            columns,
+        )
+    }
+
+    fn column(name: &str, data_type: &str, pk: bool, references: Option<&str>, constraints: &[&str]) -> DatabaseColumn {
+        DatabaseColumn {
+            id: name.to_string(),
+            table_id: String::new(),
+            column_name: name.to_string(),
+            data_type: data_type.to_string(),
+            is_primary_key: pk,
+            is_foreign_key: references.is_some(),
+            references_table: references.map(|r| r.to_string()),
+            constraints: constraints.iter().map(|c| c.to_string()).collect(),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn sample_schema() -> Vec<TableSpec> {
+        vec![
+            table("orders", vec![
+                column("id", "UUID", true, None, &["NOT NULL", "DEFAULT gen_random_uuid()"]),
+                column("user_id", "UUID", false, Some("users"), &["NOT NULL"]),
+                column("total", "DECIMAL(10,2)", false, None, &[]),
+            ]),
+            table("users", vec![
+                column("id", "UUID", true, None, &["NOT NULL"]),
+                column("email", "VARCHAR(255)", false, None, &["NOT NULL", "UNIQUE"]),
+            ]),
+        ]
+    }
+
+    #[test]
+    fn test_validation_reports_dangling_references_and_missing_keys() {
+        let tables = vec![
+            table("orders", vec![
+                column("id", "UUID", true, None, &[]),
+                column("customer_id", "UUID", false, Some("customers"), &[]),
+            ]),
+            table("audit_log", vec![column("message", "TEXT", false, None, &[])]),
+        ];
+
+        let issues = validate_schema(&tables, &[]);
+        let errors: Vec<&str> = issues.iter()
+            .filter(|i| i.severity == "error")
+            .map(|i| i.message.as_str())
+            .collect();
+
+        assert_eq!(errors, vec![
+            "Foreign key 'orders.customer_id' references missing table 'customers'",
+            "Table 'audit_log' has no primary key",
+        ]);
+    }
+
+    #[test]
+    fn test_ddl_creates_referenced_tables_first() {
+        let tables = sample_schema();
+        assert!(validate_schema(&tables, &[]).is_empty());
+
+        let postgres = render_schema(SchemaFormat::Postgres, &tables, &[]);
+        assert!(postgres.find("CREATE TABLE IF NOT EXISTS users").unwrap()
+            < postgres.find("CREATE TABLE IF NOT EXISTS orders").unwrap());
+        assert!(postgres.contains("id UUID PRIMARY KEY NOT NULL DEFAULT gen_random_uuid()"));
+        assert!(postgres.contains("total NUMERIC(10, 2)"));
+        assert!(postgres.contains("FOREIGN KEY (user_id) REFERENCES users(id)"));
+
+        let sqlite = render_schema(SchemaFormat::Sqlite, &tables, &[]);
+        assert!(sqlite.contains("id TEXT PRIMARY KEY NOT NULL,"));
+        assert!(!sqlite.contains("gen_random_uuid"));
+    }
+
+    #[test]
+    fn test_orm_schemas_include_relations() {
+        let tables = sample_schema();
+
+        let prisma = render_schema(SchemaFormat::Prisma, &tables, &[]);
+        assert!(prisma.contains("@relation(\"orders_user_id\", fields: [user_id], references: [id])"));
+        assert!(prisma.contains("orders[] @relation(\"orders_user_id\")"));
+
+        let diesel = render_schema(SchemaFormat::Diesel, &tables, &[]);
+        assert!(diesel.contains("total -> Nullable<Numeric>,"));
+        assert!(diesel.contains("diesel::joinable!(orders -> users (user_id));"));
+
+        let mermaid = render_schema(SchemaFormat::Mermaid, &tables, &[]);
+        assert!(mermaid.contains("users ||--o{ orders : \"user_id\""));
+    }
+}
@@ -1,95 +1,405 @@
-use portable_pty::{native_pty_system, CommandBuilder, PtySize};
-use std::collections::HashMap;
+use crate::commands::filesystem::{default_sandboxed_dir, resolve_sandboxed_dir};
+use crate::commands::terminal_profiles::{find_profile, record_command_result, TerminalCommandResult, TerminalProfile};
+use crate::commands::terminal_recording::{recording_path, CastHeader, Recording};
+use crate::db::{queries::Queries, DbPool};
+use portable_pty::{native_pty_system, CommandBuilder, MasterPty, PtySize};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 use std::io::{Read, Write};
 use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, State};
 use std::thread;
+use std::time::Duration;
+use tokio::sync::oneshot;
 
 // Store active terminal sessions
 lazy_static::lazy_static! {
     static ref TERMINAL_SESSIONS: Arc<Mutex<HashMap<String, TerminalSession>>> = Arc::new(Mutex::new(HashMap::new()));
 }
 
+/// Bytes of output kept per session for replay on attach
+const SCROLLBACK_BYTES: usize = 256 * 1024;
+const DEFAULT_COMMAND_TIMEOUT_SECS: u64 = 600;
+
 struct TerminalSession {
     writer: Box<dyn Write + Send>,
-    _child: Box<dyn portable_pty::Child + Send>,
+    // Kept so the PTY can be resized; dropping it closes the terminal
+    master: Box<dyn MasterPty + Send>,
+    child: Box<dyn portable_pty::Child + Send + Sync>,
+    scrollback: Scrollback,
+    // Set while the session is being recorded to an asciicast file
+    recording: Option<Recording>,
+    // Set for one-shot commands waiting on the exit status and output
+    exit_notify: Option<oneshot::Sender<(TerminalExit, String)>>,
+    cwd: String,
+    shell: String,
+    cols: u16,
+    rows: u16,
+    created_at: DateTime<Utc>,
+}
+
+/// Ring buffer of recent output. `offset` counts every byte ever written, so a client
+/// can tell which live chunks are already covered by a replay.
+struct Scrollback {
+    bytes: VecDeque<u8>,
+    capacity: usize,
+    offset: u64,
+}
+
+impl Scrollback {
+    fn new(capacity: usize) -> Self {
+        Self { bytes: VecDeque::with_capacity(capacity.min(16 * 1024)), capacity, offset: 0 }
+    }
+
+    fn push(&mut self, chunk: &[u8]) -> u64 {
+        self.offset += chunk.len() as u64;
+        let chunk = &chunk[chunk.len().saturating_sub(self.capacity)..];
+        let overflow = (self.bytes.len() + chunk.len()).saturating_sub(self.capacity);
+        self.bytes.drain(..overflow);
+        self.bytes.extend(chunk);
+        self.offset
+    }
+
+    fn contents(&self) -> String {
+        let (front, back) = self.bytes.as_slices();
+        let mut bytes = Vec::with_capacity(self.bytes.len());
+        bytes.extend_from_slice(front);
+        bytes.extend_from_slice(back);
+        // Trimming can cut a UTF-8 sequence in half; skip its continuation bytes
+        let start = bytes.iter().take(3).take_while(|b| (**b & 0xC0) == 0x80).count();
+        String::from_utf8_lossy(&bytes[start..]).into_owned()
+    }
+}
+
+/// Decodes output read in chunks, holding back a character split across two reads
+#[derive(Default)]
+struct Utf8Decoder {
+    pending: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    fn decode(&mut self, chunk: &[u8]) -> String {
+        self.pending.extend_from_slice(chunk);
+        let complete = match std::str::from_utf8(&self.pending) {
+            // Only an incomplete sequence at the end waits; invalid bytes are replaced below
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            _ => self.pending.len(),
+        };
+        let tail = self.pending.split_off(complete);
+        let data = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending = tail;
+        data
+    }
+}
+
+/// Payload of `terminal-output-{session_id}`. `offset` is the scrollback offset after this chunk.
+#[derive(Debug, Clone, Serialize)]
+pub struct TerminalOutput {
+    pub data: String,
+    pub offset: u64,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct TerminalSessionInfo {
+    pub session_id: String,
+    pub cwd: String,
+    pub shell: String,
+    pub pid: Option<u32>,
+    pub cols: u16,
+    pub rows: u16,
+    pub running: bool,
+    pub recording: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Scrollback to replay on attach; live chunks with `offset` at or below this one are already included
+#[derive(Debug, Clone, Serialize)]
+pub struct TerminalAttach {
+    pub session: TerminalSessionInfo,
+    pub scrollback: String,
+    pub offset: u64,
+}
+
+/// Payload of `terminal-exit-{session_id}`
+#[derive(Debug, Clone, Serialize)]
+pub struct TerminalExit {
+    pub session_id: String,
+    pub exit_code: Option<u32>,
+    pub success: bool,
+}
+
+/// Per-session event name, so concurrent terminals never see each other's output
+pub fn terminal_event(kind: &str, session_id: &str) -> String {
+    format!("terminal-{}-{}", kind, session_id)
+}
+
+/// Start an interactive shell. With a `profile`, its shell, arguments, environment, size and
+/// startup command are read from the project's settings. Without a `cwd` the shell starts in
+/// the project's folder, or the first workspace folder.
 #[tauri::command]
-pub async fn start_terminal_session(app: AppHandle, cwd: String) -> Result<String, String> {
+pub async fn start_terminal_session(
+    app: AppHandle,
+    cwd: Option<String>,
+    project_id: Option<String>,
+    profile: Option<String>,
+    pool: State<'_, DbPool>,
+) -> Result<String, String> {
+    let queries = Queries::new(pool.inner().clone());
+    let profile = resolve_profile(&queries, project_id.as_deref(), profile.as_deref())?;
+    let cwd = sandboxed_cwd(&queries, cwd.as_deref(), project_id.as_deref())?;
+    
     let session_id = uuid::Uuid::new_v4().to_string();
+    spawn_session(&app, &session_id, cwd, &profile, None, None)?;
+    
+    // Type the startup command once the shell is listening
+    if let Some(command) = profile.startup_command.as_ref().filter(|c| !c.trim().is_empty()) {
+        let mut sessions = TERMINAL_SESSIONS.lock().unwrap();
+        if let Some(session) = sessions.get_mut(&session_id) {
+            let line = format!("{}{}", command, if cfg!(target_os = "windows") { "\r\n" } else { "\n" });
+            session.writer.write_all(line.as_bytes())
+                .and_then(|_| session.writer.flush())
+                .map_err(|e| format!("Failed to send startup command: {}", e))?;
+        }
+    }
+    
+    Ok(session_id)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RunTerminalCommandRequest {
+    /// Defaults like `start_terminal_session`
+    pub cwd: Option<String>,
+    pub command: String,
+    pub project_id: Option<String>,
+    pub profile: Option<String>,
+    // Chosen by the caller so it can listen for output before the command starts
+    pub session_id: Option<String>,
+    pub timeout_secs: Option<u64>,
+}
+
+/// Run one command in a PTY and wait for it to exit. Output streams on the usual session
+/// events; with a `project_id` the result is also kept in the project's command history.
+#[tauri::command]
+pub async fn run_terminal_command(
+    app: AppHandle,
+    request: RunTerminalCommandRequest,
+    pool: State<'_, DbPool>,
+) -> Result<TerminalCommandResult, String> {
+    let queries = Queries::new(pool.inner().clone());
+    
+    if request.command.trim().is_empty() {
+        return Err("Command is required".to_string());
+    }
+    let profile = resolve_profile(&queries, request.project_id.as_deref(), request.profile.as_deref())?;
+    let cwd = sandboxed_cwd(&queries, request.cwd.as_deref(), request.project_id.as_deref())?;
+    let session_id = match &request.session_id {
+        Some(id) => {
+            uuid::Uuid::parse_str(id).map_err(|_| "Session id must be a UUID".to_string())?;
+            if TERMINAL_SESSIONS.lock().unwrap().contains_key(id) {
+                return Err("Terminal session id is already in use".to_string());
+            }
+            id.clone()
+        }
+        None => uuid::Uuid::new_v4().to_string(),
+    };
+    
+    let started_at = Utc::now();
+    let started = std::time::Instant::now();
+    let (exit_tx, mut exit_rx) = oneshot::channel();
+    spawn_session(&app, &session_id, cwd.clone(), &profile, Some(&request.command), Some(exit_tx))?;
+    
+    let limit = Duration::from_secs(request.timeout_secs.unwrap_or(DEFAULT_COMMAND_TIMEOUT_SECS));
+    let mut timed_out = false;
+    let finished = match tokio::time::timeout(limit, &mut exit_rx).await {
+        Ok(finished) => finished,
+        Err(_) => {
+            timed_out = true;
+            // Killing reports the exit through the same channel
+            let _ = kill_session(&app, &session_id);
+            exit_rx.await
+        }
+    };
+    let (exit, output) = finished.map_err(|_| "Command ended without an exit status".to_string())?;
+    
+    let result = TerminalCommandResult {
+        session_id,
+        command: request.command,
+        cwd,
+        profile: request.profile,
+        exit_code: exit.exit_code,
+        success: exit.success && !timed_out,
+        timed_out,
+        output,
+        started_at,
+        duration_ms: started.elapsed().as_millis() as u64,
+    };
+    if let Some(project_id) = &request.project_id {
+        record_command_result(&queries, project_id, &result).map_err(|e| e.to_string())?;
+    }
+    Ok(result)
+}
+
+/// Terminals only start inside a registered workspace folder
+fn sandboxed_cwd(queries: &Queries, cwd: Option<&str>, project_id: Option<&str>) -> Result<String, String> {
+    match cwd {
+        Some(cwd) => resolve_sandboxed_dir(queries, cwd),
+        None => default_sandboxed_dir(queries, project_id),
+    }
+    .map(|path| path.to_string_lossy().to_string())
+    .map_err(|e| e.to_string())
+}
+
+fn resolve_profile(queries: &Queries, project_id: Option<&str>, name: Option<&str>) -> Result<TerminalProfile, String> {
+    match (project_id, name) {
+        (Some(project_id), Some(name)) => find_profile(queries, project_id, name).map_err(|e| e.to_string()),
+        (None, Some(_)) => Err("A project is required to use a terminal profile".to_string()),
+        _ => Ok(TerminalProfile::default()),
+    }
+}
+
+/// Open a PTY, start the profile's shell in it (running `command` instead of an interactive
+/// shell when given) and stream its output until it exits.
+fn spawn_session(
+    app: &AppHandle,
+    session_id: &str,
+    cwd: String,
+    profile: &TerminalProfile,
+    command: Option<&str>,
+    exit_notify: Option<oneshot::Sender<(TerminalExit, String)>>,
+) -> Result<(), String> {
+    let (cols, rows) = profile.size();
     
     // Create a new PTY
     let pty_system = native_pty_system();
     
     let pty_pair = pty_system.openpty(PtySize {
-        rows: 24,
-        cols: 80,
+        rows,
+        cols,
         pixel_width: 0,
         pixel_height: 0,
     }).map_err(|e| format!("Failed to open PTY: {}", e))?;
     
-    // Get the shell command based on the OS
-    let shell = if cfg!(target_os = "windows") {
-        "cmd.exe".to_string()
-    } else {
-        std::env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string())
-    };
-    
-    let mut cmd = CommandBuilder::new(shell);
+    let shell = profile.shell();
+    let mut cmd = CommandBuilder::new(&shell);
+    cmd.args(&profile.args);
+    if let Some(command) = command {
+        cmd.arg(if cfg!(target_os = "windows") { "/C" } else { "-c" });
+        cmd.arg(command);
+    }
     cmd.cwd(&cwd);
+    for (key, value) in &profile.env {
+        cmd.env(key, value);
+    }
     
     // Spawn the shell process
     let child = pty_pair.slave.spawn_command(cmd)
         .map_err(|e| format!("Failed to spawn shell: {}", e))?;
+    // Only the child may hold the slave end, otherwise the reader never sees EOF
+    drop(pty_pair.slave);
     
     // Get reader and writer
-    let reader = pty_pair.master.try_clone_reader()
+    let master = pty_pair.master;
+    let reader = master.try_clone_reader()
         .map_err(|e| format!("Failed to clone reader: {}", e))?;
-    let writer = pty_pair.master.take_writer()
+    let writer = master.take_writer()
         .map_err(|e| format!("Failed to take writer: {}", e))?;
     
     // Store the session
     {
         let mut sessions = TERMINAL_SESSIONS.lock().unwrap();
-        sessions.insert(session_id.clone(), TerminalSession {
+        sessions.insert(session_id.to_string(), TerminalSession {
             writer,
-            _child: child,
+            master,
+            child,
+            scrollback: Scrollback::new(SCROLLBACK_BYTES),
+            recording: None,
+            exit_notify,
+            cwd,
+            shell,
+            cols,
+            rows,
+            created_at: Utc::now(),
         });
     }
     
     // Start reading output in a separate thread
     let app_handle = app.clone();
-    let session_id_clone = session_id.clone();
+    let session_id_clone = session_id.to_string();
     thread::spawn(move || {
         let mut reader = reader;
         let mut buffer = [0u8; 4096];
+        let mut decoder = Utf8Decoder::default();
         
         loop {
             match reader.read(&mut buffer) {
-                Ok(0) => {
-                    // EOF, terminal closed
-                    let _ = app_handle.emit("terminal-closed", &session_id_clone);
-                    break;
-                }
+                Ok(0) => break, // EOF, terminal closed
                 Ok(n) => {
-                    let output = String::from_utf8_lossy(&buffer[..n]);
-                    let _ = app_handle.emit("terminal-output", output.to_string());
+                    let data = decoder.decode(&buffer[..n]);
+                    let offset = {
+                        let mut sessions = TERMINAL_SESSIONS.lock().unwrap();
+                        match sessions.get_mut(&session_id_clone) {
+                            Some(session) => {
+                                record(session, |recording| recording.output(&data));
+                                session.scrollback.push(&buffer[..n])
+                            }
+                            None => break,
+                        }
+                    };
+                    // Bytes held back by the decoder arrive with the next chunk
+                    let output = TerminalOutput { data, offset: offset - decoder.pending.len() as u64 };
+                    let _ = app_handle.emit(&terminal_event("output", &session_id_clone), output);
                 }
                 Err(e) => {
-                    eprintln!("Error reading from terminal: {}", e);
-                    let _ = app_handle.emit("terminal-error", format!("Read error: {}", e));
+                    // Linux reports EIO on the master once the shell has exited
+                    if is_running(&session_id_clone) {
+                        eprintln!("Error reading from terminal: {}", e);
+                        let _ = app_handle.emit(&terminal_event("error", &session_id_clone), format!("Read error: {}", e));
+                    }
                     break;
                 }
             }
         }
         
-        // Clean up the session
-        let mut sessions = TERMINAL_SESSIONS.lock().unwrap();
-        sessions.remove(&session_id_clone);
+        // Clean up the session unless close_terminal_session already did
+        let session = TERMINAL_SESSIONS.lock().unwrap().remove(&session_id_clone);
+        if let Some(session) = session {
+            emit_exit(&app_handle, &session_id_clone, session, true);
+        }
+        let _ = app_handle.emit(&terminal_event("closed", &session_id_clone), &session_id_clone);
     });
     
-    Ok(session_id)
+    Ok(())
+}
+
+/// Sessions still alive in the backend, e.g. after the webview reloaded
+#[tauri::command]
+pub async fn list_terminal_sessions() -> Result<Vec<TerminalSessionInfo>, String> {
+    let mut sessions = TERMINAL_SESSIONS.lock().unwrap();
+    
+    let mut list: Vec<TerminalSessionInfo> = sessions
+        .iter_mut()
+        .map(|(id, session)| session_info(id, session))
+        .collect();
+    list.sort_by_key(|info| info.created_at);
+    Ok(list)
+}
+
+/// Replay a session's scrollback. Live output keeps arriving on `terminal-output-{session_id}`;
+/// the client should listen first and drop chunks whose offset is not past the returned one.
+#[tauri::command]
+pub async fn attach_terminal_session(session_id: String) -> Result<TerminalAttach, String> {
+    let mut sessions = TERMINAL_SESSIONS.lock().unwrap();
+    
+    if let Some(session) = sessions.get_mut(&session_id) {
+        Ok(TerminalAttach {
+            session: session_info(&session_id, session),
+            scrollback: session.scrollback.contents(),
+            offset: session.scrollback.offset,
+        })
+    } else {
+        Err("Terminal session not found".to_string())
+    }
 }
 
 #[tauri::command]
@@ -108,19 +418,190 @@ pub async fn write_to_terminal(session_id: String, data: String) -> Result<(), S
 }
 
 #[tauri::command]
-pub async fn close_terminal_session(session_id: String) -> Result<(), String> {
+pub async fn close_terminal_session(app: AppHandle, session_id: String) -> Result<(), String> {
+    kill_session(&app, &session_id)
+}
+
+#[tauri::command]
+pub async fn resize_terminal(session_id: String, cols: u16, rows: u16) -> Result<(), String> {
     let mut sessions = TERMINAL_SESSIONS.lock().unwrap();
     
-    if sessions.remove(&session_id).is_some() {
+    if let Some(session) = sessions.get_mut(&session_id) {
+        session.master.resize(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        }).map_err(|e| format!("Failed to resize terminal: {}", e))?;
+        session.cols = cols;
+        session.rows = rows;
+        record(session, |recording| recording.resize(cols, rows));
         Ok(())
     } else {
         Err("Terminal session not found".to_string())
     }
 }
 
+/// Record a session's output to `recordings/` in the project folder, in asciicast v2 format
+#[tauri::command]
+pub async fn start_terminal_recording(
+    session_id: String,
+    project_id: String,
+    title: Option<String>,
+    pool: State<'_, DbPool>,
+) -> Result<String, String> {
+    let queries = Queries::new(pool.inner().clone());
+    let path = recording_path(&queries, &project_id, &session_id).map_err(|e| e.to_string())?;
+    
+    let mut sessions = TERMINAL_SESSIONS.lock().unwrap();
+    let session = sessions.get_mut(&session_id)
+        .ok_or_else(|| "Terminal session not found".to_string())?;
+    if session.recording.is_some() {
+        return Err("Terminal session is already being recorded".to_string());
+    }
+    
+    let header = CastHeader {
+        version: 2,
+        width: session.cols,
+        height: session.rows,
+        timestamp: Some(Utc::now().timestamp()),
+        title,
+        env: Some(serde_json::json!({ "SHELL": session.shell, "TERM": "xterm-256color" })),
+    };
+    let recording = Recording::create(path, &header)
+        .map_err(|e| format!("Failed to start recording: {}", e))?;
+    let path = recording.path().to_string_lossy().to_string();
+    session.recording = Some(recording);
+    Ok(path)
+}
+
+/// Stop recording and return the path of the finished file, if the session was being recorded
 #[tauri::command]
-pub async fn resize_terminal(_session_id: String, _cols: u16, _rows: u16) -> Result<(), String> {
-    // Note: This would require storing the PTY master in the session
-    // For now, this is a placeholder
+pub async fn stop_terminal_recording(session_id: String) -> Result<Option<String>, String> {
+    let recording = {
+        let mut sessions = TERMINAL_SESSIONS.lock().unwrap();
+        let session = sessions.get_mut(&session_id)
+            .ok_or_else(|| "Terminal session not found".to_string())?;
+        session.recording.take()
+    };
+    
+    match recording {
+        Some(recording) => {
+            let path = recording.finish().map_err(|e| format!("Failed to save recording: {}", e))?;
+            Ok(Some(path.to_string_lossy().to_string()))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Append to the session's recording; a failing recording is dropped so the terminal keeps working
+fn record(session: &mut TerminalSession, write: impl FnOnce(&mut Recording) -> anyhow::Result<()>) {
+    if let Some(recording) = session.recording.as_mut() {
+        if let Err(e) = write(recording) {
+            eprintln!("Stopped recording {}: {}", recording.path().display(), e);
+            session.recording = None;
+        }
+    }
+}
+
+/// Stop a session's process. Waiters and listeners get the exit either way; when the kill
+/// fails the process is not waited for and the exit has no code.
+fn kill_session(app: &AppHandle, session_id: &str) -> Result<(), String> {
+    let session = TERMINAL_SESSIONS.lock().unwrap().remove(session_id);
+    let Some(mut session) = session else {
+        return Err("Terminal session not found".to_string());
+    };
+
+    if let Err(e) = session.child.kill() {
+        emit_exit(app, session_id, session, false);
+        return Err(format!("Failed to stop terminal process: {}", e));
+    }
+
+    // Reaping blocks until the process is gone, so keep it off the async runtime
+    let app = app.clone();
+    let session_id = session_id.to_string();
+    tokio::task::spawn_blocking(move || emit_exit(&app, &session_id, session, true));
     Ok(())
-} 
\ No newline at end of file
+}
+
+fn session_info(session_id: &str, session: &mut TerminalSession) -> TerminalSessionInfo {
+    TerminalSessionInfo {
+        session_id: session_id.to_string(),
+        cwd: session.cwd.clone(),
+        shell: session.shell.clone(),
+        pid: session.child.process_id(),
+        cols: session.cols,
+        rows: session.rows,
+        running: matches!(session.child.try_wait(), Ok(None)),
+        recording: session.recording.as_ref().map(|r| r.path().to_string_lossy().to_string()),
+        created_at: session.created_at,
+    }
+}
+
+fn is_running(session_id: &str) -> bool {
+    let mut sessions = TERMINAL_SESSIONS.lock().unwrap();
+    sessions
+        .get_mut(session_id)
+        .map(|session| matches!(session.child.try_wait(), Ok(None)))
+        .unwrap_or(false)
+}
+
+/// Reap the child and report how it ended. Consumes the session so the PTY is released.
+/// Without `wait` only an exit that already happened is reported. `wait` blocks, so call
+/// it from a thread rather than the async runtime.
+fn emit_exit(app: &AppHandle, session_id: &str, mut session: TerminalSession, wait: bool) {
+    if let Some(recording) = session.recording.take() {
+        if let Err(e) = recording.finish() {
+            eprintln!("Failed to save terminal recording: {}", e);
+        }
+    }
+    let status = if wait {
+        session.child.wait().map(Some)
+    } else {
+        session.child.try_wait()
+    };
+    let exit = match status {
+        Ok(Some(status)) => TerminalExit {
+            session_id: session_id.to_string(),
+            exit_code: Some(status.exit_code()),
+            success: status.success(),
+        },
+        Ok(None) => TerminalExit { session_id: session_id.to_string(), exit_code: None, success: false },
+        Err(e) => {
+            eprintln!("Failed to wait for terminal process: {}", e);
+            TerminalExit { session_id: session_id.to_string(), exit_code: None, success: false }
+        }
+    };
+    if let Some(notify) = session.exit_notify.take() {
+        let _ = notify.send((exit.clone(), session.scrollback.contents()));
+    }
+    let _ = app.emit(&terminal_event("exit", session_id), exit);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrollback_keeps_the_tail_and_counts_every_byte() {
+        let mut scrollback = Scrollback::new(8);
+        assert_eq!(scrollback.push(b"hello "), 6);
+        assert_eq!(scrollback.push(b"world"), 11);
+        assert_eq!(scrollback.contents(), "lo world");
+
+        assert_eq!(scrollback.push("añb".as_bytes()), 15);
+        // "ñ" is two bytes; trimming through it must not leave half a character behind
+        assert_eq!(scrollback.push(b"cdefgh"), 21);
+        assert_eq!(scrollback.contents(), "bcdefgh");
+    }
+
+    #[test]
+    fn test_characters_split_across_reads_are_decoded_whole() {
+        let mut decoder = Utf8Decoder::default();
+        let bytes = "a€b".as_bytes();
+        assert_eq!(decoder.decode(&bytes[..2]), "a");
+        assert_eq!(decoder.decode(&bytes[2..3]), "");
+        assert_eq!(decoder.decode(&bytes[3..]), "€b");
+        assert_eq!(decoder.decode(b"\xFFc"), "\u{FFFD}c");
+    }
+}
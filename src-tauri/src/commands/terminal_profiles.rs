@@ -0,0 +1,216 @@
+use crate::db::{queries::Queries, DbPool};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::State;
+
+const PROFILES_SETTING: &str = "terminal_profiles";
+const HISTORY_SETTING: &str = "terminal_command_history";
+/// One-shot command results kept per project
+const HISTORY_LIMIT: usize = 20;
+/// Tail of each command's output kept in the history
+const HISTORY_OUTPUT_BYTES: usize = 8 * 1024;
+
+/// How to launch a terminal: shell, arguments, environment overrides, size and a startup command
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TerminalProfile {
+    pub name: String,
+    pub shell: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    pub cols: Option<u16>,
+    pub rows: Option<u16>,
+    pub startup_command: Option<String>,
+}
+
+impl TerminalProfile {
+    /// `$SHELL` or `/bin/bash`, `cmd.exe` on Windows
+    pub fn shell(&self) -> String {
+        if let Some(shell) = self.shell.as_ref().filter(|s| !s.trim().is_empty()) {
+            return shell.clone();
+        }
+        if cfg!(target_os = "windows") {
+            "cmd.exe".to_string()
+        } else {
+            std::env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string())
+        }
+    }
+
+    pub fn size(&self) -> (u16, u16) {
+        (self.cols.unwrap_or(80).max(1), self.rows.unwrap_or(24).max(1))
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(anyhow!("Profile name is required"));
+        }
+        if let Some(key) = self.env.keys().find(|k| k.is_empty() || k.contains('=')) {
+            return Err(anyhow!("Invalid environment variable name '{}'", key));
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of a one-shot command run through `run_terminal_command`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalCommandResult {
+    pub session_id: String,
+    pub command: String,
+    pub cwd: String,
+    pub profile: Option<String>,
+    pub exit_code: Option<u32>,
+    pub success: bool,
+    pub timed_out: bool,
+    pub output: String,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub duration_ms: u64,
+}
+
+pub fn load_profiles(queries: &Queries, project_id: &str) -> Result<Vec<TerminalProfile>> {
+    match queries.get_project_setting(project_id, PROFILES_SETTING)? {
+        Some(value) => Ok(serde_json::from_str(&value)?),
+        None => Ok(Vec::new()),
+    }
+}
+
+pub fn find_profile(queries: &Queries, project_id: &str, name: &str) -> Result<TerminalProfile> {
+    load_profiles(queries, project_id)?
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| anyhow!("Terminal profile '{}' not found", name))
+}
+
+/// Keep the latest results, newest first, so the project can show how its last runs went
+pub fn record_command_result(queries: &Queries, project_id: &str, result: &TerminalCommandResult) -> Result<()> {
+    let mut result = result.clone();
+    if result.output.len() > HISTORY_OUTPUT_BYTES {
+        let mut start = result.output.len() - HISTORY_OUTPUT_BYTES;
+        while !result.output.is_char_boundary(start) {
+            start += 1;
+        }
+        result.output = result.output.split_off(start);
+    }
+
+    let mut history = command_history(queries, project_id)?;
+    history.insert(0, result);
+    history.truncate(HISTORY_LIMIT);
+    queries.set_project_setting(project_id, HISTORY_SETTING, &serde_json::to_string(&history)?)
+}
+
+fn command_history(queries: &Queries, project_id: &str) -> Result<Vec<TerminalCommandResult>> {
+    match queries.get_project_setting(project_id, HISTORY_SETTING)? {
+        Some(value) => Ok(serde_json::from_str(&value)?),
+        None => Ok(Vec::new()),
+    }
+}
+
+#[tauri::command]
+pub async fn get_terminal_profiles(
+    project_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<Vec<TerminalProfile>, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    load_profiles(&queries, &project_id).map_err(|e| e.to_string())
+}
+
+/// Create or replace the profile with the same name
+#[tauri::command]
+pub async fn save_terminal_profile(
+    project_id: String,
+    profile: TerminalProfile,
+    pool: State<'_, DbPool>,
+) -> Result<Vec<TerminalProfile>, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    profile.validate().map_err(|e| e.to_string())?;
+    let mut profiles = load_profiles(&queries, &project_id).map_err(|e| e.to_string())?;
+    match profiles.iter_mut().find(|p| p.name == profile.name) {
+        Some(existing) => *existing = profile,
+        None => profiles.push(profile),
+    }
+
+    let value = serde_json::to_string(&profiles).map_err(|e| e.to_string())?;
+    queries
+        .set_project_setting(&project_id, PROFILES_SETTING, &value)
+        .map_err(|e| e.to_string())?;
+    Ok(profiles)
+}
+
+#[tauri::command]
+pub async fn delete_terminal_profile(
+    project_id: String,
+    name: String,
+    pool: State<'_, DbPool>,
+) -> Result<Vec<TerminalProfile>, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    let mut profiles = load_profiles(&queries, &project_id).map_err(|e| e.to_string())?;
+    let before = profiles.len();
+    profiles.retain(|p| p.name != name);
+    if profiles.len() == before {
+        return Err(format!("Terminal profile '{}' not found", name));
+    }
+
+    let value = serde_json::to_string(&profiles).map_err(|e| e.to_string())?;
+    queries
+        .set_project_setting(&project_id, PROFILES_SETTING, &value)
+        .map_err(|e| e.to_string())?;
+    Ok(profiles)
+}
+
+#[tauri::command]
+pub async fn get_terminal_command_history(
+    project_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<Vec<TerminalCommandResult>, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    command_history(&queries, &project_id).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_db;
+    use chrono::Utc;
+
+    #[test]
+    fn test_profiles_are_found_by_name_and_history_is_capped() {
+        let (_pool, queries) = test_db("p1", None);
+        let profile = TerminalProfile {
+            name: "node".to_string(),
+            shell: Some("/bin/sh".to_string()),
+            env: HashMap::from([("NODE_ENV".to_string(), "test".to_string())]),
+            cols: Some(120),
+            ..Default::default()
+        };
+        queries.set_project_setting("p1", PROFILES_SETTING, &serde_json::to_string(&vec![profile.clone()]).unwrap()).unwrap();
+
+        assert_eq!(find_profile(&queries, "p1", "node").unwrap(), profile);
+        assert_eq!(profile.size(), (120, 24));
+        assert!(find_profile(&queries, "p1", "python").is_err());
+
+        for i in 0..HISTORY_LIMIT + 5 {
+            let result = TerminalCommandResult {
+                session_id: i.to_string(),
+                command: "npm test".to_string(),
+                cwd: "/tmp".to_string(),
+                profile: None,
+                exit_code: Some(0),
+                success: true,
+                timed_out: false,
+                output: "x".repeat(HISTORY_OUTPUT_BYTES + 10),
+                started_at: Utc::now(),
+                duration_ms: 1,
+            };
+            record_command_result(&queries, "p1", &result).unwrap();
+        }
+        let history = command_history(&queries, "p1").unwrap();
+        assert_eq!(history.len(), HISTORY_LIMIT);
+        assert_eq!(history[0].session_id, (HISTORY_LIMIT + 4).to_string());
+        assert_eq!(history[0].output.len(), HISTORY_OUTPUT_BYTES);
+    }
+}
@@ -0,0 +1,243 @@
+use crate::commands::terminal::{terminal_event, TerminalExit, TerminalOutput};
+use crate::commands::workspace::resolve_project_folder;
+use crate::db::{queries::Queries, DbPool};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
+
+/// Folder under the project where `.cast` files are written
+pub const RECORDINGS_DIR: &str = "recordings";
+/// Pauses longer than this are shortened on replay
+const MAX_REPLAY_IDLE: f64 = 2.0;
+
+/// First line of an asciicast v2 file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CastHeader {
+    pub version: u8,
+    pub width: u16,
+    pub height: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<JsonValue>,
+}
+
+/// One `[time, code, data]` line; code is "o" for output, "i" for input, "r" for resize
+#[derive(Debug, Clone, PartialEq)]
+pub struct CastEvent {
+    pub time: f64,
+    pub code: String,
+    pub data: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TerminalRecordingInfo {
+    pub file_name: String,
+    pub path: String,
+    pub title: Option<String>,
+    pub width: u16,
+    pub height: u16,
+    pub timestamp: Option<i64>,
+    pub duration_secs: f64,
+    pub size_bytes: u64,
+}
+
+/// An asciicast v2 file being written while a terminal session runs
+pub struct Recording {
+    writer: BufWriter<File>,
+    started: Instant,
+    path: PathBuf,
+}
+
+impl Recording {
+    pub fn create(path: PathBuf, header: &CastHeader) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut writer = BufWriter::new(File::create(&path)?);
+        writeln!(writer, "{}", serde_json::to_string(header)?)?;
+        Ok(Self { writer, started: Instant::now(), path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn output(&mut self, data: &str) -> Result<()> {
+        self.event("o", data)
+    }
+
+    pub fn resize(&mut self, cols: u16, rows: u16) -> Result<()> {
+        self.event("r", &format!("{}x{}", cols, rows))
+    }
+
+    pub fn finish(mut self) -> Result<PathBuf> {
+        self.writer.flush()?;
+        Ok(self.path)
+    }
+
+    fn event(&mut self, code: &str, data: &str) -> Result<()> {
+        let time = (self.started.elapsed().as_secs_f64() * 1_000_000.0).round() / 1_000_000.0;
+        writeln!(self.writer, "{}", serde_json::json!([time, code, data]))?;
+        Ok(())
+    }
+}
+
+/// Where a new recording for a project goes, e.g. `recordings/20250101-120000-1a2b3c4d.cast`
+pub fn recording_path(queries: &Queries, project_id: &str, session_id: &str) -> Result<PathBuf> {
+    let short_id: String = session_id.chars().take(8).collect();
+    let file_name = format!("{}-{}.cast", chrono::Utc::now().format("%Y%m%d-%H%M%S"), short_id);
+    Ok(resolve_project_folder(queries, project_id)?.join(RECORDINGS_DIR).join(file_name))
+}
+
+pub fn read_cast(path: &Path) -> Result<(CastHeader, Vec<CastEvent>)> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut lines = reader.lines();
+
+    let header_line = lines.next().ok_or_else(|| anyhow!("Recording is empty"))??;
+    let header: CastHeader = serde_json::from_str(&header_line).context("Invalid asciicast header")?;
+    if header.version != 2 {
+        return Err(anyhow!("Unsupported asciicast version {}", header.version));
+    }
+
+    let mut events = Vec::new();
+    for (index, line) in lines.enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (time, code, data): (f64, String, String) = serde_json::from_str(&line)
+            .with_context(|| format!("Invalid asciicast event on line {}", index + 2))?;
+        events.push(CastEvent { time, code, data });
+    }
+    Ok((header, events))
+}
+
+#[tauri::command]
+pub async fn list_terminal_recordings(
+    project_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<Vec<TerminalRecordingInfo>, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    let dir = resolve_project_folder(&queries, &project_id)
+        .map_err(|e| e.to_string())?
+        .join(RECORDINGS_DIR);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = fs::read_dir(&dir).map_err(|e| format!("Failed to read recordings: {}", e))?;
+    let mut recordings = Vec::new();
+    for path in entries.filter_map(|entry| entry.ok().map(|e| e.path())) {
+        if path.extension().and_then(|ext| ext.to_str()) != Some("cast") {
+            continue;
+        }
+        // Skip files that are not valid recordings rather than failing the whole listing
+        let Ok((header, events)) = read_cast(&path) else { continue };
+        recordings.push(TerminalRecordingInfo {
+            file_name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+            path: path.to_string_lossy().to_string(),
+            title: header.title,
+            width: header.width,
+            height: header.height,
+            timestamp: header.timestamp,
+            duration_secs: events.last().map(|e| e.time).unwrap_or(0.0),
+            size_bytes: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+        });
+    }
+
+    recordings.sort_by(|a, b| b.file_name.cmp(&a.file_name));
+    Ok(recordings)
+}
+
+/// Play a recording back on `terminal-output-{replay_id}`, the same events a live session
+/// emits, followed by `terminal-exit-` and `terminal-closed-`. Returns the replay id.
+#[tauri::command]
+pub async fn replay_terminal_recording(
+    app: AppHandle,
+    project_id: String,
+    file_name: String,
+    speed: Option<f64>,
+    pool: State<'_, DbPool>,
+) -> Result<String, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    if file_name.contains(['/', '\\']) || file_name.starts_with('.') {
+        return Err("Invalid recording name".to_string());
+    }
+    let speed = speed.unwrap_or(1.0);
+    if !(speed.is_finite() && speed > 0.0) {
+        return Err("Replay speed must be greater than zero".to_string());
+    }
+
+    let path = resolve_project_folder(&queries, &project_id)
+        .map_err(|e| e.to_string())?
+        .join(RECORDINGS_DIR)
+        .join(&file_name);
+    let (_, events) = read_cast(&path).map_err(|e| format!("Failed to read recording: {}", e))?;
+
+    let replay_id = uuid::Uuid::new_v4().to_string();
+    let replay_id_clone = replay_id.clone();
+    thread::spawn(move || {
+        let mut offset = 0u64;
+        let mut previous = 0.0;
+        for event in events.into_iter().filter(|e| e.code == "o") {
+            let gap = (event.time - previous).clamp(0.0, MAX_REPLAY_IDLE);
+            previous = event.time;
+            thread::sleep(Duration::from_secs_f64(gap / speed));
+
+            offset += event.data.len() as u64;
+            let output = TerminalOutput { data: event.data, offset };
+            let _ = app.emit(&terminal_event("output", &replay_id_clone), output);
+        }
+
+        let exit = TerminalExit { session_id: replay_id_clone.clone(), exit_code: None, success: true };
+        let _ = app.emit(&terminal_event("exit", &replay_id_clone), exit);
+        let _ = app.emit(&terminal_event("closed", &replay_id_clone), &replay_id_clone);
+    });
+
+    Ok(replay_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_round_trips_through_asciicast() {
+        let dir = std::env::temp_dir().join(format!("prob-recording-{}", uuid::Uuid::new_v4()));
+        let path = dir.join(RECORDINGS_DIR).join("session.cast");
+        let header = CastHeader {
+            version: 2,
+            width: 80,
+            height: 24,
+            timestamp: Some(1_700_000_000),
+            title: Some("npm test".to_string()),
+            env: None,
+        };
+
+        let mut recording = Recording::create(path.clone(), &header).unwrap();
+        recording.output("$ npm test\r\n").unwrap();
+        recording.resize(120, 40).unwrap();
+        recording.output("\u{1b}[32mok\u{1b}[0m \"quoted\"\r\n").unwrap();
+        recording.finish().unwrap();
+
+        let (read_header, events) = read_cast(&path).unwrap();
+        assert_eq!(read_header.title.as_deref(), Some("npm test"));
+        assert_eq!(events.iter().map(|e| e.code.as_str()).collect::<Vec<_>>(), vec!["o", "r", "o"]);
+        assert_eq!(events[1].data, "120x40");
+        assert_eq!(events[2].data, "\u{1b}[32mok\u{1b}[0m \"quoted\"\r\n");
+        assert!(events.windows(2).all(|w| w[0].time <= w[1].time));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
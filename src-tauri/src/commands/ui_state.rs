@@ -0,0 +1,394 @@
+use crate::db::{models::*, queries::Queries, DbPool};
+use anyhow::Result;
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::State;
+use uuid::Uuid;
+
+/// Quiet period after the last change before a UI state write reaches the database
+const FLUSH_DELAY: Duration = Duration::from_millis(750);
+const INTERACTION_MODES: [&str; 4] = ["default", "selection", "panning", "locked"];
+
+// Latest unsaved UI state per project, written out once changes stop. Writes are serialised
+// by FLUSH_LOCK so the pending map itself is never locked across a database call.
+lazy_static::lazy_static! {
+    static ref PENDING_UI_STATE: Arc<Mutex<HashMap<String, PendingUiState>>> = Arc::new(Mutex::new(HashMap::new()));
+    static ref FLUSH_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Unsaved state and the number of the change that produced it; a scheduled flush only
+/// writes if no later change has arrived since
+#[derive(Clone)]
+struct PendingUiState {
+    state: ReactFlowState,
+    generation: u64,
+}
+
+/// Partial update; fields left out keep their current value
+#[derive(Debug, Default, Deserialize)]
+pub struct SaveUiStateRequest {
+    pub project_id: String,
+    pub viewport: Option<JsonValue>,
+    pub nodes: Option<JsonValue>,
+    pub edges: Option<JsonValue>,
+    pub minimap_config: Option<JsonValue>,
+    pub controls_config: Option<JsonValue>,
+    pub selection_ids: Option<Vec<String>>,
+    pub interaction_mode: Option<String>,
+    pub canvas_locked: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveSelectionRequest {
+    pub project_id: String,
+    pub selection_type: String, // "single", "multi" or "connected"
+    pub selected_items: JsonValue,
+    pub selection_metadata: Option<JsonValue>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StartAnimationRequest {
+    pub project_id: String,
+    pub element_id: String,
+    pub element_type: String,
+    pub animation_type: String,
+    pub animation_config: JsonValue,
+    pub start_state: Option<JsonValue>,
+    pub end_state: Option<JsonValue>,
+    pub duration_ms: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveTransitionRequest {
+    pub project_id: String,
+    pub from_step: String,
+    pub to_step: String,
+    pub transition_type: String,
+    pub transition_config: Option<JsonValue>,
+}
+
+/// Record viewport, minimap, selection and interaction mode. Writes are debounced so
+/// panning and zooming cost one database write once the canvas settles.
+#[tauri::command]
+pub async fn save_ui_state(
+    request: SaveUiStateRequest,
+    pool: State<'_, DbPool>,
+) -> Result<ReactFlowState, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    if let Some(mode) = &request.interaction_mode {
+        if !INTERACTION_MODES.contains(&mode.as_str()) {
+            return Err(format!("Invalid interaction mode '{}'; expected one of {}", mode, INTERACTION_MODES.join(", ")));
+        }
+    }
+
+    let project_id = request.project_id.clone();
+    let (state, generation) = loop {
+        // Read the stored state before taking the lock; it is only needed when nothing is pending
+        let stored = if is_pending(&project_id) {
+            None
+        } else {
+            Some(stored_or_default(&queries, &project_id).map_err(|e| e.to_string())?)
+        };
+
+        let mut pending = PENDING_UI_STATE.lock().unwrap();
+        let (base, generation) = match (pending.remove(&project_id), stored) {
+            (Some(entry), _) => (entry.state, entry.generation + 1),
+            (None, Some(stored)) => (stored, 1),
+            // Flushed between the check and the lock; read what was just written
+            (None, None) => continue,
+        };
+        let state = merge(base, request);
+        pending.insert(project_id.clone(), PendingUiState { state: state.clone(), generation });
+        break (state, generation);
+    };
+
+    // Every change restarts the quiet period; only the flush of the last one writes
+    let pool = pool.inner().clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(FLUSH_DELAY).await;
+        if let Err(e) = flush_pending(&Queries::new(pool), &project_id, Some(generation)) {
+            eprintln!("Failed to save UI state for {}: {}", project_id, e);
+        }
+    });
+
+    Ok(state)
+}
+
+#[tauri::command]
+pub async fn load_ui_state(
+    project_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<ReactFlowState, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    current_ui_state(&queries, &project_id)
+        .map_err(|e| e.to_string())?
+        .map_or_else(|| Ok(default_state(&project_id)), Ok)
+}
+
+/// Write any pending UI state now, e.g. before the window closes
+#[tauri::command]
+pub async fn flush_ui_state(
+    project_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<(), String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    flush_pending(&queries, &project_id, None).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn save_ui_selection(
+    request: SaveSelectionRequest,
+    pool: State<'_, DbPool>,
+) -> Result<UiSelectionState, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    if !request.selected_items.is_array() {
+        return Err("Selected items must be a valid JSON array".to_string());
+    }
+
+    let existing = queries.get_ui_selection_state(&request.project_id).map_err(|e| e.to_string())?;
+    let state = UiSelectionState {
+        id: existing.as_ref().map(|s| s.id.clone()).unwrap_or_else(|| Uuid::new_v4().to_string()),
+        project_id: request.project_id,
+        selection_type: request.selection_type,
+        selected_items: request.selected_items,
+        selection_metadata: request.selection_metadata,
+        created_at: existing.map(|s| s.created_at).unwrap_or_else(Utc::now),
+        updated_at: Utc::now(),
+    };
+
+    queries.save_ui_selection_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+#[tauri::command]
+pub async fn load_ui_selection(
+    project_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<Option<UiSelectionState>, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    queries.get_ui_selection_state(&project_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn start_ui_animation(
+    request: StartAnimationRequest,
+    pool: State<'_, DbPool>,
+) -> Result<UiAnimationState, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    let animation = UiAnimationState {
+        id: Uuid::new_v4().to_string(),
+        project_id: request.project_id,
+        element_id: request.element_id,
+        element_type: request.element_type,
+        animation_type: request.animation_type,
+        animation_config: request.animation_config,
+        start_state: request.start_state,
+        end_state: request.end_state,
+        duration_ms: request.duration_ms,
+        is_active: true,
+        created_at: Utc::now(),
+    };
+
+    queries.create_ui_animation(&animation).map_err(|e| e.to_string())?;
+    Ok(animation)
+}
+
+#[tauri::command]
+pub async fn get_active_animations(
+    project_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<Vec<UiAnimationState>, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    queries.get_active_ui_animations(&project_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn finish_ui_animation(
+    animation_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<(), String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    queries.finish_ui_animation(&animation_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn save_canvas_transition(
+    request: SaveTransitionRequest,
+    pool: State<'_, DbPool>,
+) -> Result<CanvasTransition, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    for step in [&request.from_step, &request.to_step] {
+        WorkflowStep::parse(step).ok_or_else(|| format!("Unknown workflow step: {}", step))?;
+    }
+
+    let transition = CanvasTransition {
+        id: Uuid::new_v4().to_string(),
+        project_id: request.project_id,
+        from_step: request.from_step,
+        to_step: request.to_step,
+        transition_type: request.transition_type,
+        transition_config: request.transition_config,
+        is_active: true,
+        created_at: Utc::now(),
+    };
+
+    queries.save_canvas_transition(&transition).map_err(|e| e.to_string())?;
+    Ok(transition)
+}
+
+#[tauri::command]
+pub async fn get_canvas_transition(
+    project_id: String,
+    from_step: String,
+    to_step: String,
+    pool: State<'_, DbPool>,
+) -> Result<Option<CanvasTransition>, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    queries
+        .get_canvas_transition(&project_id, &from_step, &to_step)
+        .map_err(|e| e.to_string())
+}
+
+/// Latest UI state for a project, including changes that are still waiting to be written
+pub fn current_ui_state(queries: &Queries, project_id: &str) -> Result<Option<ReactFlowState>> {
+    if let Some(entry) = PENDING_UI_STATE.lock().unwrap().get(project_id) {
+        return Ok(Some(entry.state.clone()));
+    }
+    queries.get_react_flow_state(project_id)
+}
+
+fn is_pending(project_id: &str) -> bool {
+    PENDING_UI_STATE.lock().unwrap().contains_key(project_id)
+}
+
+/// Write the pending state for a project, if it is still `generation` (any when None). The
+/// entry stays pending during the write, so a change arriving meanwhile builds on it and is
+/// flushed by its own timer.
+fn flush_pending(queries: &Queries, project_id: &str, generation: Option<u64>) -> Result<()> {
+    let _writing = FLUSH_LOCK.lock().unwrap();
+
+    let entry = PENDING_UI_STATE.lock().unwrap()
+        .get(project_id)
+        .filter(|entry| generation.is_none_or(|generation| entry.generation == generation))
+        .cloned();
+    let Some(entry) = entry else { return Ok(()) };
+
+    queries.save_react_flow_state(&entry.state)?;
+
+    let mut pending = PENDING_UI_STATE.lock().unwrap();
+    if pending.get(project_id).is_some_and(|current| current.generation == entry.generation) {
+        pending.remove(project_id);
+    }
+    Ok(())
+}
+
+fn stored_or_default(queries: &Queries, project_id: &str) -> Result<ReactFlowState> {
+    Ok(queries
+        .get_react_flow_state(project_id)?
+        .unwrap_or_else(|| default_state(project_id)))
+}
+
+fn default_state(project_id: &str) -> ReactFlowState {
+    ReactFlowState {
+        id: Uuid::new_v4().to_string(),
+        project_id: project_id.to_string(),
+        viewport: serde_json::json!({ "x": 0, "y": 0, "zoom": 1 }),
+        nodes: serde_json::json!([]),
+        edges: serde_json::json!([]),
+        minimap_config: None,
+        controls_config: None,
+        selection_ids: Vec::new(),
+        interaction_mode: "default".to_string(),
+        canvas_locked: false,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+fn merge(mut state: ReactFlowState, request: SaveUiStateRequest) -> ReactFlowState {
+    if let Some(viewport) = request.viewport {
+        state.viewport = viewport;
+    }
+    if let Some(nodes) = request.nodes {
+        state.nodes = nodes;
+    }
+    if let Some(edges) = request.edges {
+        state.edges = edges;
+    }
+    if request.minimap_config.is_some() {
+        state.minimap_config = request.minimap_config;
+    }
+    if request.controls_config.is_some() {
+        state.controls_config = request.controls_config;
+    }
+    if let Some(selection_ids) = request.selection_ids {
+        state.selection_ids = selection_ids;
+    }
+    if let Some(mode) = request.interaction_mode {
+        state.interaction_mode = mode;
+    }
+    if let Some(locked) = request.canvas_locked {
+        state.canvas_locked = locked;
+    }
+    state.updated_at = Utc::now();
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_db;
+
+    #[test]
+    fn test_merge_keeps_fields_not_in_the_update() {
+        let state = merge(default_state("p1"), SaveUiStateRequest {
+            project_id: "p1".to_string(),
+            selection_ids: Some(vec!["persona-1".to_string()]),
+            interaction_mode: Some("panning".to_string()),
+            ..Default::default()
+        });
+        let state = merge(state, SaveUiStateRequest {
+            project_id: "p1".to_string(),
+            viewport: Some(serde_json::json!({ "x": 120, "y": -40, "zoom": 0.8 })),
+            ..Default::default()
+        });
+
+        assert_eq!(state.viewport["zoom"], 0.8);
+        assert_eq!(state.selection_ids, vec!["persona-1".to_string()]);
+        assert_eq!(state.interaction_mode, "panning");
+    }
+
+    #[test]
+    fn test_pending_state_is_visible_until_flushed() {
+        let (_pool, queries) = test_db("ui-p1", None);
+
+        let mut state = default_state("ui-p1");
+        state.viewport = serde_json::json!({ "x": 10, "y": 20, "zoom": 1.5 });
+        PENDING_UI_STATE.lock().unwrap().insert("ui-p1".to_string(), PendingUiState { state, generation: 2 });
+
+        assert!(queries.get_react_flow_state("ui-p1").unwrap().is_none());
+        assert_eq!(current_ui_state(&queries, "ui-p1").unwrap().unwrap().viewport["zoom"], 1.5);
+
+        // A timer from an earlier change does not write
+        flush_pending(&queries, "ui-p1", Some(1)).unwrap();
+        assert!(queries.get_react_flow_state("ui-p1").unwrap().is_none());
+
+        flush_pending(&queries, "ui-p1", Some(2)).unwrap();
+        assert!(!PENDING_UI_STATE.lock().unwrap().contains_key("ui-p1"));
+        assert_eq!(queries.get_react_flow_state("ui-p1").unwrap().unwrap().viewport["zoom"], 1.5);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::db::{models::*, queries::Queries, DbPool};
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serde::Serialize;
+use tauri::State;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize)]
+pub struct StepTransition {
+    pub project_id: String,
+    pub from_step: String,
+    pub to_step: String,
+    pub progress: Vec<ProgressTracking>,
+}
+
+/// Move a project to another workflow step, rejecting illegal moves and
+/// moves whose preconditions are not met
+#[tauri::command]
+pub async fn advance_project_step(
+    project_id: String,
+    to_step: String,
+    pool: State<'_, DbPool>,
+) -> Result<StepTransition, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    let to = WorkflowStep::parse(&to_step)
+        .ok_or_else(|| format!("Unknown workflow step: {}", to_step))?;
+
+    transition_project_step(&queries, &project_id, &to, "user").map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_workflow_progress(
+    project_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<Vec<ProgressTracking>, String> {
+    let queries = Queries::new(pool.inner().clone());
+
+    queries
+        .get_progress_tracking(&project_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Validate and record a single transition
+pub fn transition_project_step(
+    queries: &Queries,
+    project_id: &str,
+    to: &WorkflowStep,
+    triggered_by: &str,
+) -> Result<StepTransition> {
+    let from = current_step(queries, project_id)?;
+
+    if &from == to {
+        return Err(anyhow!("Project is already at step '{}'", to.to_string()));
+    }
+    if !from.can_transition_to(to) {
+        return Err(anyhow!(
+            "Cannot move from '{}' to '{}'; steps can only advance one at a time",
+            from.to_string(),
+            to.to_string()
+        ));
+    }
+    if to.index() > from.index() {
+        check_preconditions(queries, project_id, to)?;
+    }
+
+    queries.record_step_transition(project_id, &from, to)?;
+
+    queries.append_state_event(&LangGraphStateEvent {
+        id: Uuid::new_v4().to_string(),
+        project_id: project_id.to_string(),
+        event_type: "workflow_step_changed".to_string(),
+        event_data: serde_json::json!({
+            "from": from.to_string(),
+            "to": to.to_string(),
+        }),
+        event_metadata: None,
+        sequence_number: 0,
+        created_at: Utc::now(),
+        created_by: Some(triggered_by.to_string()),
+    })?;
+
+    Ok(StepTransition {
+        project_id: project_id.to_string(),
+        from_step: from.to_string(),
+        to_step: to.to_string(),
+        progress: queries.get_progress_tracking(project_id)?,
+    })
+}
+
+/// Bring a project forward to `reached` after a command has produced that
+/// step's data, walking through each intermediate step. Never moves backwards.
+pub fn sync_project_step(
+    queries: &Queries,
+    project_id: &str,
+    reached: &WorkflowStep,
+    triggered_by: &str,
+) -> Result<()> {
+    let mut current = current_step(queries, project_id)?;
+
+    while current.index() < reached.index() {
+        let next = current.next().ok_or_else(|| anyhow!("No step after '{}'", current.to_string()))?;
+        transition_project_step(queries, project_id, &next, triggered_by)?;
+        current = next;
+    }
+
+    Ok(())
+}
+
+/// `sync_project_step` for commands whose work is already saved. Failing to reach the step
+/// does not undo that work, so the failure is only logged.
+pub fn sync_project_step_or_log(queries: &Queries, project_id: &str, reached: &WorkflowStep, triggered_by: &str) {
+    if let Err(e) = sync_project_step(queries, project_id, reached, triggered_by) {
+        eprintln!("Project step was not moved to '{}': {}", reached.to_string(), e);
+    }
+}
+
+fn current_step(queries: &Queries, project_id: &str) -> Result<WorkflowStep> {
+    let project = queries.get_project(project_id)?
+        .ok_or_else(|| anyhow!("Project not found"))?;
+
+    // Older rows may hold a step name this version does not know; treat them as the start
+    Ok(WorkflowStep::parse(&project.current_step).unwrap_or(WorkflowStep::ProblemInput))
+}
+
+/// What has to exist before a project may enter `step`
+fn check_preconditions(queries: &Queries, project_id: &str, step: &WorkflowStep) -> Result<()> {
+    let unmet = match step {
+        WorkflowStep::ProblemInput => None,
+        WorkflowStep::SolutionDiscovery => {
+            let valid = queries.get_latest_core_problem(project_id)?
+                .map(|problem| problem.is_valid)
+                .unwrap_or(false);
+            (!valid).then_some("a validated problem statement")
+        }
+        WorkflowStep::FeatureSelection => {
+            let solutions = queries.get_solutions_with_mappings(project_id)?;
+            solutions.is_empty().then_some("generated solutions")
+        }
+        WorkflowStep::UserStories => {
+            let selected = queries.get_solutions_with_mappings(project_id)?
+                .iter()
+                .any(|(solution, _)| solution.is_selected);
+            (!selected).then_some("at least one selected solution")
+        }
+        WorkflowStep::Architecture => {
+            let stories = queries.get_user_stories(project_id)?;
+            stories.is_empty().then_some("at least one user story")
+        }
+        WorkflowStep::DesignSystem | WorkflowStep::RepositorySetup => {
+            let version = queries.get_latest_architecture_version(project_id)?;
+            (version == 0).then_some("a generated system design")
+        }
+    };
+
+    match unmet {
+        Some(requirement) => Err(anyhow!("Step '{}' requires {}", step.to_string(), requirement)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transitions_advance_one_step_or_go_back() {
+        let stories = WorkflowStep::UserStories;
+
+        assert!(stories.can_transition_to(&WorkflowStep::Architecture));
+        assert!(stories.can_transition_to(&WorkflowStep::ProblemInput));
+        assert!(!stories.can_transition_to(&WorkflowStep::DesignSystem));
+        assert!(!stories.can_transition_to(&WorkflowStep::UserStories));
+        assert_eq!(WorkflowStep::RepositorySetup.next(), None);
+    }
+
+    #[test]
+    fn test_parse_accepts_legacy_spelling() {
+        assert_eq!(WorkflowStep::parse("solution_discovery"), Some(WorkflowStep::SolutionDiscovery));
+        assert_eq!(WorkflowStep::parse("SolutionDiscovery"), Some(WorkflowStep::SolutionDiscovery));
+        assert_eq!(WorkflowStep::parse("persona_generation"), None);
+    }
+}
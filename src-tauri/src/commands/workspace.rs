@@ -1,9 +1,14 @@
+use crate::commands::export_history::{new_export, ExportKind, Exported};
 use crate::db::{models::*, queries::Queries, DbPool};
 use serde::{Deserialize, Serialize};
 use tauri::State;
 use uuid::Uuid;
 use anyhow::Result;
 use chrono::Utc;
+use std::path::PathBuf;
+
+/// What `export_project` includes, as export dialog section ids
+const PROJECT_EXPORT_SECTIONS: [&str; 6] = ["problem", "personas", "painPoints", "solutions", "userStories", "canvas"];
 
 #[derive(Debug, Deserialize)]
 pub struct CreateProjectRequest {
@@ -410,10 +415,13 @@ pub async fn export_project(
     project_id: String,
     format: String,
 ) -> Result<ExportProjectResponse, String> {
-    let pool = db.inner();
-    let queries = Queries::new(pool.clone());
-    
-    let export_data = gather_project_data(&project_id, &queries)
+    let queries = Queries::new(db.inner().clone());
+
+    Ok(export_project_with_record(&queries, project_id, format).await?.output)
+}
+
+pub async fn export_project_with_record(queries: &Queries, project_id: String, format: String) -> Result<Exported<ExportProjectResponse>, String> {
+    let export_data = gather_project_data(&project_id, queries)
         .await
         .map_err(|e| e.to_string())?;
     
@@ -424,16 +432,25 @@ pub async fn export_project(
         export_data.project.name.replace(' ', "_").to_lowercase(),
         chrono::Utc::now().format("%Y%m%d_%H%M%S")
     );
-    
-    Ok(ExportProjectResponse {
+
+    let args = serde_json::json!({ "project_id": project_id, "format": format });
+    let record = ExportRecord {
+        file_size_bytes: Some(data.len() as i64),
+        included_sections: PROJECT_EXPORT_SECTIONS.iter().map(|s| s.to_string()).collect(),
+        ..new_export(&project_id, ExportKind::Project, "json", &args).map_err(|e| e.to_string())?
+    };
+    queries.record_export(&record).map_err(|e| e.to_string())?;
+
+    let output = ExportProjectResponse {
         format,
         data,
         filename,
-    })
+    };
+    Ok(Exported { output, record })
 }
 
 /// Helper function to gather all project data
-async fn gather_project_data(project_id: &str, queries: &Queries) -> Result<ProjectExportData> {
+pub async fn gather_project_data(project_id: &str, queries: &Queries) -> Result<ProjectExportData> {
     let project = queries.get_project(project_id)?
         .ok_or_else(|| anyhow::anyhow!("Project not found"))?;
     
@@ -474,6 +491,40 @@ async fn gather_project_data(project_id: &str, queries: &Queries) -> Result<Proj
     })
 }
 
+/// Resolve the folder a project's generated files live in, under its workspace's `folder_path`
+pub fn resolve_project_folder(queries: &Queries, project_id: &str) -> Result<PathBuf> {
+    let project = queries.get_project(project_id)?
+        .ok_or_else(|| anyhow::anyhow!("Project not found"))?;
+
+    let workspace = queries.get_workspace(&project.workspace_id)?
+        .ok_or_else(|| anyhow::anyhow!("Workspace not found"))?;
+
+    let folder_path = workspace.folder_path
+        .filter(|path| !path.trim().is_empty())
+        .ok_or_else(|| anyhow::anyhow!("Workspace '{}' has no folder configured", workspace.name))?;
+
+    Ok(PathBuf::from(folder_path).join(slugify(&project.name)))
+}
+
+/// Turn a display name into a lowercase, dash-separated file name
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    let slug = slug.trim_end_matches('-').to_string();
+    if slug.is_empty() {
+        "untitled".to_string()
+    } else {
+        slug
+    }
+}
+
 /// Helper function to validate import data
 fn validate_import_data(data: &str) -> Result<ProjectImportData> {
     let import_data: ProjectImportData = serde_json::from_str(data)
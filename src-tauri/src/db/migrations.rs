@@ -65,6 +65,12 @@ pub fn load_migrations() -> Result<Vec<Migration>> {
         sql: include_str!("migrations/002_sync_with_supabase.sql").to_string(),
     });
     
+    migrations.push(Migration {
+        version: 3,
+        name: "canvas_versioning".to_string(),
+        sql: include_str!("migrations/003_canvas_versioning.sql").to_string(),
+    });
+    
     // Sort by version to ensure proper order
     migrations.sort_by_key(|m| m.version);
     
@@ -0,0 +1,30 @@
+-- Migration 003: Canvas version history
+-- Adds the columns needed to tell autosaves from manual saves, name checkpoints
+-- and track branches, then backfills version numbers for existing rows.
+
+ALTER TABLE canvas_states ADD COLUMN save_kind TEXT NOT NULL DEFAULT 'manual'; -- 'autosave', 'manual', 'restore', 'branch'
+ALTER TABLE canvas_states ADD COLUMN checkpoint_name TEXT;
+ALTER TABLE canvas_states ADD COLUMN branch_name TEXT NOT NULL DEFAULT 'main';
+
+-- Number existing saves per project in the order they were written
+UPDATE canvas_states SET version = (
+    SELECT COUNT(*) FROM canvas_states older
+    WHERE older.project_id = canvas_states.project_id
+      AND (older.created_at < canvas_states.created_at
+           OR (older.created_at = canvas_states.created_at AND older.id <= canvas_states.id))
+);
+
+UPDATE canvas_states SET parent_version_id = (
+    SELECT previous.id FROM canvas_states previous
+    WHERE previous.project_id = canvas_states.project_id
+      AND previous.version = canvas_states.version - 1
+)
+WHERE parent_version_id IS NULL;
+
+-- The newest save becomes current for projects that have none
+UPDATE canvas_states SET is_current = 1
+WHERE version = (SELECT MAX(version) FROM canvas_states latest WHERE latest.project_id = canvas_states.project_id)
+  AND NOT EXISTS (SELECT 1 FROM canvas_states cur WHERE cur.project_id = canvas_states.project_id AND cur.is_current = 1);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_canvas_checkpoint_name ON canvas_states(project_id, checkpoint_name) WHERE checkpoint_name IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_canvas_parent_version ON canvas_states(parent_version_id);
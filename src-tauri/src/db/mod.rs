@@ -39,3 +39,25 @@ pub async fn init_db() -> Result<DbPool> {
     
     Ok(pool)
 }
+
+/// In-memory database holding user `u1`, workspace `w1` (in `folder`, if given) and one project
+#[cfg(test)]
+pub fn test_db(project_id: &str, folder: Option<&std::path::Path>) -> (DbPool, Queries) {
+    use chrono::Utc;
+    use models::{Project, User};
+
+    let manager = SqliteConnectionManager::memory();
+    let pool = Pool::builder().max_size(1).build(manager).unwrap();
+    run_migrations(&pool).unwrap();
+
+    let queries = Queries::new(pool.clone());
+    queries.create_user(&User { id: "u1".to_string(), email: "u1@example.com".to_string(), created_at: Utc::now(), updated_at: Utc::now() }).unwrap();
+    queries.create_workspace(&Workspace {
+        id: "w1".to_string(),
+        user_id: "u1".to_string(),
+        folder_path: folder.map(|f| f.to_string_lossy().to_string()),
+        ..Default::default()
+    }).unwrap();
+    queries.create_project(&Project { id: project_id.to_string(), workspace_id: "w1".to_string(), name: "Demo App".to_string(), ..Default::default() }).unwrap();
+    (pool, queries)
+}
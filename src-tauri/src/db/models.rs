@@ -231,6 +231,41 @@ pub struct AtomicComponent {
     pub created_at: DateTime<Utc>,
 }
 
+// Focus Group & Export Models
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusGroupSession {
+    pub id: String,
+    pub project_id: String,
+    pub session_type: String,
+    pub total_votes_available: i32,
+    pub persona_votes: Vec<PersonaVote>, // Will be stored as JSON
+    pub status: String,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonaVote {
+    pub persona_id: String,
+    pub solution_id: String,
+    pub votes: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportRecord {
+    pub id: String,
+    pub project_id: String,
+    pub export_format: String, // 'markdown', 'json', 'pdf', 'docx', 'share', or a canvas/schema format
+    pub export_config: serde_json::Value, // Arguments of the exporting command, with `kind` naming it
+    pub file_path: Option<String>,
+    pub file_size_bytes: Option<i64>,
+    pub included_sections: Vec<String>, // Will be stored as JSON
+    pub export_version: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Option<String>,
+}
+
 // State Management Models
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -250,10 +285,16 @@ pub struct LangGraphExecutionLog {
 pub struct ReactFlowState {
     pub id: String,
     pub project_id: String,
-    pub viewport: Option<serde_json::Value>,
+    pub viewport: serde_json::Value,
     pub nodes: serde_json::Value,
     pub edges: serde_json::Value,
+    pub minimap_config: Option<serde_json::Value>,
+    pub controls_config: Option<serde_json::Value>,
+    pub selection_ids: Vec<String>,
+    pub interaction_mode: String,
+    pub canvas_locked: bool,
     pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -279,6 +320,138 @@ pub struct CanvasState {
     pub updated_at: DateTime<Utc>,
 }
 
+/// One entry in a project's canvas history, without the node/edge payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanvasVersion {
+    pub id: String,
+    pub project_id: String,
+    pub version: i32,
+    pub parent_version_id: Option<String>,
+    pub change_description: Option<String>,
+    pub is_current: bool,
+    pub save_kind: String,
+    pub checkpoint_name: Option<String>,
+    pub branch_name: String,
+    pub node_count: i64,
+    pub edge_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgressTracking {
+    pub id: String,
+    pub project_id: String,
+    pub step_name: String,
+    pub step_index: i32,
+    pub is_complete: bool,
+    pub is_current: bool,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub ui_state: Option<serde_json::Value>,
+    pub persona_circles: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A generated document written into the workspace folder. `file_hash` and `content` describe
+/// what was last written; `is_synced` is false once the file on disk no longer matches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectDocument {
+    pub id: String,
+    pub project_id: String,
+    pub document_type: String,
+    pub file_path: String,
+    pub file_hash: Option<String>,
+    pub last_modified: Option<DateTime<Utc>>,
+    pub content: Option<String>,
+    pub is_synced: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentDriftReport {
+    pub id: String,
+    pub project_id: String,
+    pub detected_at: DateTime<Utc>,
+    pub affected_documents: Vec<String>,
+    pub drift_details: serde_json::Value,
+    pub drift_severity: String,
+    pub resolution_status: String,
+    pub resolution_method: Option<String>,
+    pub resolution_metadata: Option<serde_json::Value>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub resolved_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentGenerationJob {
+    pub id: String,
+    pub project_id: String,
+    pub document_type: String,
+    pub dependencies: Vec<String>,
+    pub status: String, // 'pending', 'processing', 'completed', 'failed' or 'cancelled'
+    pub priority: i32, // Lower runs first
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub error_message: Option<String>,
+    pub retry_count: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentFlowCache {
+    pub id: String,
+    pub user_id: Option<String>,
+    pub project_id: String,
+    pub problem_statement: String,
+    pub last_activity: DateTime<Utc>,
+    pub progress_segments: serde_json::Value,
+    pub completion_percentage: i32,
+    pub cache_key: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UiSelectionState {
+    pub id: String,
+    pub project_id: String,
+    pub selection_type: String,
+    pub selected_items: serde_json::Value,
+    pub selection_metadata: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UiAnimationState {
+    pub id: String,
+    pub project_id: String,
+    pub element_id: String,
+    pub element_type: String,
+    pub animation_type: String,
+    pub animation_config: serde_json::Value,
+    pub start_state: Option<serde_json::Value>,
+    pub end_state: Option<serde_json::Value>,
+    pub duration_ms: Option<i32>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanvasTransition {
+    pub id: String,
+    pub project_id: String,
+    pub from_step: String,
+    pub to_step: String,
+    pub transition_type: String,
+    pub transition_config: Option<serde_json::Value>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
 // Default Implementations
 
 impl Default for Workspace {
@@ -490,6 +663,26 @@ impl UserStory {
 
 // Workflow State Enums
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CanvasSaveKind {
+    Autosave,
+    Manual,
+    Restore,
+    Branch,
+}
+
+impl CanvasSaveKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CanvasSaveKind::Autosave => "autosave",
+            CanvasSaveKind::Manual => "manual",
+            CanvasSaveKind::Restore => "restore",
+            CanvasSaveKind::Branch => "branch",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum WorkflowStep {
     ProblemInput,
@@ -528,4 +721,37 @@ impl From<String> for WorkflowStep {
             _ => WorkflowStep::ProblemInput,
         }
     }
-}
\ No newline at end of file
+}
+impl WorkflowStep {
+    /// Every step in workflow order
+    pub const ALL: [WorkflowStep; 7] = [
+        WorkflowStep::ProblemInput,
+        WorkflowStep::SolutionDiscovery,
+        WorkflowStep::FeatureSelection,
+        WorkflowStep::UserStories,
+        WorkflowStep::Architecture,
+        WorkflowStep::DesignSystem,
+        WorkflowStep::RepositorySetup,
+    ];
+
+    /// Strict parse of a stored step. Unlike `From<String>` this does not fall
+    /// back to `ProblemInput`, and it accepts the legacy PascalCase spelling.
+    pub fn parse(s: &str) -> Option<WorkflowStep> {
+        WorkflowStep::ALL.into_iter().find(|step| {
+            step.to_string() == s || format!("{:?}", step) == s
+        })
+    }
+
+    pub fn index(&self) -> usize {
+        WorkflowStep::ALL.iter().position(|step| step == self).unwrap_or(0)
+    }
+
+    pub fn next(&self) -> Option<WorkflowStep> {
+        WorkflowStep::ALL.get(self.index() + 1).cloned()
+    }
+
+    /// A project moves forward one step at a time but may go back to any earlier step
+    pub fn can_transition_to(&self, to: &WorkflowStep) -> bool {
+        to.index() < self.index() || self.next().as_ref() == Some(to)
+    }
+}
@@ -5,6 +5,27 @@ use r2d2_sqlite::SqliteConnectionManager;
 use rusqlite::{params, OptionalExtension, Transaction};
 use serde_json;
 
+// Subqueries resolving a row id to its project, used for recent flow invalidation
+const PROJECT_BY_ID: &str = "SELECT ?1";
+const PROJECT_BY_CORE_PROBLEM: &str = "SELECT project_id FROM core_problems WHERE id = ?1";
+const PROJECT_BY_PERSONA: &str = "SELECT cp.project_id FROM personas p JOIN core_problems cp ON cp.id = p.core_problem_id WHERE p.id = ?1";
+const PROJECT_BY_SOLUTION: &str = "SELECT project_id FROM key_solutions WHERE id = ?1";
+
+const PROJECT_DOCUMENT_COLUMNS: &str = "id, project_id, document_type, file_path, file_hash, last_modified,
+     content, is_synced, created_at, updated_at";
+
+const DRIFT_REPORT_COLUMNS: &str = "id, project_id, detected_at, affected_documents, drift_details, drift_severity,
+     resolution_status, resolution_method, resolution_metadata, resolved_at, resolved_by";
+
+const GENERATION_JOB_COLUMNS: &str = "id, project_id, document_type, dependencies, status, priority, started_at,
+     completed_at, error_message, retry_count, created_at";
+
+const EXPORT_RECORD_COLUMNS: &str = "id, project_id, export_format, export_config, file_path, file_size_bytes,
+     included_sections, export_version, created_at, created_by";
+
+const CANVAS_VERSION_COLUMNS: &str = "id, project_id, version, parent_version_id, change_description, is_current,
+     save_kind, checkpoint_name, branch_name, COALESCE(json_array_length(nodes), 0),
+     COALESCE(json_array_length(edges), 0), created_at, created_by";
 
 pub struct Queries {
     pool: Pool<SqliteConnectionManager>,
@@ -116,6 +137,20 @@ impl Queries {
         Ok(workspaces)
     }
 
+    /// Folders configured on any workspace; the only places terminals may be opened in
+    pub fn list_workspace_folders(&self) -> Result<Vec<String>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT folder_path FROM workspaces
+             WHERE folder_path IS NOT NULL AND TRIM(folder_path) != ''"
+        )?;
+        
+        let folders = stmt.query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        
+        Ok(folders)
+    }
+
     // Project queries
     pub fn create_project(&self, project: &Project) -> Result<()> {
         let conn = self.pool.get()?;
@@ -159,13 +194,77 @@ impl Queries {
         Ok(project)
     }
 
-    pub fn update_project_step(&self, project_id: &str, step: &str) -> Result<()> {
+    // Progress tracking queries
+    pub fn get_progress_tracking(&self, project_id: &str) -> Result<Vec<ProgressTracking>> {
         let conn = self.pool.get()?;
-        conn.execute(
-            "UPDATE projects SET current_step = ?1 WHERE id = ?2",
-            params![step, project_id],
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, step_name, step_index, is_complete, is_current, completed_at,
+                    ui_state, persona_circles, created_at, updated_at
+             FROM progress_tracking
+             WHERE project_id = ?1
+             ORDER BY step_index"
         )?;
-        Ok(())
+
+        let rows = stmt.query_map(params![project_id], |row| {
+            let ui_state: Option<String> = row.get(7)?;
+            let persona_circles: Option<String> = row.get(8)?;
+            Ok(ProgressTracking {
+                id: row.get(0)?,
+                project_id: row.get(1)?,
+                step_name: row.get(2)?,
+                step_index: row.get(3)?,
+                is_complete: row.get::<_, i32>(4)? == 1,
+                is_current: row.get::<_, i32>(5)? == 1,
+                completed_at: row.get(6)?,
+                ui_state: ui_state.and_then(|s| serde_json::from_str(&s).ok()),
+                persona_circles: persona_circles.and_then(|s| serde_json::from_str(&s).ok()),
+                created_at: row.get(9)?,
+                updated_at: row.get(10)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// Move a project to `to`, updating `current_step` and the per-step
+    /// progress rows in one transaction. Moving forward completes `from`.
+    pub fn record_step_transition(&self, project_id: &str, from: &WorkflowStep, to: &WorkflowStep) -> Result<()> {
+        self.with_transaction(|tx| {
+            for step in WorkflowStep::ALL.iter() {
+                tx.execute(
+                    "INSERT INTO progress_tracking (id, project_id, step_name, step_index)
+                     SELECT ?1, ?2, ?3, ?4
+                     WHERE NOT EXISTS (
+                         SELECT 1 FROM progress_tracking WHERE project_id = ?2 AND step_name = ?3
+                     )",
+                    params![uuid::Uuid::new_v4().to_string(), project_id, step.to_string(), step.index() as i32],
+                )?;
+            }
+
+            if to.index() > from.index() {
+                tx.execute(
+                    "UPDATE progress_tracking
+                     SET is_complete = 1, completed_at = datetime('now'), updated_at = datetime('now')
+                     WHERE project_id = ?1 AND step_name = ?2",
+                    params![project_id, from.to_string()],
+                )?;
+            }
+
+            tx.execute(
+                "UPDATE progress_tracking
+                 SET is_current = CASE WHEN step_name = ?2 THEN 1 ELSE 0 END, updated_at = datetime('now')
+                 WHERE project_id = ?1",
+                params![project_id, to.to_string()],
+            )?;
+
+            tx.execute(
+                "UPDATE projects SET current_step = ?1, updated_at = datetime('now') WHERE id = ?2",
+                params![to.to_string(), project_id],
+            )?;
+
+            Self::invalidate_recent_flows(tx, PROJECT_BY_ID, project_id)?;
+            Ok(())
+        })
     }
 
     pub fn rename_project(&self, project_id: &str, new_name: &str) -> Result<()> {
@@ -174,6 +273,7 @@ impl Queries {
             "UPDATE projects SET name = ?1, updated_at = datetime('now') WHERE id = ?2",
             params![new_name, project_id],
         )?;
+        Self::invalidate_recent_flows(&conn, PROJECT_BY_ID, project_id)?;
         Ok(())
     }
 
@@ -193,6 +293,7 @@ impl Queries {
                 problem.version,
             ],
         )?;
+        Self::invalidate_recent_flows(&conn, PROJECT_BY_ID, &problem.project_id)?;
         Ok(())
     }
 
@@ -243,6 +344,7 @@ impl Queries {
                     persona.is_active as i32,
                     persona.generation_batch,
                 ])?;
+                Self::invalidate_recent_flows(tx, PROJECT_BY_CORE_PROBLEM, &persona.core_problem_id)?;
             }
             Ok(())
         })
@@ -298,6 +400,8 @@ impl Queries {
                 "UPDATE personas SET is_active = 1 WHERE id = ?1",
                 params![persona_id],
             )?;
+
+            Self::invalidate_recent_flows(tx, PROJECT_BY_CORE_PROBLEM, core_problem_id)?;
             Ok(())
         })
     }
@@ -309,6 +413,7 @@ impl Queries {
                 "DELETE FROM personas WHERE core_problem_id = ?1 AND is_locked = 0",
                 params![core_problem_id],
             )?;
+            Self::invalidate_recent_flows(tx, PROJECT_BY_CORE_PROBLEM, core_problem_id)?;
             
             // Insert new personas
             let mut stmt = tx.prepare(
@@ -329,6 +434,7 @@ impl Queries {
                     persona.is_active as i32,
                     persona.generation_batch,
                 ])?;
+                Self::invalidate_recent_flows(tx, PROJECT_BY_CORE_PROBLEM, &persona.core_problem_id)?;
             }
             Ok(())
         })
@@ -353,6 +459,7 @@ impl Queries {
                     pain_point.is_locked as i32,
                     pain_point.generation_batch,
                 ])?;
+                Self::invalidate_recent_flows(tx, PROJECT_BY_PERSONA, &pain_point.persona_id)?;
             }
             Ok(())
         })
@@ -411,6 +518,7 @@ impl Queries {
                     solution.is_selected as i32,
                     solution.generation_batch,
                 ])?;
+                Self::invalidate_recent_flows(tx, PROJECT_BY_ID, &solution.project_id)?;
             }
             
             // Insert mappings
@@ -494,43 +602,364 @@ impl Queries {
             "UPDATE key_solutions SET is_selected = NOT is_selected WHERE id = ?1",
             params![solution_id],
         )?;
+        Self::invalidate_recent_flows(&conn, PROJECT_BY_SOLUTION, solution_id)?;
         Ok(())
     }
 
+    /// Delete personas, pain points and solutions together with the rows that hang off them
+    pub fn delete_domain_entities(
+        &self,
+        project_id: &str,
+        persona_ids: &[String],
+        pain_point_ids: &[String],
+        solution_ids: &[String],
+    ) -> Result<()> {
+        self.with_transaction(|tx| {
+            for persona_id in persona_ids {
+                tx.execute(
+                    "DELETE FROM solution_pain_point_mappings
+                     WHERE pain_point_id IN (SELECT id FROM pain_points WHERE persona_id = ?1)
+                        OR solution_id IN (SELECT id FROM key_solutions WHERE persona_id = ?1)",
+                    params![persona_id],
+                )?;
+                tx.execute("DELETE FROM pain_points WHERE persona_id = ?1", params![persona_id])?;
+                tx.execute("DELETE FROM key_solutions WHERE persona_id = ?1", params![persona_id])?;
+                tx.execute("DELETE FROM personas WHERE id = ?1", params![persona_id])?;
+            }
+            for pain_point_id in pain_point_ids {
+                tx.execute("DELETE FROM solution_pain_point_mappings WHERE pain_point_id = ?1", params![pain_point_id])?;
+                tx.execute("DELETE FROM pain_points WHERE id = ?1", params![pain_point_id])?;
+            }
+            for solution_id in solution_ids {
+                tx.execute("DELETE FROM solution_pain_point_mappings WHERE solution_id = ?1", params![solution_id])?;
+                tx.execute(
+                    "DELETE FROM key_solutions WHERE id = ?1 AND project_id = ?2",
+                    params![solution_id, project_id],
+                )?;
+            }
+            Self::invalidate_recent_flows(tx, PROJECT_BY_ID, project_id)?;
+            Ok(())
+        })
+    }
+
     // Canvas state queries
     pub fn save_canvas_state(&self, canvas_state: &CanvasState) -> Result<()> {
+        self.save_canvas_version(canvas_state, CanvasSaveKind::Manual, None)?;
+        Ok(())
+    }
+
+    /// Store `canvas_state` as a new version on top of the current one and make it current.
+    /// An autosave identical to the current version is dropped and the current version returned.
+    pub fn save_canvas_version(
+        &self,
+        canvas_state: &CanvasState,
+        kind: CanvasSaveKind,
+        change_description: Option<&str>,
+    ) -> Result<CanvasVersion> {
+        let id = self.with_transaction(|tx| {
+            let head = Self::current_canvas_state(tx, &canvas_state.project_id)?;
+
+            if let Some((head, _)) = &head {
+                // Viewport churn is UI state (react_flow_states), not a new business version
+                let unchanged = head.nodes == canvas_state.nodes && head.edges == canvas_state.edges;
+                if kind == CanvasSaveKind::Autosave && unchanged {
+                    return Ok(head.id.clone());
+                }
+            }
+
+            let (parent_id, branch) = match head {
+                Some((head, branch)) => (Some(head.id), branch),
+                None => (None, "main".to_string()),
+            };
+            Self::insert_canvas_version(
+                tx,
+                canvas_state,
+                parent_id.as_deref(),
+                &branch,
+                kind,
+                change_description,
+            )?;
+            Ok(canvas_state.id.clone())
+        })?;
+
+        self.get_canvas_version(&id)?
+            .ok_or_else(|| anyhow::anyhow!("Canvas version {} was not saved", id))
+    }
+
+    pub fn get_latest_canvas_state(&self, project_id: &str) -> Result<Option<CanvasState>> {
+        let conn = self.pool.get()?;
+        Ok(Self::current_canvas_state(&conn, project_id)?.map(|(state, _)| state))
+    }
+
+    pub fn get_canvas_state(&self, id: &str) -> Result<Option<CanvasState>> {
+        let conn = self.pool.get()?;
+        let canvas_state = conn.query_row(
+            "SELECT id, project_id, nodes, edges, viewport, created_at, updated_at, branch_name
+             FROM canvas_states WHERE id = ?1",
+            params![id],
+            Self::canvas_state_from_row,
+        ).optional()?;
+        Ok(canvas_state.map(|(state, _)| state))
+    }
+
+    pub fn get_canvas_version(&self, id: &str) -> Result<Option<CanvasVersion>> {
+        let conn = self.pool.get()?;
+        let version = conn.query_row(
+            &format!("SELECT {} FROM canvas_states WHERE id = ?1", CANVAS_VERSION_COLUMNS),
+            params![id],
+            Self::canvas_version_from_row,
+        ).optional()?;
+        Ok(version)
+    }
+
+    /// Every saved version of a project's canvas, newest first
+    pub fn get_canvas_history(&self, project_id: &str, branch_name: Option<&str>) -> Result<Vec<CanvasVersion>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM canvas_states
+             WHERE project_id = ?1 AND (?2 IS NULL OR branch_name = ?2)
+             ORDER BY version DESC",
+            CANVAS_VERSION_COLUMNS
+        ))?;
+
+        let versions = stmt.query_map(params![project_id, branch_name], Self::canvas_version_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(versions)
+    }
+
+    /// The newest version saved at or before `at`
+    pub fn get_canvas_version_at(&self, project_id: &str, at: chrono::DateTime<chrono::Utc>) -> Result<Option<CanvasVersion>> {
         let conn = self.pool.get()?;
+        let version = conn.query_row(
+            &format!(
+                "SELECT {} FROM canvas_states
+                 WHERE project_id = ?1 AND datetime(created_at) <= datetime(?2)
+                 ORDER BY version DESC
+                 LIMIT 1",
+                CANVAS_VERSION_COLUMNS
+            ),
+            params![project_id, at.to_rfc3339()],
+            Self::canvas_version_from_row,
+        ).optional()?;
+        Ok(version)
+    }
+
+    /// Copy an older version's content into a new current version on the current branch
+    pub fn restore_canvas_version(&self, project_id: &str, version_id: &str) -> Result<CanvasVersion> {
+        let id = self.with_transaction(|tx| {
+            let (target, target_version) = Self::canvas_version_in_project(tx, project_id, version_id)?;
+            let (parent_id, branch) = match Self::current_canvas_state(tx, project_id)? {
+                Some((head, branch)) => (Some(head.id), branch),
+                None => (None, "main".to_string()),
+            };
+
+            let restored = CanvasState { id: uuid::Uuid::new_v4().to_string(), ..target };
+            Self::insert_canvas_version(
+                tx,
+                &restored,
+                parent_id.as_deref(),
+                &branch,
+                CanvasSaveKind::Restore,
+                Some(&format!("Restored version {}", target_version)),
+            )?;
+            Ok(restored.id)
+        })?;
+
+        self.get_canvas_version(&id)?
+            .ok_or_else(|| anyhow::anyhow!("Canvas version {} was not saved", id))
+    }
+
+    /// Start a new branch whose first version is a copy of `version_id`
+    pub fn branch_canvas_version(
+        &self,
+        project_id: &str,
+        version_id: &str,
+        branch_name: &str,
+        change_description: Option<&str>,
+    ) -> Result<CanvasVersion> {
+        let id = self.with_transaction(|tx| {
+            let exists: bool = tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM canvas_states WHERE project_id = ?1 AND branch_name = ?2)",
+                params![project_id, branch_name],
+                |row| row.get(0),
+            )?;
+            if exists {
+                return Err(anyhow::anyhow!("Canvas branch '{}' already exists", branch_name));
+            }
+
+            let (source, source_version) = Self::canvas_version_in_project(tx, project_id, version_id)?;
+            let branched = CanvasState { id: uuid::Uuid::new_v4().to_string(), ..source };
+            let description = change_description
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("Branched from version {}", source_version));
+            Self::insert_canvas_version(
+                tx,
+                &branched,
+                Some(version_id),
+                branch_name,
+                CanvasSaveKind::Branch,
+                Some(&description),
+            )?;
+            Ok(branched.id)
+        })?;
+
+        self.get_canvas_version(&id)?
+            .ok_or_else(|| anyhow::anyhow!("Canvas version {} was not saved", id))
+    }
+
+    /// Name the current version so it is easy to find and never squashed
+    pub fn create_canvas_checkpoint(
+        &self,
+        project_id: &str,
+        name: &str,
+        change_description: Option<&str>,
+    ) -> Result<CanvasVersion> {
+        let id = self.with_transaction(|tx| {
+            let taken: bool = tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM canvas_states WHERE project_id = ?1 AND checkpoint_name = ?2)",
+                params![project_id, name],
+                |row| row.get(0),
+            )?;
+            if taken {
+                return Err(anyhow::anyhow!("Checkpoint '{}' already exists", name));
+            }
+
+            let (head, _) = Self::current_canvas_state(tx, project_id)?
+                .ok_or_else(|| anyhow::anyhow!("Project has no saved canvas"))?;
+            tx.execute(
+                "UPDATE canvas_states
+                 SET checkpoint_name = ?2, change_description = COALESCE(?3, change_description)
+                 WHERE id = ?1",
+                params![head.id, name, change_description],
+            )?;
+            Ok(head.id)
+        })?;
+
+        self.get_canvas_version(&id)?
+            .ok_or_else(|| anyhow::anyhow!("Canvas version {} not found", id))
+    }
+
+    /// Collapse runs of autosaves older than `older_than_days` into the last save of each run.
+    /// Checkpoints, the current version and branch points are kept. Returns the number removed.
+    pub fn squash_canvas_autosaves(&self, project_id: &str, older_than_days: u32) -> Result<usize> {
+        self.with_transaction(|tx| {
+            let mut stmt = tx.prepare(
+                "SELECT id FROM canvas_states
+                 WHERE project_id = ?1 AND save_kind = 'autosave' AND checkpoint_name IS NULL
+                   AND is_current = 0 AND created_at < datetime('now', ?2)
+                 ORDER BY version ASC",
+            )?;
+            let candidates = stmt.query_map(
+                params![project_id, format!("-{} days", older_than_days)],
+                |row| row.get::<_, String>(0),
+            )?.collect::<Result<Vec<_>, _>>()?;
+            let squashable: std::collections::HashSet<&str> = candidates.iter().map(String::as_str).collect();
+
+            let mut removed = 0;
+            for id in &candidates {
+                // Read the parent now; an earlier squash in this run may have re-pointed it
+                let parent_id: Option<String> = tx.query_row(
+                    "SELECT parent_version_id FROM canvas_states WHERE id = ?1",
+                    params![id],
+                    |row| row.get(0),
+                )?;
+                let mut children = tx.prepare("SELECT id FROM canvas_states WHERE parent_version_id = ?1")?;
+                let children = children.query_map(params![id], |row| row.get::<_, String>(0))?
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                // Only drop a save whose single successor is another squashable autosave
+                if let [child] = children.as_slice() {
+                    if squashable.contains(child.as_str()) {
+                        tx.execute(
+                            "UPDATE canvas_states SET parent_version_id = ?2 WHERE id = ?1",
+                            params![child, parent_id],
+                        )?;
+                        tx.execute("DELETE FROM canvas_states WHERE id = ?1", params![id])?;
+                        removed += 1;
+                    }
+                }
+            }
+            Ok(removed)
+        })
+    }
+
+    fn insert_canvas_version(
+        conn: &rusqlite::Connection,
+        canvas_state: &CanvasState,
+        parent_id: Option<&str>,
+        branch_name: &str,
+        kind: CanvasSaveKind,
+        change_description: Option<&str>,
+    ) -> Result<()> {
+        let version: i32 = conn.query_row(
+            "SELECT COALESCE(MAX(version), 0) + 1 FROM canvas_states WHERE project_id = ?1",
+            params![canvas_state.project_id],
+            |row| row.get(0),
+        )?;
+
         conn.execute(
-            "INSERT OR REPLACE INTO canvas_states 
-             (id, project_id, nodes, edges, viewport) 
-             VALUES (?1, ?2, ?3, ?4, ?5)",
+            "UPDATE canvas_states SET is_current = 0 WHERE project_id = ?1 AND is_current = 1",
+            params![canvas_state.project_id],
+        )?;
+        conn.execute(
+            "INSERT INTO canvas_states
+             (id, project_id, nodes, edges, viewport, workflow_step, version, parent_version_id,
+              change_description, is_current, save_kind, branch_name)
+             VALUES (?1, ?2, ?3, ?4, ?5,
+                     COALESCE((SELECT current_step FROM projects WHERE id = ?2), 'problem_input'),
+                     ?6, ?7, ?8, 1, ?9, ?10)",
             params![
                 canvas_state.id,
                 canvas_state.project_id,
                 canvas_state.nodes.to_string(),
                 canvas_state.edges.to_string(),
                 canvas_state.viewport.as_ref().map(|v| v.to_string()),
+                version,
+                parent_id,
+                change_description,
+                kind.as_str(),
+                branch_name,
             ],
         )?;
         Ok(())
     }
 
-    pub fn get_latest_canvas_state(&self, project_id: &str) -> Result<Option<CanvasState>> {
-        let conn = self.pool.get()?;
-        let mut stmt = conn.prepare(
-            "SELECT id, project_id, nodes, edges, viewport, created_at, updated_at 
-             FROM canvas_states 
-             WHERE project_id = ?1 
-             ORDER BY updated_at DESC 
-             LIMIT 1"
-        )?;
-        
-        let canvas_state = stmt.query_row(params![project_id], |row| {
-            let nodes_str: String = row.get(2)?;
-            let edges_str: String = row.get(3)?;
-            let viewport_str: Option<String> = row.get(4)?;
-            
-            Ok(CanvasState {
+    /// The current version with its branch; older rows written before versioning may have
+    /// no current flag, in which case the highest version wins
+    fn current_canvas_state(conn: &rusqlite::Connection, project_id: &str) -> Result<Option<(CanvasState, String)>> {
+        let canvas_state = conn.query_row(
+            "SELECT id, project_id, nodes, edges, viewport, created_at, updated_at, branch_name
+             FROM canvas_states
+             WHERE project_id = ?1
+             ORDER BY is_current DESC, version DESC, updated_at DESC
+             LIMIT 1",
+            params![project_id],
+            Self::canvas_state_from_row,
+        ).optional()?;
+        Ok(canvas_state)
+    }
+
+    fn canvas_version_in_project(
+        conn: &rusqlite::Connection,
+        project_id: &str,
+        version_id: &str,
+    ) -> Result<(CanvasState, i32)> {
+        let found = conn.query_row(
+            "SELECT id, project_id, nodes, edges, viewport, created_at, updated_at, branch_name, version
+             FROM canvas_states WHERE id = ?1 AND project_id = ?2",
+            params![version_id, project_id],
+            |row| Ok((Self::canvas_state_from_row(row)?.0, row.get(8)?)),
+        ).optional()?;
+        found.ok_or_else(|| anyhow::anyhow!("Canvas version {} not found in project", version_id))
+    }
+
+    fn canvas_state_from_row(row: &rusqlite::Row) -> rusqlite::Result<(CanvasState, String)> {
+        let nodes_str: String = row.get(2)?;
+        let edges_str: String = row.get(3)?;
+        let viewport_str: Option<String> = row.get(4)?;
+
+        Ok((
+            CanvasState {
                 id: row.get(0)?,
                 project_id: row.get(1)?,
                 nodes: serde_json::from_str(&nodes_str).unwrap_or(serde_json::Value::Null),
@@ -538,10 +967,27 @@ impl Queries {
                 viewport: viewport_str.and_then(|s| serde_json::from_str(&s).ok()),
                 created_at: row.get(5)?,
                 updated_at: row.get(6)?,
-            })
-        }).optional()?;
-        
-        Ok(canvas_state)
+            },
+            row.get(7)?,
+        ))
+    }
+
+    fn canvas_version_from_row(row: &rusqlite::Row) -> rusqlite::Result<CanvasVersion> {
+        Ok(CanvasVersion {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            version: row.get(2)?,
+            parent_version_id: row.get(3)?,
+            change_description: row.get(4)?,
+            is_current: row.get::<_, i32>(5)? != 0,
+            save_kind: row.get(6)?,
+            checkpoint_name: row.get(7)?,
+            branch_name: row.get(8)?,
+            node_count: row.get(9)?,
+            edge_count: row.get(10)?,
+            created_at: row.get(11)?,
+            created_by: row.get(12)?,
+        })
     }
 
     // Event sourcing queries
@@ -715,6 +1161,7 @@ impl Queries {
                     story.original_content,
                     story.edited_content,
                 ])?;
+                Self::invalidate_recent_flows(tx, PROJECT_BY_ID, &story.project_id)?;
             }
             Ok(())
         })
@@ -754,6 +1201,1195 @@ impl Queries {
         Ok(stories)
     }
 
+    pub fn update_user_story(&self, story: &UserStory) -> Result<()> {
+        let conn = self.pool.get()?;
+        let criteria_json = serde_json::to_string(&story.acceptance_criteria)?;
+        conn.execute(
+            "UPDATE user_stories
+             SET title = ?1, as_a = ?2, i_want = ?3, so_that = ?4, acceptance_criteria = ?5,
+                 is_edited = ?6, edited_content = ?7
+             WHERE id = ?8",
+            params![
+                story.title,
+                story.as_a,
+                story.i_want,
+                story.so_that,
+                criteria_json,
+                story.is_edited as i32,
+                story.edited_content,
+                story.id,
+            ],
+        )?;
+        Self::invalidate_recent_flows(&conn, PROJECT_BY_ID, &story.project_id)?;
+        Ok(())
+    }
+
+    // System design queries
+    pub fn get_latest_architecture_version(&self, project_id: &str) -> Result<i32> {
+        let conn = self.pool.get()?;
+        let version: i32 = conn.query_row(
+            "SELECT COALESCE(MAX(version), 0) FROM system_architecture WHERE project_id = ?1",
+            params![project_id],
+            |row| row.get(0),
+        )?;
+        Ok(version)
+    }
+
+    /// Store a generated system design. Architecture rows are appended under a new
+    /// version; tables, relationships and data flows are replaced wholesale.
+    pub fn save_system_design(
+        &self,
+        project_id: &str,
+        architecture: &[SystemArchitecture],
+        tables: &[(DatabaseTable, Vec<DatabaseColumn>)],
+        relationships: &[DatabaseRelationship],
+        data_flows: &[(DataFlow, Vec<DataFlowStep>)],
+    ) -> Result<()> {
+        self.with_transaction(|tx| {
+            // Clear the previous schema and flows (children first, foreign keys are not enforced per connection)
+            tx.execute(
+                "DELETE FROM database_columns WHERE table_id IN (SELECT id FROM database_tables WHERE project_id = ?1)",
+                params![project_id],
+            )?;
+            tx.execute("DELETE FROM database_tables WHERE project_id = ?1", params![project_id])?;
+            tx.execute("DELETE FROM database_relationships WHERE project_id = ?1", params![project_id])?;
+            tx.execute(
+                "DELETE FROM data_flow_steps WHERE data_flow_id IN (
+                    SELECT df.id FROM data_flows df
+                    JOIN user_stories us ON us.id = df.user_story_id
+                    WHERE us.project_id = ?1)",
+                params![project_id],
+            )?;
+            tx.execute(
+                "DELETE FROM data_flows WHERE user_story_id IN (SELECT id FROM user_stories WHERE project_id = ?1)",
+                params![project_id],
+            )?;
+
+            let mut arch_stmt = tx.prepare(
+                "INSERT INTO system_architecture (id, project_id, layer, technology, justification, version)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)"
+            )?;
+            for layer in architecture {
+                arch_stmt.execute(params![
+                    layer.id,
+                    layer.project_id,
+                    layer.layer,
+                    layer.technology,
+                    layer.justification,
+                    layer.version,
+                ])?;
+            }
+
+            let mut table_stmt = tx.prepare(
+                "INSERT INTO database_tables (id, project_id, table_name) VALUES (?1, ?2, ?3)"
+            )?;
+            let mut column_stmt = tx.prepare(
+                "INSERT INTO database_columns (id, table_id, column_name, data_type, is_primary_key, is_foreign_key, references_table, constraints)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"
+            )?;
+            for (table, columns) in tables {
+                table_stmt.execute(params![table.id, table.project_id, table.table_name])?;
+                for column in columns {
+                    column_stmt.execute(params![
+                        column.id,
+                        column.table_id,
+                        column.column_name,
+                        column.data_type,
+                        column.is_primary_key as i32,
+                        column.is_foreign_key as i32,
+                        column.references_table,
+                        serde_json::to_string(&column.constraints)?,
+                    ])?;
+                }
+            }
+
+            let mut relationship_stmt = tx.prepare(
+                "INSERT INTO database_relationships (id, project_id, from_table, to_table, relationship_type)
+                 VALUES (?1, ?2, ?3, ?4, ?5)"
+            )?;
+            for relationship in relationships {
+                relationship_stmt.execute(params![
+                    relationship.id,
+                    relationship.project_id,
+                    relationship.from_table,
+                    relationship.to_table,
+                    relationship.relationship_type,
+                ])?;
+            }
+
+            let mut flow_stmt = tx.prepare(
+                "INSERT INTO data_flows (id, user_story_id, description) VALUES (?1, ?2, ?3)"
+            )?;
+            let mut step_stmt = tx.prepare(
+                "INSERT INTO data_flow_steps (id, data_flow_id, step_number, action, source, target, data_payload)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"
+            )?;
+            for (flow, steps) in data_flows {
+                flow_stmt.execute(params![flow.id, flow.user_story_id, flow.description])?;
+                for step in steps {
+                    step_stmt.execute(params![
+                        step.id,
+                        step.data_flow_id,
+                        step.step_number,
+                        step.action,
+                        step.source,
+                        step.target,
+                        step.data_payload,
+                    ])?;
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Architecture layers for a version, or the latest version when `version` is None
+    pub fn get_system_architecture(&self, project_id: &str, version: Option<i32>) -> Result<Vec<SystemArchitecture>> {
+        let version = match version {
+            Some(version) => version,
+            None => self.get_latest_architecture_version(project_id)?,
+        };
+
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, layer, technology, justification, version, created_at
+             FROM system_architecture
+             WHERE project_id = ?1 AND version = ?2
+             ORDER BY rowid"
+        )?;
+
+        let layers = stmt.query_map(params![project_id, version], |row| {
+            Ok(SystemArchitecture {
+                id: row.get(0)?,
+                project_id: row.get(1)?,
+                layer: row.get(2)?,
+                technology: row.get(3)?,
+                justification: row.get(4)?,
+                version: row.get(5)?,
+                created_at: row.get(6)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(layers)
+    }
+
+    pub fn get_database_tables(&self, project_id: &str) -> Result<Vec<(DatabaseTable, Vec<DatabaseColumn>)>> {
+        let conn = self.pool.get()?;
+        let mut table_stmt = conn.prepare(
+            "SELECT id, project_id, table_name, created_at
+             FROM database_tables
+             WHERE project_id = ?1
+             ORDER BY rowid"
+        )?;
+
+        let tables: Vec<DatabaseTable> = table_stmt.query_map(params![project_id], |row| {
+            Ok(DatabaseTable {
+                id: row.get(0)?,
+                project_id: row.get(1)?,
+                table_name: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        let mut column_stmt = conn.prepare(
+            "SELECT id, table_id, column_name, data_type, is_primary_key, is_foreign_key, references_table, constraints, created_at
+             FROM database_columns
+             WHERE table_id = ?1
+             ORDER BY rowid"
+        )?;
+
+        let mut result = Vec::new();
+        for table in tables {
+            let columns: Vec<DatabaseColumn> = column_stmt.query_map(params![&table.id], |row| {
+                let constraints_json: Option<String> = row.get(7)?;
+                Ok(DatabaseColumn {
+                    id: row.get(0)?,
+                    table_id: row.get(1)?,
+                    column_name: row.get(2)?,
+                    data_type: row.get(3)?,
+                    is_primary_key: row.get::<_, i32>(4)? == 1,
+                    is_foreign_key: row.get::<_, i32>(5)? == 1,
+                    references_table: row.get(6)?,
+                    constraints: constraints_json
+                        .and_then(|s| serde_json::from_str(&s).ok())
+                        .unwrap_or_default(),
+                    created_at: row.get(8)?,
+                })
+            })?.collect::<Result<Vec<_>, _>>()?;
+
+            result.push((table, columns));
+        }
+
+        Ok(result)
+    }
+
+    pub fn get_database_relationships(&self, project_id: &str) -> Result<Vec<DatabaseRelationship>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, from_table, to_table, relationship_type, created_at
+             FROM database_relationships
+             WHERE project_id = ?1
+             ORDER BY rowid"
+        )?;
+
+        let relationships = stmt.query_map(params![project_id], |row| {
+            Ok(DatabaseRelationship {
+                id: row.get(0)?,
+                project_id: row.get(1)?,
+                from_table: row.get(2)?,
+                to_table: row.get(3)?,
+                relationship_type: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(relationships)
+    }
+
+    pub fn get_data_flows(&self, project_id: &str) -> Result<Vec<(DataFlow, Vec<DataFlowStep>)>> {
+        let conn = self.pool.get()?;
+        let mut flow_stmt = conn.prepare(
+            "SELECT df.id, df.user_story_id, df.description, df.created_at
+             FROM data_flows df
+             JOIN user_stories us ON us.id = df.user_story_id
+             WHERE us.project_id = ?1
+             ORDER BY us.position"
+        )?;
+
+        let flows: Vec<DataFlow> = flow_stmt.query_map(params![project_id], |row| {
+            Ok(DataFlow {
+                id: row.get(0)?,
+                user_story_id: row.get(1)?,
+                description: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        let mut step_stmt = conn.prepare(
+            "SELECT id, data_flow_id, step_number, action, source, target, data_payload, created_at
+             FROM data_flow_steps
+             WHERE data_flow_id = ?1
+             ORDER BY step_number"
+        )?;
+
+        let mut result = Vec::new();
+        for flow in flows {
+            let steps: Vec<DataFlowStep> = step_stmt.query_map(params![&flow.id], |row| {
+                Ok(DataFlowStep {
+                    id: row.get(0)?,
+                    data_flow_id: row.get(1)?,
+                    step_number: row.get(2)?,
+                    action: row.get(3)?,
+                    source: row.get(4)?,
+                    target: row.get(5)?,
+                    data_payload: row.get(6)?,
+                    created_at: row.get(7)?,
+                })
+            })?.collect::<Result<Vec<_>, _>>()?;
+
+            result.push((flow, steps));
+        }
+
+        Ok(result)
+    }
+
+    // UI & design system queries
+    pub fn get_ui_screens(&self, project_id: &str) -> Result<Vec<UIScreen>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, screen_name, description, route_path, created_at
+             FROM ui_screens
+             WHERE project_id = ?1
+             ORDER BY rowid"
+        )?;
+
+        let screens = stmt.query_map(params![project_id], |row| {
+            Ok(UIScreen {
+                id: row.get(0)?,
+                project_id: row.get(1)?,
+                screen_name: row.get(2)?,
+                description: row.get(3)?,
+                route_path: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(screens)
+    }
+
+    /// Components of every screen in the project, grouped by screen in screen order
+    pub fn get_ui_components(&self, project_id: &str) -> Result<Vec<UIComponent>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT c.id, c.screen_id, c.component_name, c.component_type, c.data_displayed, c.props, c.created_at
+             FROM ui_components c
+             JOIN ui_screens s ON s.id = c.screen_id
+             WHERE s.project_id = ?1
+             ORDER BY s.rowid, c.rowid"
+        )?;
+
+        let components = stmt.query_map(params![project_id], |row| {
+            let props_json: Option<String> = row.get(5)?;
+            Ok(UIComponent {
+                id: row.get(0)?,
+                screen_id: row.get(1)?,
+                component_name: row.get(2)?,
+                component_type: row.get(3)?,
+                data_displayed: row.get(4)?,
+                props: props_json.and_then(|s| serde_json::from_str(&s).ok()),
+                created_at: row.get(6)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(components)
+    }
+
+    pub fn get_design_tokens(&self, project_id: &str) -> Result<Vec<DesignToken>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, token_category, token_name, token_value, created_at
+             FROM design_tokens
+             WHERE project_id = ?1
+             ORDER BY token_category, rowid"
+        )?;
+
+        let tokens = stmt.query_map(params![project_id], |row| {
+            Ok(DesignToken {
+                id: row.get(0)?,
+                project_id: row.get(1)?,
+                token_category: row.get(2)?,
+                token_name: row.get(3)?,
+                token_value: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(tokens)
+    }
+
+    pub fn get_atomic_components(&self, project_id: &str) -> Result<Vec<AtomicComponent>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, component_level, component_name, description, props, composed_of, created_at
+             FROM atomic_components
+             WHERE project_id = ?1
+             ORDER BY rowid"
+        )?;
+
+        let components = stmt.query_map(params![project_id], |row| {
+            let props_json: Option<String> = row.get(5)?;
+            let composed_of_json: Option<String> = row.get(6)?;
+            Ok(AtomicComponent {
+                id: row.get(0)?,
+                project_id: row.get(1)?,
+                component_level: row.get(2)?,
+                component_name: row.get(3)?,
+                description: row.get(4)?,
+                props: props_json.and_then(|s| serde_json::from_str(&s).ok()),
+                composed_of: composed_of_json
+                    .and_then(|s| serde_json::from_str(&s).ok())
+                    .unwrap_or_default(),
+                created_at: row.get(7)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(components)
+    }
+
+    // Recent flows queries
+    pub fn list_projects(&self, workspace_id: Option<&str>) -> Result<Vec<Project>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, workspace_id, name, status, current_step, langgraph_state, created_at, updated_at
+             FROM projects
+             WHERE ?1 IS NULL OR workspace_id = ?1
+             ORDER BY updated_at DESC"
+        )?;
+
+        let projects = stmt.query_map(params![workspace_id], |row| {
+            let langgraph_state_str: Option<String> = row.get(5)?;
+            Ok(Project {
+                id: row.get(0)?,
+                workspace_id: row.get(1)?,
+                name: row.get(2)?,
+                status: row.get(3)?,
+                current_step: row.get(4)?,
+                langgraph_state: langgraph_state_str
+                    .and_then(|s| serde_json::from_str(&s).ok()),
+                created_at: row.get(6)?,
+                updated_at: row.get(7)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(projects)
+    }
+
+    pub fn update_project_status(&self, project_id: &str, status: &str) -> Result<()> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "UPDATE projects SET status = ?1 WHERE id = ?2",
+            params![status, project_id],
+        )?;
+        Self::invalidate_recent_flows(&conn, PROJECT_BY_ID, project_id)?;
+        Ok(())
+    }
+
+    /// Latest of the project's own update time and its newest state event
+    pub fn get_project_last_activity(&self, project_id: &str) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        let conn = self.pool.get()?;
+        let last_activity = conn.query_row(
+            "SELECT MAX(activity) FROM (
+                 SELECT updated_at AS activity FROM projects WHERE id = ?1
+                 UNION ALL
+                 SELECT MAX(created_at) FROM langgraph_state_events WHERE project_id = ?1
+             )",
+            params![project_id],
+            |row| row.get(0),
+        )?;
+        Ok(last_activity)
+    }
+
+    pub fn get_project_setting(&self, project_id: &str, key: &str) -> Result<Option<String>> {
+        let conn = self.pool.get()?;
+        let value = conn.query_row(
+            "SELECT setting_value FROM project_settings
+             WHERE project_id = ?1 AND setting_key = ?2
+             ORDER BY updated_at DESC
+             LIMIT 1",
+            params![project_id, key],
+            |row| row.get(0),
+        ).optional()?;
+        Ok(value.flatten())
+    }
+
+    pub fn set_project_setting(&self, project_id: &str, key: &str, value: &str) -> Result<()> {
+        self.with_transaction(|tx| {
+            let updated = tx.execute(
+                "UPDATE project_settings SET setting_value = ?3, updated_at = datetime('now')
+                 WHERE project_id = ?1 AND setting_key = ?2",
+                params![project_id, key, value],
+            )?;
+            if updated == 0 {
+                tx.execute(
+                    "INSERT INTO project_settings (id, project_id, setting_key, setting_value)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![uuid::Uuid::new_v4().to_string(), project_id, key, value],
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Cached summary for a project, ignoring expired rows
+    pub fn get_recent_flow_cache(&self, project_id: &str) -> Result<Option<RecentFlowCache>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, user_id, project_id, problem_statement, last_activity, progress_segments,
+                    completion_percentage, cache_key, expires_at, created_at, updated_at
+             FROM recent_flows_cache
+             WHERE project_id = ?1 AND (expires_at IS NULL OR expires_at > datetime('now'))"
+        )?;
+
+        let cache = stmt.query_row(params![project_id], |row| {
+            let segments: String = row.get(5)?;
+            Ok(RecentFlowCache {
+                id: row.get(0)?,
+                user_id: row.get(1)?,
+                project_id: row.get(2)?,
+                problem_statement: row.get(3)?,
+                last_activity: row.get(4)?,
+                progress_segments: serde_json::from_str(&segments).unwrap_or(serde_json::Value::Null),
+                completion_percentage: row.get::<_, Option<i32>>(6)?.unwrap_or(0),
+                cache_key: row.get(7)?,
+                expires_at: row.get(8)?,
+                created_at: row.get(9)?,
+                updated_at: row.get(10)?,
+            })
+        }).optional()?;
+
+        Ok(cache)
+    }
+
+    pub fn save_recent_flow_cache(&self, cache: &RecentFlowCache, ttl_seconds: i64) -> Result<()> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "INSERT INTO recent_flows_cache (id, user_id, project_id, problem_statement, last_activity,
+                                             progress_segments, completion_percentage, cache_key, expires_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, datetime('now', ?9))
+             ON CONFLICT(cache_key) DO UPDATE SET
+                 user_id = excluded.user_id,
+                 problem_statement = excluded.problem_statement,
+                 last_activity = excluded.last_activity,
+                 progress_segments = excluded.progress_segments,
+                 completion_percentage = excluded.completion_percentage,
+                 expires_at = excluded.expires_at,
+                 updated_at = datetime('now')",
+            params![
+                cache.id,
+                cache.user_id,
+                cache.project_id,
+                cache.problem_statement,
+                cache.last_activity,
+                cache.progress_segments.to_string(),
+                cache.completion_percentage,
+                cache.cache_key,
+                format!("+{} seconds", ttl_seconds),
+            ],
+        )?;
+        Ok(())
+    }
+
+    // Project document queries
+    pub fn get_project_documents(&self, project_id: &str) -> Result<Vec<ProjectDocument>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM project_documents WHERE project_id = ?1 ORDER BY document_type",
+            PROJECT_DOCUMENT_COLUMNS
+        ))?;
+        
+        let documents = stmt.query_map(params![project_id], Self::project_document_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(documents)
+    }
+
+    pub fn get_project_document(&self, project_id: &str, document_type: &str) -> Result<Option<ProjectDocument>> {
+        let conn = self.pool.get()?;
+        let document = conn.query_row(
+            &format!(
+                "SELECT {} FROM project_documents WHERE project_id = ?1 AND document_type = ?2",
+                PROJECT_DOCUMENT_COLUMNS
+            ),
+            params![project_id, document_type],
+            Self::project_document_from_row,
+        ).optional()?;
+        Ok(document)
+    }
+
+    pub fn get_project_document_by_path(&self, file_path: &str) -> Result<Option<ProjectDocument>> {
+        let conn = self.pool.get()?;
+        let document = conn.query_row(
+            &format!("SELECT {} FROM project_documents WHERE file_path = ?1", PROJECT_DOCUMENT_COLUMNS),
+            params![file_path],
+            Self::project_document_from_row,
+        ).optional()?;
+        Ok(document)
+    }
+
+    /// One row per project and document type; writing again replaces it
+    pub fn save_project_document(&self, document: &ProjectDocument) -> Result<()> {
+        let last_modified = document.last_modified.map(|t| t.to_rfc3339());
+        let is_synced = document.is_synced as i32;
+
+        self.with_transaction(|tx| {
+            let values = params![
+                document.id,
+                document.project_id,
+                document.document_type,
+                document.file_path,
+                document.file_hash,
+                last_modified,
+                document.content,
+                is_synced,
+            ];
+            let updated = tx.execute(
+                "UPDATE project_documents
+                 SET file_path = ?4, file_hash = ?5, last_modified = ?6, content = ?7, is_synced = ?8,
+                     updated_at = datetime('now')
+                 WHERE project_id = ?2 AND document_type = ?3",
+                values,
+            )?;
+            if updated == 0 {
+                tx.execute(
+                    "INSERT INTO project_documents
+                     (id, project_id, document_type, file_path, file_hash, last_modified, content, is_synced)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    values,
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Record that the file on disk changed, without touching what was generated
+    pub fn mark_project_document_disk_state(&self, id: &str, last_modified: chrono::DateTime<chrono::Utc>, is_synced: bool) -> Result<()> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "UPDATE project_documents
+             SET last_modified = ?2, is_synced = ?3, updated_at = datetime('now')
+             WHERE id = ?1",
+            params![id, last_modified.to_rfc3339(), is_synced as i32],
+        )?;
+        Ok(())
+    }
+
+    /// Store a new pending report; older pending reports for the project are superseded by it
+    pub fn create_drift_report(&self, report: &DocumentDriftReport) -> Result<()> {
+        let affected_documents = serde_json::to_string(&report.affected_documents)?;
+        let drift_details = report.drift_details.to_string();
+        let superseded = serde_json::json!({ "superseded_by": report.id }).to_string();
+
+        self.with_transaction(|tx| {
+            tx.execute(
+                "UPDATE document_drift_reports
+                 SET resolution_status = 'ignored', resolution_metadata = ?2, resolved_at = datetime('now')
+                 WHERE project_id = ?1 AND resolution_status = 'pending'",
+                params![report.project_id, superseded],
+            )?;
+            tx.execute(
+                "INSERT INTO document_drift_reports
+                 (id, project_id, detected_at, affected_documents, drift_details, drift_severity, resolution_status)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    report.id,
+                    report.project_id,
+                    report.detected_at.to_rfc3339(),
+                    affected_documents,
+                    drift_details,
+                    report.drift_severity,
+                    report.resolution_status,
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    pub fn get_drift_report(&self, id: &str) -> Result<Option<DocumentDriftReport>> {
+        let conn = self.pool.get()?;
+        let report = conn.query_row(
+            &format!("SELECT {} FROM document_drift_reports WHERE id = ?1", DRIFT_REPORT_COLUMNS),
+            params![id],
+            Self::drift_report_from_row,
+        ).optional()?;
+        Ok(report)
+    }
+
+    pub fn get_drift_reports(&self, project_id: &str, status: Option<&str>) -> Result<Vec<DocumentDriftReport>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM document_drift_reports
+             WHERE project_id = ?1 AND (?2 IS NULL OR resolution_status = ?2)
+             ORDER BY detected_at DESC",
+            DRIFT_REPORT_COLUMNS
+        ))?;
+        
+        let reports = stmt.query_map(params![project_id, status], Self::drift_report_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(reports)
+    }
+
+    pub fn resolve_drift_report(&self, id: &str, method: &str, metadata: &serde_json::Value, resolved_by: Option<&str>) -> Result<()> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "UPDATE document_drift_reports
+             SET resolution_status = 'resolved', resolution_method = ?2, resolution_metadata = ?3,
+                 resolved_at = datetime('now'), resolved_by = ?4
+             WHERE id = ?1",
+            params![id, method, metadata.to_string(), resolved_by],
+        )?;
+        Ok(())
+    }
+
+    fn drift_report_from_row(row: &rusqlite::Row) -> rusqlite::Result<DocumentDriftReport> {
+        let affected: String = row.get(3)?;
+        let details: String = row.get(4)?;
+        let metadata: Option<String> = row.get(8)?;
+        Ok(DocumentDriftReport {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            detected_at: row.get(2)?,
+            affected_documents: serde_json::from_str(&affected).unwrap_or_default(),
+            drift_details: serde_json::from_str(&details).unwrap_or(serde_json::Value::Null),
+            drift_severity: row.get::<_, Option<String>>(5)?.unwrap_or_else(|| "medium".to_string()),
+            resolution_status: row.get::<_, Option<String>>(6)?.unwrap_or_else(|| "pending".to_string()),
+            resolution_method: row.get(7)?,
+            resolution_metadata: metadata.and_then(|s| serde_json::from_str(&s).ok()),
+            resolved_at: row.get(9)?,
+            resolved_by: row.get(10)?,
+        })
+    }
+
+    fn project_document_from_row(row: &rusqlite::Row) -> rusqlite::Result<ProjectDocument> {
+        Ok(ProjectDocument {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            document_type: row.get(2)?,
+            file_path: row.get(3)?,
+            file_hash: row.get(4)?,
+            last_modified: row.get(5)?,
+            content: row.get(6)?,
+            is_synced: row.get::<_, Option<i32>>(7)?.unwrap_or(1) != 0,
+            created_at: row.get(8)?,
+            updated_at: row.get(9)?,
+        })
+    }
+
+    // Document generation queue queries
+    pub fn enqueue_generation_jobs(&self, jobs: &[DocumentGenerationJob]) -> Result<()> {
+        self.with_transaction(|tx| {
+            for job in jobs {
+                tx.execute(
+                    "INSERT INTO document_generation_queue
+                     (id, project_id, document_type, dependencies, status, priority, retry_count, created_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![
+                        job.id,
+                        job.project_id,
+                        job.document_type,
+                        serde_json::to_string(&job.dependencies)?,
+                        job.status,
+                        job.priority,
+                        job.retry_count,
+                        job.created_at.to_rfc3339(),
+                    ],
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Jobs for a project in the order the worker considers them
+    pub fn get_generation_jobs(&self, project_id: &str) -> Result<Vec<DocumentGenerationJob>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM document_generation_queue WHERE project_id = ?1 ORDER BY priority, created_at",
+            GENERATION_JOB_COLUMNS
+        ))?;
+
+        let jobs = stmt.query_map(params![project_id], Self::generation_job_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(jobs)
+    }
+
+    /// Projects that still have pending jobs
+    pub fn get_generation_queue_projects(&self) -> Result<Vec<String>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT project_id FROM document_generation_queue WHERE status = 'pending'"
+        )?;
+
+        let projects = stmt.query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(projects)
+    }
+
+    /// Finished jobs are dropped when a project is queued again, so the queue only shows the latest run
+    pub fn clear_finished_generation_jobs(&self, project_id: &str) -> Result<()> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "DELETE FROM document_generation_queue
+             WHERE project_id = ?1 AND status IN ('completed', 'failed', 'cancelled')",
+            params![project_id],
+        )?;
+        Ok(())
+    }
+
+    /// Move a pending job to processing. Returns false if it was cancelled or claimed in the meantime.
+    pub fn claim_generation_job(&self, id: &str) -> Result<bool> {
+        let conn = self.pool.get()?;
+        let updated = conn.execute(
+            "UPDATE document_generation_queue SET status = 'processing', started_at = ?2, error_message = NULL
+             WHERE id = ?1 AND status = 'pending'",
+            params![id, chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(updated == 1)
+    }
+
+    /// Record the outcome of an attempt; a cancelled job stays cancelled
+    pub fn finish_generation_job(&self, id: &str, status: &str, error: Option<&str>, retry_count: i32) -> Result<()> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "UPDATE document_generation_queue
+             SET status = ?2, error_message = ?3, retry_count = ?4,
+                 completed_at = CASE WHEN ?2 = 'pending' THEN NULL ELSE ?5 END
+             WHERE id = ?1 AND status IN ('pending', 'processing')",
+            params![id, status, error, retry_count, chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Cancel one job, or every unfinished job of the project. Returns how many were cancelled.
+    pub fn cancel_generation_jobs(&self, project_id: &str, job_id: Option<&str>) -> Result<usize> {
+        let conn = self.pool.get()?;
+        let cancelled = conn.execute(
+            "UPDATE document_generation_queue SET status = 'cancelled', completed_at = ?3
+             WHERE project_id = ?1 AND (?2 IS NULL OR id = ?2) AND status IN ('pending', 'processing')",
+            params![project_id, job_id, chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(cancelled)
+    }
+
+    /// Jobs left processing by a previous run of the app go back to pending
+    pub fn requeue_interrupted_generation_jobs(&self) -> Result<usize> {
+        let conn = self.pool.get()?;
+        let requeued = conn.execute(
+            "UPDATE document_generation_queue SET status = 'pending' WHERE status = 'processing'",
+            [],
+        )?;
+        Ok(requeued)
+    }
+
+    fn generation_job_from_row(row: &rusqlite::Row) -> rusqlite::Result<DocumentGenerationJob> {
+        let dependencies: Option<String> = row.get(3)?;
+        Ok(DocumentGenerationJob {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            document_type: row.get(2)?,
+            dependencies: dependencies.and_then(|s| serde_json::from_str(&s).ok()).unwrap_or_default(),
+            status: row.get::<_, Option<String>>(4)?.unwrap_or_else(|| "pending".to_string()),
+            priority: row.get::<_, Option<i32>>(5)?.unwrap_or(5),
+            started_at: row.get(6)?,
+            completed_at: row.get(7)?,
+            error_message: row.get(8)?,
+            retry_count: row.get::<_, Option<i32>>(9)?.unwrap_or(0),
+            created_at: row.get(10)?,
+        })
+    }
+
+    // Focus group queries
+    pub fn get_latest_focus_group_session(&self, project_id: &str) -> Result<Option<FocusGroupSession>> {
+        let conn = self.pool.get()?;
+        let session = conn.query_row(
+            "SELECT id, project_id, session_type, total_votes_available, persona_votes, status, completed_at, created_at
+             FROM focus_group_sessions
+             WHERE project_id = ?1 AND status = 'completed'
+             ORDER BY created_at DESC LIMIT 1",
+            params![project_id],
+            |row| {
+                let votes: String = row.get(4)?;
+                Ok(FocusGroupSession {
+                    id: row.get(0)?,
+                    project_id: row.get(1)?,
+                    session_type: row.get::<_, Option<String>>(2)?.unwrap_or_else(|| "dot_voting".to_string()),
+                    total_votes_available: row.get::<_, Option<i32>>(3)?.unwrap_or(15),
+                    persona_votes: serde_json::from_str(&votes).unwrap_or_default(),
+                    status: row.get::<_, Option<String>>(5)?.unwrap_or_else(|| "completed".to_string()),
+                    completed_at: row.get(6)?,
+                    created_at: row.get(7)?,
+                })
+            },
+        ).optional()?;
+        Ok(session)
+    }
+
+    // Export history queries
+    pub fn record_export(&self, record: &ExportRecord) -> Result<()> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            &format!("INSERT INTO export_history ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)", EXPORT_RECORD_COLUMNS),
+            params![
+                record.id,
+                record.project_id,
+                record.export_format,
+                record.export_config.to_string(),
+                record.file_path,
+                record.file_size_bytes,
+                serde_json::to_string(&record.included_sections)?,
+                record.export_version,
+                record.created_at.to_rfc3339(),
+                record.created_by,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Exports of a project, newest first
+    pub fn get_exports(&self, project_id: &str) -> Result<Vec<ExportRecord>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM export_history WHERE project_id = ?1 ORDER BY created_at DESC",
+            EXPORT_RECORD_COLUMNS
+        ))?;
+
+        let exports = stmt.query_map(params![project_id], Self::export_record_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(exports)
+    }
+
+    pub fn get_export(&self, id: &str) -> Result<Option<ExportRecord>> {
+        let conn = self.pool.get()?;
+        let export = conn.query_row(
+            &format!("SELECT {} FROM export_history WHERE id = ?1", EXPORT_RECORD_COLUMNS),
+            params![id],
+            Self::export_record_from_row,
+        ).optional()?;
+        Ok(export)
+    }
+
+    fn export_record_from_row(row: &rusqlite::Row) -> rusqlite::Result<ExportRecord> {
+        let config: Option<String> = row.get(3)?;
+        let sections: Option<String> = row.get(6)?;
+        Ok(ExportRecord {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            export_format: row.get(2)?,
+            export_config: config.and_then(|s| serde_json::from_str(&s).ok()).unwrap_or(serde_json::Value::Null),
+            file_path: row.get(4)?,
+            file_size_bytes: row.get(5)?,
+            included_sections: sections.and_then(|s| serde_json::from_str(&s).ok()).unwrap_or_default(),
+            export_version: row.get(7)?,
+            created_at: row.get(8)?,
+            created_by: row.get(9)?,
+        })
+    }
+
+    // React Flow UI state queries
+    pub fn get_react_flow_state(&self, project_id: &str) -> Result<Option<ReactFlowState>> {
+        let conn = self.pool.get()?;
+        let state = conn.query_row(
+            "SELECT id, project_id, viewport, nodes, edges, minimap_config, controls_config,
+                    selection_ids, interaction_mode, canvas_locked, created_at, updated_at
+             FROM react_flow_states
+             WHERE project_id = ?1
+             ORDER BY updated_at DESC
+             LIMIT 1",
+            params![project_id],
+            |row| {
+                let json = |value: Option<String>| value.and_then(|s| serde_json::from_str(&s).ok());
+                Ok(ReactFlowState {
+                    id: row.get(0)?,
+                    project_id: row.get(1)?,
+                    viewport: json(row.get(2)?).unwrap_or(serde_json::Value::Null),
+                    nodes: json(row.get(3)?).unwrap_or_else(|| serde_json::json!([])),
+                    edges: json(row.get(4)?).unwrap_or_else(|| serde_json::json!([])),
+                    minimap_config: json(row.get(5)?),
+                    controls_config: json(row.get(6)?),
+                    selection_ids: json(row.get(7)?)
+                        .and_then(|ids| serde_json::from_value(ids).ok())
+                        .unwrap_or_default(),
+                    interaction_mode: row.get::<_, Option<String>>(8)?.unwrap_or_else(|| "default".to_string()),
+                    canvas_locked: row.get::<_, Option<i32>>(9)?.unwrap_or(0) != 0,
+                    created_at: row.get(10)?,
+                    updated_at: row.get(11)?,
+                })
+            },
+        ).optional()?;
+        Ok(state)
+    }
+
+    /// Overwrite the project's single UI state row
+    pub fn save_react_flow_state(&self, state: &ReactFlowState) -> Result<()> {
+        let viewport = state.viewport.to_string();
+        let nodes = state.nodes.to_string();
+        let edges = state.edges.to_string();
+        let minimap_config = state.minimap_config.as_ref().map(|v| v.to_string());
+        let controls_config = state.controls_config.as_ref().map(|v| v.to_string());
+        let selection_ids = serde_json::to_string(&state.selection_ids)?;
+        let canvas_locked = state.canvas_locked as i32;
+
+        self.with_transaction(|tx| {
+            let values = params![
+                state.id,
+                state.project_id,
+                viewport,
+                nodes,
+                edges,
+                minimap_config,
+                controls_config,
+                selection_ids,
+                state.interaction_mode,
+                canvas_locked,
+            ];
+            let updated = tx.execute(
+                "UPDATE react_flow_states
+                 SET viewport = ?3, nodes = ?4, edges = ?5, minimap_config = ?6, controls_config = ?7,
+                     selection_ids = ?8, interaction_mode = ?9, canvas_locked = ?10,
+                     updated_at = datetime('now')
+                 WHERE project_id = ?2",
+                values,
+            )?;
+            if updated == 0 {
+                tx.execute(
+                    "INSERT INTO react_flow_states
+                     (id, project_id, viewport, nodes, edges, minimap_config, controls_config,
+                      selection_ids, interaction_mode, canvas_locked)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                    values,
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    pub fn get_ui_selection_state(&self, project_id: &str) -> Result<Option<UiSelectionState>> {
+        let conn = self.pool.get()?;
+        let state = conn.query_row(
+            "SELECT id, project_id, selection_type, selected_items, selection_metadata, created_at, updated_at
+             FROM ui_selection_states
+             WHERE project_id = ?1
+             ORDER BY updated_at DESC
+             LIMIT 1",
+            params![project_id],
+            |row| {
+                let items: String = row.get(3)?;
+                let metadata: Option<String> = row.get(4)?;
+                Ok(UiSelectionState {
+                    id: row.get(0)?,
+                    project_id: row.get(1)?,
+                    selection_type: row.get(2)?,
+                    selected_items: serde_json::from_str(&items).unwrap_or_else(|_| serde_json::json!([])),
+                    selection_metadata: metadata.and_then(|s| serde_json::from_str(&s).ok()),
+                    created_at: row.get(5)?,
+                    updated_at: row.get(6)?,
+                })
+            },
+        ).optional()?;
+        Ok(state)
+    }
+
+    pub fn save_ui_selection_state(&self, state: &UiSelectionState) -> Result<()> {
+        let selected_items = state.selected_items.to_string();
+        let selection_metadata = state.selection_metadata.as_ref().map(|v| v.to_string());
+
+        self.with_transaction(|tx| {
+            let values = params![
+                state.id,
+                state.project_id,
+                state.selection_type,
+                selected_items,
+                selection_metadata,
+            ];
+            let updated = tx.execute(
+                "UPDATE ui_selection_states
+                 SET selection_type = ?3, selected_items = ?4, selection_metadata = ?5,
+                     updated_at = datetime('now')
+                 WHERE project_id = ?2",
+                values,
+            )?;
+            if updated == 0 {
+                tx.execute(
+                    "INSERT INTO ui_selection_states (id, project_id, selection_type, selected_items, selection_metadata)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    values,
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    pub fn create_ui_animation(&self, animation: &UiAnimationState) -> Result<()> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "INSERT INTO ui_animation_states
+             (id, project_id, element_id, element_type, animation_type, animation_config,
+              start_state, end_state, duration_ms, is_active)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                animation.id,
+                animation.project_id,
+                animation.element_id,
+                animation.element_type,
+                animation.animation_type,
+                animation.animation_config.to_string(),
+                animation.start_state.as_ref().map(|v| v.to_string()),
+                animation.end_state.as_ref().map(|v| v.to_string()),
+                animation.duration_ms,
+                animation.is_active as i32,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_active_ui_animations(&self, project_id: &str) -> Result<Vec<UiAnimationState>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, element_id, element_type, animation_type, animation_config,
+                    start_state, end_state, duration_ms, is_active, created_at
+             FROM ui_animation_states
+             WHERE project_id = ?1 AND is_active = 1
+             ORDER BY created_at"
+        )?;
+
+        let animations = stmt.query_map(params![project_id], |row| {
+            let config: String = row.get(5)?;
+            let json = |value: Option<String>| value.and_then(|s| serde_json::from_str(&s).ok());
+            Ok(UiAnimationState {
+                id: row.get(0)?,
+                project_id: row.get(1)?,
+                element_id: row.get(2)?,
+                element_type: row.get(3)?,
+                animation_type: row.get(4)?,
+                animation_config: serde_json::from_str(&config).unwrap_or(serde_json::Value::Null),
+                start_state: json(row.get(6)?),
+                end_state: json(row.get(7)?),
+                duration_ms: row.get(8)?,
+                is_active: row.get::<_, i32>(9)? != 0,
+                created_at: row.get(10)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+        Ok(animations)
+    }
+
+    pub fn finish_ui_animation(&self, animation_id: &str) -> Result<()> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "UPDATE ui_animation_states SET is_active = 0 WHERE id = ?1",
+            params![animation_id],
+        )?;
+        Ok(())
+    }
+
+    /// Store the transition used between two steps, replacing the previous one
+    pub fn save_canvas_transition(&self, transition: &CanvasTransition) -> Result<()> {
+        self.with_transaction(|tx| {
+            tx.execute(
+                "UPDATE canvas_transitions SET is_active = 0
+                 WHERE project_id = ?1 AND from_step = ?2 AND to_step = ?3",
+                params![transition.project_id, transition.from_step, transition.to_step],
+            )?;
+            tx.execute(
+                "INSERT INTO canvas_transitions
+                 (id, project_id, from_step, to_step, transition_type, transition_config, is_active)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    transition.id,
+                    transition.project_id,
+                    transition.from_step,
+                    transition.to_step,
+                    transition.transition_type,
+                    transition.transition_config.as_ref().map(|v| v.to_string()),
+                    transition.is_active as i32,
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    pub fn get_canvas_transition(&self, project_id: &str, from_step: &str, to_step: &str) -> Result<Option<CanvasTransition>> {
+        let conn = self.pool.get()?;
+        let transition = conn.query_row(
+            "SELECT id, project_id, from_step, to_step, transition_type, transition_config, is_active, created_at
+             FROM canvas_transitions
+             WHERE project_id = ?1 AND from_step = ?2 AND to_step = ?3 AND is_active = 1
+             ORDER BY created_at DESC
+             LIMIT 1",
+            params![project_id, from_step, to_step],
+            |row| {
+                let config: Option<String> = row.get(5)?;
+                Ok(CanvasTransition {
+                    id: row.get(0)?,
+                    project_id: row.get(1)?,
+                    from_step: row.get(2)?,
+                    to_step: row.get(3)?,
+                    transition_type: row.get(4)?,
+                    transition_config: config.and_then(|s| serde_json::from_str(&s).ok()),
+                    is_active: row.get::<_, i32>(6)? != 0,
+                    created_at: row.get(7)?,
+                })
+            },
+        ).optional()?;
+        Ok(transition)
+    }
+
+    /// Drop the cached summary of whichever project `project_query` resolves `key` to.
+    /// Called from every write that changes what a recent flow displays.
+    fn invalidate_recent_flows(conn: &rusqlite::Connection, project_query: &str, key: &str) -> Result<()> {
+        conn.execute(
+            &format!("DELETE FROM recent_flows_cache WHERE project_id IN ({})", project_query),
+            params![key],
+        )?;
+        Ok(())
+    }
+
     // Helper function to get locked item IDs
     pub fn get_locked_persona_ids(&self, core_problem_id: &str) -> Result<Vec<String>> {
         let conn = self.pool.get()?;
@@ -11,6 +11,7 @@ use commands::*;
 use commands::data_sync::{check_migration_status, run_database_migrations, get_detailed_migration_status};
 use db::init_db;
 use log::info;
+use tauri::Manager;
 
 #[tokio::main]
 async fn main() {
@@ -54,6 +55,27 @@ async fn main() {
             load_canvas_state,
             export_canvas_image,
             calculate_layout,
+            get_canvas_history,
+            restore_canvas_version,
+            create_canvas_checkpoint,
+            branch_canvas,
+            apply_canvas_retention,
+            diff_canvas_versions,
+            apply_canvas_patch,
+            reconcile_canvas,
+            regenerate_canvas_from_domain,
+            apply_canvas_deletions,
+            // UI state commands
+            save_ui_state,
+            load_ui_state,
+            flush_ui_state,
+            save_ui_selection,
+            load_ui_selection,
+            start_ui_animation,
+            get_active_animations,
+            finish_ui_animation,
+            save_canvas_transition,
+            get_canvas_transition,
             // Data sync commands
             populate_sqlite_test_data,
             verify_test_data_consistency,
@@ -72,6 +94,62 @@ async fn main() {
             write_to_terminal,
             close_terminal_session,
             resize_terminal,
+            list_terminal_sessions,
+            attach_terminal_session,
+            start_terminal_recording,
+            stop_terminal_recording,
+            list_terminal_recordings,
+            replay_terminal_recording,
+            run_terminal_command,
+            get_terminal_profiles,
+            save_terminal_profile,
+            delete_terminal_profile,
+            get_terminal_command_history,
+            // Gherkin feature commands
+            generate_feature_files,
+            import_feature_files,
+            // System design commands
+            generate_system_design,
+            get_system_design,
+            get_architecture_version,
+            // Schema export commands
+            validate_database_schema,
+            export_database_schema,
+            // Design token commands
+            export_design_tokens,
+            // Atomic component commands
+            validate_component_tree,
+            scaffold_components,
+            // Repository setup commands
+            setup_repository,
+            // Workflow commands
+            advance_project_step,
+            get_workflow_progress,
+            // Recent flow commands
+            get_recent_flows,
+            get_project_progress,
+            mark_flow_opened,
+            update_flow_status,
+            // Document commands
+            materialize_project_documents,
+            render_project_documents,
+            get_project_documents,
+            read_project_document,
+            watch_project_documents,
+            unwatch_project_documents,
+            detect_document_drift,
+            get_document_drift_reports,
+            resolve_document_drift,
+            enqueue_project_documents,
+            cancel_document_generation,
+            get_document_generation_queue,
+            // Report export commands
+            export_project_report,
+            // Export history commands
+            list_exports,
+            re_export,
+            save_export_file,
+            download_export,
             // LangGraph tools
             tools::call_llm,
             tools::query_local_sqlite,
@@ -83,7 +161,9 @@ async fn main() {
             tools::list_langgraph_workflows,
             tools::get_langgraph_workflow_definition,
         ])
-        .setup(|_app| {
+        .setup(|app| {
+            let pool = app.state::<db::DbPool>().inner().clone();
+            document_queue::start_generation_worker(app.handle().clone(), pool);
             info!("GoldiDocs setup complete");
             Ok(())
         })
@@ -19,7 +19,7 @@ export const ConnectedSidebar: React.FC = () => {
   const [activeWorkspaceId, setActiveWorkspaceId] = useState<string | undefined>();
   const [workspaceDirectories, setWorkspaceDirectories] = useState<Record<string, string>>({});
   const [terminalVisible, setTerminalVisible] = useState(false);
-  const [terminalWorkingDir, setTerminalWorkingDir] = useState<string>('/');
+  const [terminalWorkingDir, setTerminalWorkingDir] = useState<string | undefined>();
 
   useEffect(() => {
     const fetchWorkspaces = async () => {
@@ -283,6 +283,7 @@ export const ConnectedSidebar: React.FC = () => {
         />
         <Terminal 
           workingDirectory={terminalWorkingDir}
+          projectId={projectId || undefined}
           isVisible={terminalVisible}
           onClose={() => setTerminalVisible(false)}
         />
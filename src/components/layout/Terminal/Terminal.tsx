@@ -6,14 +6,32 @@ import { invoke } from '@tauri-apps/api/core';
 import '@xterm/xterm/css/xterm.css';
 import './Terminal.css';
 
+interface TerminalOutput {
+  data: string;
+  offset: number;
+}
+
+interface TerminalSessionInfo {
+  session_id: string;
+  running: boolean;
+}
+
+interface TerminalAttach {
+  scrollback: string;
+  offset: number;
+}
+
 interface TerminalProps {
+  /** Must be inside a workspace folder; without it the backend picks the project or workspace folder */
   workingDirectory?: string;
+  projectId?: string;
   isVisible: boolean;
   onClose: () => void;
 }
 
 export const Terminal: React.FC<TerminalProps> = ({ 
-  workingDirectory = '/', 
+  workingDirectory, 
+  projectId,
   isVisible,
   onClose 
 }) => {
@@ -88,28 +106,55 @@ export const Terminal: React.FC<TerminalProps> = ({
       if (cleanupFn) cleanupFn();
       term.dispose();
     };
-  }, [isVisible, workingDirectory]);
+  }, [isVisible, workingDirectory, projectId]);
 
-  const initializeTerminal = async (term: XTerm, cwd: string): Promise<(() => void) | undefined> => {
+  const initializeTerminal = async (term: XTerm, cwd?: string): Promise<(() => void) | undefined> => {
     // Check if we're in Tauri
     if (window.__TAURI__) {
       try {
-        // Start PTY session in Tauri backend
-        const sessionId = await invoke<string>('start_terminal_session', { cwd });
+        // Reattach to the PTY this view was using before a reload, otherwise start a new one
+        const storageKey = `terminal-session:${cwd ?? projectId ?? 'default'}`;
+        const previousId = sessionStorage.getItem(storageKey);
+        const sessions = await invoke<TerminalSessionInfo[]>('list_terminal_sessions');
+        const existing = sessions.find(s => s.session_id === previousId && s.running);
+        const sessionId = existing
+          ? existing.session_id
+          : await invoke<string>('start_terminal_session', { cwd: cwd ?? null, projectId: projectId ?? null });
+        sessionStorage.setItem(storageKey, sessionId);
         setSessionId(sessionId);
 
-        // Set up message handlers
-        const unlisten = await window.__TAURI__.event.listen('terminal-output', (event: any) => {
-          term.write(event.payload);
+        // Set up message handlers; events are namespaced by session id.
+        // While attaching, live output is held back and anything the replay covers is dropped.
+        let replayedOffset: number | null = existing ? null : 0;
+        const held: TerminalOutput[] = [];
+        const unlisten = await window.__TAURI__.event.listen(`terminal-output-${sessionId}`, (event: any) => {
+          const output = event.payload as TerminalOutput;
+          if (replayedOffset === null) {
+            held.push(output);
+          } else if (output.offset > replayedOffset) {
+            term.write(output.data);
+          }
+        });
+
+        if (existing) {
+          const attach = await invoke<TerminalAttach>('attach_terminal_session', { sessionId });
+          term.write(attach.scrollback);
+          replayedOffset = attach.offset;
+          held
+            .filter(output => output.offset > attach.offset)
+            .forEach(output => term.write(output.data));
+        }
+
+        const unlistenExit = await window.__TAURI__.event.listen(`terminal-exit-${sessionId}`, (event: any) => {
+          const { exit_code } = event.payload;
+          term.writeln(`\r\n[Process exited${exit_code != null ? ` with code ${exit_code}` : ''}]`);
         });
 
-        const unlistenClosed = await window.__TAURI__.event.listen('terminal-closed', (event: any) => {
-          if (event.payload === sessionId) {
-            term.writeln('\r\n[Terminal session closed]');
-          }
+        const unlistenClosed = await window.__TAURI__.event.listen(`terminal-closed-${sessionId}`, () => {
+          term.writeln('\r\n[Terminal session closed]');
         });
 
-        const unlistenError = await window.__TAURI__.event.listen('terminal-error', (event: any) => {
+        const unlistenError = await window.__TAURI__.event.listen(`terminal-error-${sessionId}`, (event: any) => {
           term.writeln(`\r\n[Error: ${event.payload}]`);
         });
 
@@ -118,23 +163,32 @@ export const Terminal: React.FC<TerminalProps> = ({
           invoke('write_to_terminal', { sessionId, data });
         });
 
+        // Keep the PTY size in step with the fitted terminal
+        const onResizeDispose = term.onResize(({ cols, rows }) => {
+          invoke('resize_terminal', { sessionId, cols, rows });
+        });
+        invoke('resize_terminal', { sessionId, cols: term.cols, rows: term.rows });
+
         // Clean up on unmount
         return () => {
           unlisten();
+          unlistenExit();
           unlistenClosed();
           unlistenError();
           onDataDispose.dispose();
+          onResizeDispose.dispose();
           invoke('close_terminal_session', { sessionId });
+          sessionStorage.removeItem(storageKey);
           setSessionId(null);
         };
       } catch (error) {
         console.error('Failed to initialize Tauri terminal:', error);
         // Fall back to simulated terminal
-        initializeSimulatedTerminal(term, cwd);
+        initializeSimulatedTerminal(term, cwd ?? '~');
       }
     } else {
       // Browser environment - use simulated terminal
-      initializeSimulatedTerminal(term, cwd);
+      initializeSimulatedTerminal(term, cwd ?? '~');
     }
   };
 
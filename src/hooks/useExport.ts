@@ -1,11 +1,20 @@
 import { useState, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
-import type { ExportOptions, ExportHistory } from '../types/export.types';
+import type { ExportOptions, ExportHistory, ExportRecord } from '../types/export.types';
 import { markdownExporter } from '../utils/exportGenerators/markdownExporter';
 import { jsonExporter } from '../utils/exportGenerators/jsonExporter';
 import { pdfExporter } from '../utils/exportGenerators/pdfExporter';
 import { zipExporter } from '../utils/exportGenerators/zipExporter';
 
+const toHistoryEntry = (record: ExportRecord): ExportHistory => ({
+  id: record.id,
+  projectId: record.project_id,
+  format: record.export_format as ExportHistory['format'],
+  template: record.export_config?.options?.template ?? '',
+  timestamp: record.created_at,
+  fileSize: record.file_size_bytes ?? undefined
+});
+
 export const useExport = () => {
   const [isExporting, setIsExporting] = useState(false);
   const [exportHistory, setExportHistory] = useState<ExportHistory[]>([]);
@@ -43,27 +52,19 @@ export const useExport = () => {
           throw new Error(`Unsupported export format: ${options.format}`);
       }
 
-      // Save the export file
-      const timestamp = new Date().toISOString();
+      // Save the export file; the backend records it in the export history
       const fileName = `${projectName}-${options.format}-${Date.now()}`;
       
-      await invoke('save_export_file', {
+      const record = await invoke<ExportRecord>('save_export_file', {
+        projectId,
         fileName,
         content: exportResult.content,
-        format: options.format
-      });
-
-      // Add to history
-      const historyEntry: ExportHistory = {
-        id: crypto.randomUUID(),
-        projectId,
         format: options.format,
-        template: options.template,
-        timestamp,
-        fileSize: exportResult.size
-      };
+        sections: options.sections,
+        config: { template: options.template, customBranding: options.customBranding }
+      });
 
-      setExportHistory(prev => [historyEntry, ...prev]);
+      setExportHistory(prev => [toHistoryEntry(record), ...prev]);
       
       return exportResult;
     } catch (err) {
@@ -75,6 +76,11 @@ export const useExport = () => {
     }
   }, []);
 
+  const loadExportHistory = useCallback(async (projectId: string) => {
+    const records = await invoke<ExportRecord[]>('list_exports', { projectId });
+    setExportHistory(records.map(toHistoryEntry));
+  }, []);
+
   const downloadExport = useCallback(async (historyId: string) => {
     const entry = exportHistory.find(h => h.id === historyId);
     if (!entry) {
@@ -82,7 +88,8 @@ export const useExport = () => {
     }
 
     try {
-      await invoke('download_export', { historyId });
+      // Copies the file into the downloads folder and returns where it went
+      return await invoke<string>('download_export', { historyId });
     } catch (err) {
       const errorMessage = err instanceof Error ? err.message : 'Download failed';
       setError(errorMessage);
@@ -92,6 +99,7 @@ export const useExport = () => {
 
   return {
     exportProject,
+    loadExportHistory,
     downloadExport,
     isExporting,
     exportHistory,
@@ -1,5 +1,6 @@
 import { invoke } from '@tauri-apps/api/core'
 import type { Workspace, CanvasState } from '@/types/database.types'
+import type { ExportRecord } from '@/types/export.types'
 
 export const tauriAPI = {
   async getAppState() {
@@ -22,6 +23,41 @@ export const tauriAPI = {
     return invoke<string>('analyze_problem', { problem })
   },
 
+  // Project documents are rendered by the Rust backend so they match what is written to disk
+  async renderProjectDocuments(projectId: string, documentTypes?: string[]) {
+    return invoke<{
+      project_name: string
+      documents: { document_type: string; file_name: string; content: string }[]
+    }>(
+      'render_project_documents',
+      { projectId, documentTypes }
+    )
+  },
+
+  // PDF/DOCX project reports are written to the project's exports folder and logged in export history
+  async exportProjectReport(request: {
+    project_id: string
+    format: 'pdf' | 'docx'
+    sections?: string[]
+    created_by?: string
+  }) {
+    return invoke<ExportRecord>('export_project_report', { request })
+  },
+
+  async listExports(projectId: string) {
+    return invoke<ExportRecord[]>('list_exports', { projectId })
+  },
+
+  // Runs an earlier export again with its stored config; `output` is what the original command returns
+  async reExport(exportId: string) {
+    return invoke<{ export: ExportRecord; output: unknown }>('re_export', { exportId })
+  },
+
+  // `destination` is a folder: the downloads folder (the default) or one inside a workspace
+  async downloadExport(historyId: string, destination?: string) {
+    return invoke<string>('download_export', { historyId, destination })
+  },
+
   // LangGraph bridge functions - connect frontend to Rust backend tools
   async callLlm(request: {
     prompt: string;
@@ -29,6 +29,21 @@ export interface ExportHistory {
   downloadUrl?: string;
 }
 
+// A row of the backend's export history
+export interface ExportRecord {
+  id: string;
+  project_id: string;
+  export_format: string;
+  // Arguments of the exporting command, with `kind` naming the command
+  export_config: { kind?: string; options?: { template?: string }; [key: string]: unknown } | null;
+  file_path: string | null;
+  file_size_bytes: number | null;
+  included_sections: string[];
+  export_version: string | null;
+  created_at: string;
+  created_by: string | null;
+}
+
 export interface ShareLink {
   id: string;
   url: string;